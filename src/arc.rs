@@ -0,0 +1,263 @@
+use std::{
+    collections::{BTreeMap, HashSet},
+    fmt,
+    sync::Arc,
+};
+
+use crate::{
+    error::JsonError,
+    lexer::Lexer,
+    number::Number,
+    parser::Parser,
+    pointer::{array_index, ToPointer},
+    value::{RawValue, Value},
+};
+
+/// 同じ文字列のキーを 1 つの `Arc<str>` にまとめる
+///
+/// 同じキーが何度も現れる文書 (ログの配列など) で、キーごとの確保を避ける。
+/// 複数の文書の解析で使い回すこともできる
+#[derive(Debug, Clone, Default)]
+pub struct KeyInterner {
+    keys: HashSet<Arc<str>>,
+}
+
+impl KeyInterner {
+    pub fn new() -> KeyInterner {
+        KeyInterner::default()
+    }
+
+    /// `key` と等しい共有の文字列を返す。初めてのキーなら確保して覚える
+    pub fn intern(&mut self, key: &str) -> Arc<str> {
+        match self.keys.get(key) {
+            Some(key) => Arc::clone(key),
+            None => {
+                let key: Arc<str> = Arc::from(key);
+                self.keys.insert(Arc::clone(&key));
+                key
+            }
+        }
+    }
+
+    /// 覚えているキーの数
+    pub fn len(&self) -> usize {
+        self.keys.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+}
+
+/// 文字列と部分木を `Arc` で共有する、読み取り専用の JSON の値
+///
+/// `clone` は参照カウントを増やすだけなので、大きな文書や部分木を O(1) で複製し、
+/// スレッドをまたいで渡せる。`Value` とは `From` で相互に変換する
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum ArcValue {
+    String(Arc<str>),
+    Number(Number),
+    Bool(bool),
+    #[default]
+    Null,
+    Array(Arc<[ArcValue]>),
+    Object(Arc<BTreeMap<Arc<str>, ArcValue>>),
+    Raw(Arc<RawValue>),
+}
+
+impl ArcValue {
+    /// JSON を解析する。オブジェクトのキーは `interner` で共有する
+    pub fn parse_interned(json: &str, interner: &mut KeyInterner) -> Result<ArcValue, JsonError> {
+        Ok(Parser::new(Lexer::new(json).tokenize()?).parse_interned_document(interner)?)
+    }
+
+    /// オブジェクトのキーで値を返す。なければ None
+    pub fn get(&self, key: &str) -> Option<&ArcValue> {
+        self.as_object()?.get(key)
+    }
+
+    /// 配列の添字で値を返す。なければ None
+    pub fn get_index(&self, index: usize) -> Option<&ArcValue> {
+        self.as_array()?.get(index)
+    }
+
+    /// JSON Pointer (RFC 6901) の指す値を返す
+    pub fn pointer<P: ToPointer + ?Sized>(&self, pointer: &P) -> Option<&ArcValue> {
+        let pointer = pointer.to_pointer().ok()?;
+        pointer
+            .tokens()
+            .iter()
+            .try_fold(self, |value, token| match value {
+                ArcValue::Object(object) => object.get(token.as_str()),
+                ArcValue::Array(array) => array.get(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            ArcValue::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            ArcValue::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            ArcValue::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&[ArcValue]> {
+        match self {
+            ArcValue::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<Arc<str>, ArcValue>> {
+        match self {
+            ArcValue::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, ArcValue::Null)
+    }
+
+    /// 共有をやめた `Value` に複製する
+    pub fn to_value(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+impl From<Value> for ArcValue {
+    fn from(value: Value) -> ArcValue {
+        match value {
+            Value::String(s) => ArcValue::String(s.into()),
+            Value::Number(n) => ArcValue::Number(n),
+            Value::Bool(b) => ArcValue::Bool(b),
+            Value::Null => ArcValue::Null,
+            Value::Array(array) => ArcValue::Array(array.into_iter().map(ArcValue::from).collect()),
+            Value::Object(object) => ArcValue::Object(Arc::new(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (Arc::from(key), ArcValue::from(value)))
+                    .collect(),
+            )),
+            Value::Raw(raw) => ArcValue::Raw(Arc::new(raw)),
+        }
+    }
+}
+
+impl From<&ArcValue> for Value {
+    fn from(value: &ArcValue) -> Value {
+        match value {
+            ArcValue::String(s) => Value::String(s.to_string()),
+            ArcValue::Number(n) => Value::Number(n.clone()),
+            ArcValue::Bool(b) => Value::Bool(*b),
+            ArcValue::Null => Value::Null,
+            ArcValue::Array(array) => Value::Array(array.iter().map(Value::from).collect()),
+            ArcValue::Object(object) => Value::Object(
+                object
+                    .iter()
+                    .map(|(key, value)| (key.to_string(), Value::from(value)))
+                    .collect(),
+            ),
+            ArcValue::Raw(raw) => Value::Raw(RawValue::clone(raw)),
+        }
+    }
+}
+
+impl From<ArcValue> for Value {
+    fn from(value: ArcValue) -> Value {
+        Value::from(&value)
+    }
+}
+
+/// `Value` に複製して表示する
+impl fmt::Display for ArcValue {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.to_value(), f)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::thread;
+
+    use super::*;
+
+    #[test]
+    fn test_arc_value() {
+        let value: Value = r#"{"a": [1, {"b": "x"}], "c": null}"#.parse().unwrap();
+        let shared = ArcValue::from(value.clone());
+        assert_eq!(
+            shared.pointer("/a/1/b").and_then(ArcValue::as_str),
+            Some("x")
+        );
+        assert_eq!(
+            shared.get("a").and_then(|a| a.get_index(0)),
+            Some(&ArcValue::Number(Number::from(1)))
+        );
+        assert!(shared.get("c").is_some_and(ArcValue::is_null));
+        assert_eq!(shared.to_value(), value);
+        assert_eq!(shared.to_string(), value.to_string());
+
+        // 複製しても部分木は共有される
+        let copy = shared.clone();
+        match (shared.get("a"), copy.get("a")) {
+            (Some(ArcValue::Array(a)), Some(ArcValue::Array(b))) => assert!(Arc::ptr_eq(a, b)),
+            _ => panic!("an array is expected"),
+        }
+
+        let handle = thread::spawn(move || copy.pointer("/a/0").cloned());
+        assert_eq!(
+            handle.join().unwrap(),
+            Some(ArcValue::Number(Number::from(1)))
+        );
+    }
+
+    #[test]
+    fn test_parse_interned() {
+        let json =
+            r#"[{"level": "info", "ts": 1}, {"level": "warn", "ts": 2, "extra": {"level": 3}}]"#;
+        let mut interner = KeyInterner::new();
+        let value = ArcValue::parse_interned(json, &mut interner).unwrap();
+        assert_eq!(value.to_value(), json.parse::<Value>().unwrap());
+        assert_eq!(interner.len(), 3);
+
+        // 同じキーは同じ確保を指す
+        let key = |pointer: &str| {
+            let object = value
+                .pointer(pointer)
+                .and_then(ArcValue::as_object)
+                .unwrap();
+            Arc::clone(object.keys().find(|k| &***k == "level").unwrap())
+        };
+        assert!(Arc::ptr_eq(&key("/0"), &key("/1")));
+        assert!(Arc::ptr_eq(&key("/0"), &key("/1/extra")));
+        assert!(Arc::ptr_eq(&key("/0"), &interner.intern("level")));
+
+        assert!(ArcValue::parse_interned(r#"{"a" 1}"#, &mut interner).is_err());
+        assert!(ArcValue::parse_interned("[1,", &mut interner).is_err());
+        assert!(ArcValue::parse_interned("[1] 2", &mut interner).is_err());
+        assert!(ArcValue::parse_interned(r#"{"a":1} }"#, &mut interner).is_err());
+        // エラーの文言は `Value` の解析と同じ
+        for json in ["[1 2]", r#"{"a": 1 "b": 2}"#, r#"{1: 2}"#] {
+            let e = ArcValue::parse_interned(json, &mut interner).unwrap_err();
+            assert_eq!(
+                e.to_string(),
+                json.parse::<Value>().unwrap_err().to_string()
+            );
+        }
+    }
+}
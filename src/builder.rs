@@ -0,0 +1,88 @@
+use std::collections::BTreeMap;
+
+use crate::value::Value;
+
+/// オブジェクトを組み立てる
+///
+/// `Value::object().field("a", 1).field("b", true).build()` のように使う
+#[derive(Debug, Clone, Default)]
+pub struct ObjectBuilder {
+    object: BTreeMap<String, Value>,
+}
+
+impl ObjectBuilder {
+    pub fn new() -> ObjectBuilder {
+        Self::default()
+    }
+
+    /// キーと値を加える。同じキーは後から加えたものが残る
+    pub fn field(mut self, key: impl Into<String>, value: impl Into<Value>) -> ObjectBuilder {
+        self.object.insert(key.into(), value.into());
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Object(self.object)
+    }
+}
+
+/// 配列を組み立てる
+///
+/// `Value::array().push(1).push("a").build()` のように使う
+#[derive(Debug, Clone, Default)]
+pub struct ArrayBuilder {
+    array: Vec<Value>,
+}
+
+impl ArrayBuilder {
+    pub fn new() -> ArrayBuilder {
+        Self::default()
+    }
+
+    /// 末尾に要素を加える
+    pub fn push(mut self, value: impl Into<Value>) -> ArrayBuilder {
+        self.array.push(value.into());
+        self
+    }
+
+    pub fn build(self) -> Value {
+        Value::Array(self.array)
+    }
+}
+
+/// 入れ子の builder は `build` せずに渡せる
+impl From<ObjectBuilder> for Value {
+    fn from(builder: ObjectBuilder) -> Value {
+        builder.build()
+    }
+}
+
+impl From<ArrayBuilder> for Value {
+    fn from(builder: ArrayBuilder) -> Value {
+        builder.build()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_builder() {
+        let value = Value::object()
+            .field("a", 1)
+            .field("b", true)
+            .field("c", Value::array().push("x").push(Value::Null))
+            .field("d", Value::object())
+            .build();
+        let expected: Value = r#"{"a": 1, "b": true, "c": ["x", null], "d": {}}"#.parse().unwrap();
+        assert_eq!(value, expected);
+
+        let mut builder = Value::array();
+        for i in 0..3 {
+            builder = builder.push(Value::object().field("id", i));
+        }
+        let expected: Value = r#"[{"id": 0}, {"id": 1}, {"id": 2}]"#.parse().unwrap();
+        assert_eq!(builder.build(), expected);
+    }
+}
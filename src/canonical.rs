@@ -0,0 +1,129 @@
+//! RFC 8785 (JSON Canonicalization Scheme) に従った正規の JSON
+//!
+//! 同じ値からは常に同じバイト列ができるので、他の JCS の実装と同じハッシュや署名を得られる
+
+use std::fmt;
+
+use crate::{number::format_f64, printer::JsonPrinter, value::Value};
+
+/// 正規の JSON にできない値のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub struct CanonicalError {
+    pub msg: String,
+}
+
+impl CanonicalError {
+    fn new(msg: &str) -> CanonicalError {
+        CanonicalError {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for CanonicalError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for CanonicalError {}
+
+/// `value` を JCS の正規の JSON の文字列にする
+///
+/// 空白を入れず、キーは UTF-16 のコード単位の順に並べ、数値は `f64` として ECMAScript と同じ
+/// 最短の表記にする (2^53 を超える整数は丸められる)。文字列は `"` と `\` と制御文字だけを
+/// エスケープする。NaN や無限大、解析できない未解析の部分木はエラー
+pub fn to_canonical_string(value: &Value) -> Result<String, CanonicalError> {
+    let mut buf = String::new();
+    write_canonical(&mut buf, value)?;
+    Ok(buf)
+}
+
+fn write_canonical(buf: &mut String, value: &Value) -> Result<(), CanonicalError> {
+    match value {
+        Value::Null => buf.push_str("null"),
+        Value::Bool(b) => buf.push_str(if *b { "true" } else { "false" }),
+        Value::Number(n) => {
+            let n = n.as_f64();
+            if !n.is_finite() {
+                return Err(CanonicalError::new(
+                    "error: NaN and Infinity cannot be canonicalized",
+                ));
+            }
+            // -0 も 0 と書く
+            buf.push_str(&format_f64(if n == 0.0 { 0.0 } else { n }));
+        }
+        Value::String(s) => write_string(buf, s),
+        Value::Raw(raw) => {
+            let value = raw
+                .parse()
+                .map_err(|_| CanonicalError::new("error: invalid raw JSON"))?;
+            write_canonical(buf, &value)?;
+        }
+        Value::Array(array) => {
+            buf.push('[');
+            for (i, value) in array.iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_canonical(buf, value)?;
+            }
+            buf.push(']');
+        }
+        Value::Object(object) => {
+            // BTreeMap は UTF-8 のバイト順なので、U+E000 以上と U+10000 以上の順が入れ替わる
+            let mut entries = object.iter().collect::<Vec<_>>();
+            entries.sort_by(|(a, _), (b, _)| a.encode_utf16().cmp(b.encode_utf16()));
+            buf.push('{');
+            for (i, (key, value)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    buf.push(',');
+                }
+                write_string(buf, key);
+                buf.push(':');
+                write_canonical(buf, value)?;
+            }
+            buf.push('}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(buf: &mut String, s: &str) {
+    // String への書き込みは失敗しない
+    let _ = JsonPrinter::write_escaped(buf, s, &Default::default());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_canonical_numbers() {
+        let value = parse(
+            "[333333333.33333329, 1E30, 4.50, 2e-3, 0.000000000000000000000000001, -0.0, 100]",
+        );
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            "[333333333.3333333,1e+30,4.5,0.002,1e-27,0,100]"
+        );
+        let nan = Value::from(f64::NAN);
+        assert!(to_canonical_string(&nan).is_err());
+    }
+
+    #[test]
+    fn test_canonical_keys() {
+        let value = parse(
+            r#"{"\u20ac": "Euro Sign", "\r": "Carriage Return", "\ufb33": "Hebrew",
+                "1": "One", "\ud83d\ude00": "Emoji", "\u0080": "Control", "\u00f6": "o",
+                "nested": {"b": [true, null], "a": "\u001f\u00e9\"\\/"}}"#,
+        );
+        assert_eq!(
+            to_canonical_string(&value).unwrap(),
+            "{\"\\r\":\"Carriage Return\",\"1\":\"One\",\"nested\":{\"a\":\"\\u001fé\\\"\\\\/\",\
+             \"b\":[true,null]},\"\u{80}\":\"Control\",\"ö\":\"o\",\"€\":\"Euro Sign\",\
+             \"😀\":\"Emoji\",\"\u{fb33}\":\"Hebrew\"}"
+        );
+    }
+}
@@ -0,0 +1,215 @@
+use crate::{
+    error::JsonError,
+    lexer::{LexState, Lexer, LexerOptions, Suspended, Token},
+};
+
+/// 入力をチャンクごとに受け取る字句解析
+///
+/// チャンクの終端で読みかけの token (文字列、数値、リテラルなど) は読み終えた部分を状態として
+/// 次のチャンクまで持ち越し、完結した token だけを返す。入力を先頭から読み直すことはない。
+pub struct ChunkedLexer {
+    options: LexerOptions,
+    buf: String, // まだ token にしていない入力
+    suspended: Suspended,
+    state: LexState,
+    input_bytes: usize,
+    failed: bool,
+}
+
+impl ChunkedLexer {
+    pub fn new() -> ChunkedLexer {
+        Self::with_options(LexerOptions::default())
+    }
+
+    pub fn with_options(options: LexerOptions) -> ChunkedLexer {
+        ChunkedLexer {
+            options,
+            buf: String::new(),
+            suspended: Suspended::default(),
+            state: LexState::Ready,
+            input_bytes: 0,
+            failed: false,
+        }
+    }
+
+    /// 次のチャンクを読み、完結した `Token` を返す。エラーの後は何も返さない
+    pub fn feed(&mut self, chunk: &str) -> Result<Vec<Token<'static>>, JsonError> {
+        self.buf.push_str(chunk);
+        self.input_bytes += chunk.len();
+        self.lex(true)
+    }
+
+    /// 入力の終わりを伝え、残りの `Token` を返す。読みかけの token はエラーになる
+    pub fn finish(&mut self) -> Result<Vec<Token<'static>>, JsonError> {
+        self.lex(false)
+    }
+
+    /// 持ち越している読みかけの token の種類
+    pub fn state(&self) -> LexState {
+        self.state
+    }
+
+    fn lex(&mut self, partial: bool) -> Result<Vec<Token<'static>>, JsonError> {
+        if self.failed {
+            return Ok(vec![]);
+        }
+        let mut lexer = Lexer::resume(
+            &self.buf,
+            self.options.clone(),
+            std::mem::take(&mut self.suspended),
+            self.input_bytes,
+            partial,
+        );
+        let tokens = lexer
+            .by_ref()
+            .map(|token| token.map(Token::into_owned))
+            .collect::<Result<Vec<_>, _>>();
+        let (consumed, suspended, state) = lexer.suspend();
+        self.buf.drain(..consumed);
+        self.suspended = suspended;
+        self.state = state;
+        self.failed = tokens.is_err();
+        tokens
+    }
+}
+
+impl Default for ChunkedLexer {
+    fn default() -> ChunkedLexer {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// `json` をあらゆる位置で 2 つに分けても、まとめて読んだときと同じ `Token` になる
+    fn assert_split(json: &str, options: LexerOptions) {
+        let expected = Lexer::with_options(json, options.clone())
+            .tokenize()
+            .unwrap();
+        for (i, _) in json.char_indices() {
+            let mut lexer = ChunkedLexer::with_options(options.clone());
+            let mut tokens = lexer.feed(&json[..i]).unwrap();
+            tokens.extend(lexer.feed(&json[i..]).unwrap());
+            tokens.extend(lexer.finish().unwrap());
+            assert_eq!(tokens, expected, "split at {}", i);
+        }
+
+        // 1 文字ずつ与えても同じになる
+        let mut lexer = ChunkedLexer::with_options(options.clone());
+        let mut tokens = vec![];
+        for (i, c) in json.char_indices() {
+            tokens.extend(lexer.feed(&json[i..i + c.len_utf8()]).unwrap());
+        }
+        tokens.extend(lexer.finish().unwrap());
+        assert_eq!(tokens, expected);
+    }
+
+    #[test]
+    fn test_split() {
+        let json = r#"{"a": [true, false, null, -12.5e3, 42], "b\u00e9\n": "x\\y"}"#;
+        assert_split(json, LexerOptions::default());
+
+        let options = LexerOptions {
+            raw_keys: vec!["raw".to_string()],
+            ..Default::default()
+        };
+        assert_split(r#"{"raw": {"x": [1, "]"]}, "y": 1}"#, options);
+
+        let options = LexerOptions::from(crate::dialect::Dialect::Json5);
+        assert_split(
+            "{key: 0x1F, n: -Infinity, s: 'it\\'s\\0', /* c ** */ m: .5, t: '\\\r\n'} // end",
+            options,
+        );
+        let json = r#"["\ud83d\ude00\u00e9", "a\"b", 1.5e-3, -0, {"raw": "]}"}]"#;
+        let options = LexerOptions {
+            raw_keys: vec!["raw".to_string()],
+            ..Default::default()
+        };
+        assert_split(json, options);
+    }
+
+    #[test]
+    fn test_long_token() {
+        // 読みかけの token は読み終えた部分を状態として持ち越し、入力を読み直さない
+        let body = "ab\\n\\u00e9".repeat(20_000);
+        let json = format!("[\"{}\", {}, /* {} */]", body, "1".repeat(1000), body);
+        let options = LexerOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        let mut lexer = ChunkedLexer::with_options(options);
+        let mut tokens = vec![];
+        for (i, c) in json.char_indices() {
+            tokens.extend(lexer.feed(&json[i..i + c.len_utf8()]).unwrap());
+            assert!(
+                lexer.buf.len() <= 6,
+                "{} bytes buffered at {}",
+                lexer.buf.len(),
+                i
+            );
+        }
+        tokens.extend(lexer.finish().unwrap());
+        assert_eq!(tokens.len(), 6);
+        assert_eq!(tokens[1], Token::String("ab\né".repeat(20_000).into()));
+        assert!(matches!(&tokens[3], Token::Number { raw, .. } if raw.len() == 1000));
+    }
+
+    #[test]
+    fn test_state() {
+        let mut lexer = ChunkedLexer::new();
+        assert_eq!(lexer.feed("[\"ab").unwrap(), vec![Token::LeftBracket]);
+        assert_eq!(
+            lexer.state(),
+            LexState::InString {
+                escape_pending: false
+            }
+        );
+        assert_eq!(lexer.feed("\\").unwrap(), vec![]);
+        assert_eq!(
+            lexer.state(),
+            LexState::InString {
+                escape_pending: true
+            }
+        );
+        assert_eq!(
+            lexer.feed("n\", 12").unwrap(),
+            vec![Token::String("ab\n".into()), Token::Comma]
+        );
+        assert_eq!(lexer.state(), LexState::InNumber);
+        assert_eq!(
+            lexer.feed("3, tr").unwrap(),
+            vec![Token::Integer(123), Token::Comma]
+        );
+        assert_eq!(lexer.state(), LexState::InLiteral);
+        assert_eq!(
+            lexer.feed("ue]").unwrap(),
+            vec![Token::Bool(true), Token::RightBracket]
+        );
+        assert_eq!(lexer.state(), LexState::Ready);
+        assert_eq!(lexer.finish().unwrap(), vec![]);
+    }
+
+    #[test]
+    fn test_error() {
+        // エラーの位置はストリーム全体での位置になる
+        let mut lexer = ChunkedLexer::new();
+        lexer.feed("[1,\n 2, ").unwrap();
+        let e = lexer.feed("x]").unwrap_err();
+        let position = e.position().unwrap();
+        assert_eq!((position.offset, position.line, position.column), (8, 2, 5));
+        assert_eq!(lexer.feed("1").unwrap(), vec![]);
+
+        let mut lexer = ChunkedLexer::new();
+        lexer.feed("[\"abc").unwrap();
+        assert!(lexer.finish().is_err());
+
+        // チャンクをまたいだ token のエラーは、その token の開始位置を指す
+        let mut lexer = ChunkedLexer::new();
+        lexer.feed("[tr").unwrap();
+        let e = lexer.feed("ux]").unwrap_err();
+        let position = e.position().unwrap();
+        assert_eq!((position.offset, position.column), (1, 2));
+    }
+}
@@ -0,0 +1,78 @@
+//! 型の揃っていない外部のデータを読むための、緩い変換
+//!
+//! どれも変換できないときは None を返す
+
+use std::borrow::Cow;
+
+use crate::value::Value;
+
+impl Value {
+    /// 数値、数値の文字列 (前後の空白は無視する)、真偽値 (`true` は 1、`false` は 0) を `f64` にする
+    ///
+    /// `"NaN"` や `"inf"` のような有限でない数値の文字列は変換しない
+    pub fn as_f64_lossy(&self) -> Option<f64> {
+        match self {
+            Value::Number(n) => Some(n.as_f64()),
+            Value::String(s) => s.trim().parse::<f64>().ok().filter(|n| n.is_finite()),
+            Value::Bool(b) => Some(if *b { 1.0 } else { 0.0 }),
+            _ => None,
+        }
+    }
+
+    /// 真偽値、数値の 1 と 0、文字列の `"true"` `"1"` `"yes"` `"on"` と `"false"` `"0"` `"no"` `"off"`
+    /// (大文字小文字と前後の空白は無視する) を `bool` にする
+    pub fn as_bool_lossy(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            Value::Number(n) if n.as_f64() == 1.0 => Some(true),
+            Value::Number(n) if n.as_f64() == 0.0 => Some(false),
+            Value::String(s) => match s.trim().to_ascii_lowercase().as_str() {
+                "true" | "1" | "yes" | "on" => Some(true),
+                "false" | "0" | "no" | "off" => Some(false),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// 文字列はそのまま、数値と真偽値は JSON の表記の文字列にする
+    pub fn as_string_lossy(&self) -> Option<Cow<'_, str>> {
+        match self {
+            Value::String(s) => Some(Cow::Borrowed(s)),
+            Value::Number(n) => Some(Cow::Owned(n.to_string())),
+            Value::Bool(b) => Some(Cow::Borrowed(if *b { "true" } else { "false" })),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_lossy() {
+        assert_eq!(Value::from(2).as_f64_lossy(), Some(2.0));
+        assert_eq!(Value::from(" 1.5e1 ").as_f64_lossy(), Some(15.0));
+        assert_eq!(Value::from(true).as_f64_lossy(), Some(1.0));
+        assert_eq!(Value::from("NaN").as_f64_lossy(), None);
+        assert_eq!(Value::from("abc").as_f64_lossy(), None);
+        assert_eq!(Value::Null.as_f64_lossy(), None);
+
+        assert_eq!(Value::from("True").as_bool_lossy(), Some(true));
+        assert_eq!(Value::from("1").as_bool_lossy(), Some(true));
+        assert_eq!(Value::from(" off").as_bool_lossy(), Some(false));
+        assert_eq!(Value::from(0).as_bool_lossy(), Some(false));
+        assert_eq!(Value::from(1.0).as_bool_lossy(), Some(true));
+        assert_eq!(Value::from(2).as_bool_lossy(), None);
+        assert_eq!(Value::from("maybe").as_bool_lossy(), None);
+
+        assert_eq!(Value::from("x").as_string_lossy().as_deref(), Some("x"));
+        assert_eq!(Value::from(42).as_string_lossy().as_deref(), Some("42"));
+        assert_eq!(
+            Value::from(false).as_string_lossy().as_deref(),
+            Some("false")
+        );
+        assert_eq!(Value::Array(vec![]).as_string_lossy(), None);
+    }
+}
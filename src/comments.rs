@@ -0,0 +1,338 @@
+//! コメントを保ったままの解析と整形
+//!
+//! JSONC や JSON5 の設定ファイルを整形し直してもコメントが消えないように、各コメントを近くの
+//! 値の JSON Pointer に結び付けて覚えておき、整形した出力に書き戻す
+
+use std::{collections::HashMap, fmt::Write};
+
+use crate::{
+    dialect::Dialect,
+    error::JsonError,
+    lexer::{Lexer, LexerOptions, Token},
+    parser::{Parser, ParserOptions},
+    pointer::{JsonPointer, ToPointer},
+    printer::{to_string_with, JsonPrinter, PrinterOptions},
+    value::Value,
+};
+
+/// 1 つの値に結び付けたコメント (`//` や `/* */` を含む元の文字列)
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NodeComments {
+    pub before: Vec<String>, // 値 (オブジェクトのメンバーならキー) の前の行のコメント
+    pub after: Vec<String>,  // 値 (と続くカンマ) の後の同じ行のコメント
+    pub end: Vec<String>,    // オブジェクトや配列の閉じ括弧の前のコメント
+}
+
+/// コメントを保ったまま解析した文書
+#[derive(Debug, Clone, PartialEq)]
+pub struct Commented {
+    pub value: Value,
+    pub comments: HashMap<JsonPointer, NodeComments>,
+    pub trailing: Vec<String>, // 文書の後の行のコメント
+}
+
+/// 開いているコンテナ
+struct Frame {
+    object: bool,
+    pointer: JsonPointer,
+    index: usize,        // 配列の今の要素の添字
+    key: Option<String>, // オブジェクトの今のメンバーのキー
+    expect_key: bool,
+}
+
+impl Commented {
+    /// `dialect` の文法で `json` を解析し、コメントを近くの値に結び付ける
+    ///
+    /// コメントは `Dialect::Jsonc` と `Dialect::Json5` でだけ書ける
+    pub fn parse(json: &str, dialect: Dialect) -> Result<Commented, JsonError> {
+        let lexemes = Lexer::with_options(json, LexerOptions::from(dialect)).tokenize_lossless()?;
+        let tokens = lexemes
+            .iter()
+            .filter(|l| !matches!(l.token, Token::WhiteSpace | Token::Comment(_)))
+            .map(|l| l.token.clone())
+            .collect();
+        let value = Parser::with_options(tokens, ParserOptions::from(dialect)).parse_document()?;
+
+        let mut comments: HashMap<JsonPointer, NodeComments> = HashMap::new();
+        let mut stack: Vec<Frame> = vec![];
+        let mut pending = vec![]; // 次の値に付けるコメント
+        let mut last: Option<JsonPointer> = None; // 直前に書き終えた値
+        let mut same_line = false; // 直前の値から改行していないか
+        let child = |stack: &[Frame]| match stack.last() {
+            None => JsonPointer::root(),
+            Some(f) if f.object => f.pointer.child(f.key.clone().unwrap_or_default()),
+            Some(f) => f.pointer.clone().index(f.index),
+        };
+        for lexeme in &lexemes {
+            match &lexeme.token {
+                Token::Comment(text) => match &last {
+                    Some(pointer) if same_line => comments
+                        .entry(pointer.clone())
+                        .or_default()
+                        .after
+                        .push(text.to_string()),
+                    _ => pending.push(text.to_string()),
+                },
+                Token::WhiteSpace => same_line &= !lexeme.text.contains('\n'),
+                Token::Comma => match stack.last_mut() {
+                    Some(f) if f.object => f.expect_key = true,
+                    Some(f) => f.index += 1,
+                    None => (),
+                },
+                Token::Colon => (),
+                Token::String(key) if stack.last().is_some_and(|f| f.object && f.expect_key) => {
+                    if let Some(f) = stack.last_mut() {
+                        f.key = Some(key.to_string());
+                        f.expect_key = false;
+                    }
+                    attach_before(&mut comments, child(&stack), &mut pending);
+                    last = None;
+                }
+                Token::LeftBrace | Token::LeftBracket => {
+                    let pointer = child(&stack);
+                    attach_before(&mut comments, pointer.clone(), &mut pending);
+                    let object = lexeme.token == Token::LeftBrace;
+                    stack.push(Frame {
+                        object,
+                        pointer,
+                        index: 0,
+                        key: None,
+                        expect_key: object,
+                    });
+                    last = None;
+                }
+                Token::RightBrace | Token::RightBracket => {
+                    if let Some(f) = stack.pop() {
+                        if !pending.is_empty() {
+                            let entry = comments.entry(f.pointer.clone()).or_default();
+                            entry.end.append(&mut pending);
+                        }
+                        last = Some(f.pointer);
+                        same_line = true;
+                    }
+                }
+                _ => {
+                    let pointer = child(&stack);
+                    attach_before(&mut comments, pointer.clone(), &mut pending);
+                    last = Some(pointer);
+                    same_line = true;
+                }
+            }
+        }
+        Ok(Commented {
+            value,
+            comments,
+            trailing: pending,
+        })
+    }
+
+    /// `pointer` の値に結び付けたコメント
+    pub fn comments_at<P: ToPointer + ?Sized>(&self, pointer: &P) -> Option<&NodeComments> {
+        self.comments.get(&*pointer.to_pointer().ok()?)
+    }
+
+    /// コメントを書き戻し、2 文字の空白で字下げした文字列にする
+    pub fn to_string_pretty(&self) -> String {
+        self.to_string_with(&PrinterOptions::pretty())
+    }
+
+    /// コメントを書き戻し、`options` の字下げと改行で整形した文字列にする
+    /// (コメントを書くために、`options.pretty` にかかわらず要素ごとに改行する)
+    pub fn to_string_with(&self, options: &PrinterOptions) -> String {
+        let mut w = CommentWriter {
+            buf: String::new(),
+            comments: &self.comments,
+            options,
+        };
+        let root = JsonPointer::root();
+        if let Some(node) = self.comments.get(&root) {
+            for comment in &node.before {
+                w.buf.push_str(comment);
+                w.newline(0);
+            }
+        }
+        w.write_node(&self.value, &root, 0);
+        w.write_after(&root, 0);
+        for comment in &self.trailing {
+            w.newline(0);
+            w.buf.push_str(comment);
+        }
+        w.buf
+    }
+}
+
+fn attach_before(
+    comments: &mut HashMap<JsonPointer, NodeComments>,
+    pointer: JsonPointer,
+    pending: &mut Vec<String>,
+) {
+    if !pending.is_empty() {
+        comments.entry(pointer).or_default().before.append(pending);
+    }
+}
+
+/// コメントを書き戻しながら整形する
+struct CommentWriter<'a> {
+    buf: String,
+    comments: &'a HashMap<JsonPointer, NodeComments>,
+    options: &'a PrinterOptions,
+}
+
+impl CommentWriter<'_> {
+    fn newline(&mut self, depth: usize) {
+        self.buf.push_str(self.options.line_ending.as_str());
+        for _ in 0..depth * self.options.indent_width {
+            self.buf.push(self.options.indent_char);
+        }
+    }
+
+    /// `pointer` の値を書く。前のコメントと後のコメントは呼び出し側で書く
+    fn write_node(&mut self, value: &Value, pointer: &JsonPointer, depth: usize) {
+        let end = self
+            .comments
+            .get(pointer)
+            .map_or(&[][..], |node| &node.end[..]);
+        let (open, close, items) = match value {
+            Value::Object(object) => (
+                '{',
+                '}',
+                object
+                    .iter()
+                    .map(|(k, v)| (pointer.child(k.as_str()), Some(k.as_str()), v))
+                    .collect::<Vec<_>>(),
+            ),
+            Value::Array(array) => (
+                '[',
+                ']',
+                array
+                    .iter()
+                    .enumerate()
+                    .map(|(i, v)| (pointer.clone().index(i), None, v))
+                    .collect::<Vec<_>>(),
+            ),
+            value => {
+                self.buf.push_str(&to_string_with(value, self.options));
+                return;
+            }
+        };
+        self.buf.push(open);
+        if items.is_empty() && end.is_empty() {
+            self.buf.push(close);
+            return;
+        }
+        let len = items.len();
+        for (i, (child, key, value)) in items.into_iter().enumerate() {
+            self.newline(depth + 1);
+            if let Some(node) = self.comments.get(&child) {
+                for comment in &node.before {
+                    self.buf.push_str(comment);
+                    self.newline(depth + 1);
+                }
+            }
+            if let Some(key) = key {
+                // String への書き込みは失敗しない
+                let _ = JsonPrinter::write_escaped(&mut self.buf, key, self.options);
+                self.buf.push_str(": ");
+            }
+            self.write_node(value, &child, depth + 1);
+            if i + 1 < len {
+                self.buf.push(',');
+            }
+            self.write_after(&child, depth + 1);
+        }
+        for comment in end {
+            self.newline(depth + 1);
+            self.buf.push_str(comment);
+        }
+        self.newline(depth);
+        self.buf.push(close);
+    }
+
+    /// 値の後の同じ行にコメントを書く。`//` の後にコメントが続けば改行する
+    fn write_after(&mut self, pointer: &JsonPointer, depth: usize) {
+        let Some(node) = self.comments.get(pointer) else {
+            return;
+        };
+        for (i, comment) in node.after.iter().enumerate() {
+            let _ = write!(self.buf, " {}", comment);
+            if comment.starts_with("//") && i + 1 < node.after.len() {
+                self.newline(depth);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_jsonc(json: &str) -> Value {
+        let tokens = Lexer::with_options(json, Dialect::Jsonc.into())
+            .tokenize()
+            .unwrap();
+        Parser::with_options(tokens, Dialect::Jsonc.into())
+            .parse()
+            .unwrap()
+    }
+
+    const CONFIG: &str = r#"// config
+{
+  // the name
+  "name": "x", // inline
+  "list": [
+    1, // one
+    /* two */ 2
+    // end of list
+  ],
+  "empty": {
+    // nothing here
+  },
+  "plain": [],
+}
+// trailing
+"#;
+
+    #[test]
+    fn test_parse_comments() {
+        let doc = Commented::parse(CONFIG, Dialect::Jsonc).unwrap();
+        assert_eq!(doc.value, parse_jsonc(CONFIG));
+        assert_eq!(doc.comments_at("").unwrap().before, ["// config"]);
+        let name = doc.comments_at("/name").unwrap();
+        assert_eq!(name.before, ["// the name"]);
+        assert_eq!(name.after, ["// inline"]);
+        assert_eq!(doc.comments_at("/list/0").unwrap().after, ["// one"]);
+        assert_eq!(doc.comments_at("/list/1").unwrap().before, ["/* two */"]);
+        assert_eq!(doc.comments_at("/list").unwrap().end, ["// end of list"]);
+        assert_eq!(doc.comments_at("/empty").unwrap().end, ["// nothing here"]);
+        assert_eq!(doc.comments_at("/plain"), None);
+        assert_eq!(doc.trailing, ["// trailing"]);
+    }
+
+    #[test]
+    fn test_comments_round_trip() {
+        let doc = Commented::parse(CONFIG, Dialect::Jsonc).unwrap();
+        let pretty = doc.to_string_pretty();
+        assert_eq!(
+            pretty,
+            r#"// config
+{
+  "empty": {
+    // nothing here
+  },
+  "list": [
+    1, // one
+    /* two */
+    2
+    // end of list
+  ],
+  // the name
+  "name": "x", // inline
+  "plain": []
+}
+// trailing"#
+        );
+        let again = Commented::parse(&pretty, Dialect::Jsonc).unwrap();
+        assert_eq!(again, doc);
+        assert!(Commented::parse("[1, // x\n]", Dialect::Json).is_err());
+    }
+}
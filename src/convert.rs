@@ -0,0 +1,462 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    fmt,
+};
+
+use crate::{pointer::escape, value::Value};
+
+/// `Value` から Rust の型への変換の失敗
+#[derive(Debug, Clone, PartialEq)]
+pub struct FromJsonError {
+    pub msg: String,
+    pub path: String, // 失敗した値を指す JSON Pointer
+}
+
+impl FromJsonError {
+    pub fn new(msg: &str) -> FromJsonError {
+        FromJsonError {
+            msg: msg.to_string(),
+            path: String::new(),
+        }
+    }
+
+    /// 期待した型と実際の値を示すエラー
+    pub fn expected(expected: &str, found: &Value) -> FromJsonError {
+        Self::new(&format!(
+            "error: {} is expected but found {}",
+            expected, found
+        ))
+    }
+
+    /// 親の値から見た位置 `token` を path の前に付ける
+    fn within(mut self, token: &str) -> FromJsonError {
+        self.path = format!("/{}{}", escape(token), self.path);
+        self
+    }
+}
+
+impl fmt::Display for FromJsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.path.is_empty() {
+            write!(f, "{}", self.msg)
+        } else {
+            write!(f, "{} (at {})", self.msg, self.path)
+        }
+    }
+}
+
+impl std::error::Error for FromJsonError {}
+
+/// `Value` から取り出せる型
+pub trait FromJson: Sized {
+    fn from_json(value: &Value) -> Result<Self, FromJsonError>;
+}
+
+/// `value` を型 `T` として取り出す
+pub fn from_value<T: FromJson>(value: &Value) -> Result<T, FromJsonError> {
+    T::from_json(value)
+}
+
+impl FromJson for Value {
+    fn from_json(value: &Value) -> Result<Value, FromJsonError> {
+        Ok(value.clone())
+    }
+}
+
+impl FromJson for bool {
+    fn from_json(value: &Value) -> Result<bool, FromJsonError> {
+        value
+            .as_bool()
+            .ok_or_else(|| FromJsonError::expected("a boolean", value))
+    }
+}
+
+impl FromJson for String {
+    fn from_json(value: &Value) -> Result<String, FromJsonError> {
+        value
+            .as_str()
+            .map(str::to_string)
+            .ok_or_else(|| FromJsonError::expected("a string", value))
+    }
+}
+
+impl FromJson for f64 {
+    fn from_json(value: &Value) -> Result<f64, FromJsonError> {
+        value
+            .as_f64()
+            .ok_or_else(|| FromJsonError::expected("a number", value))
+    }
+}
+
+impl FromJson for f32 {
+    fn from_json(value: &Value) -> Result<f32, FromJsonError> {
+        f64::from_json(value).map(|n| n as f32)
+    }
+}
+
+/// 整数型は範囲外の値や小数をエラーにする
+macro_rules! impl_from_json_integer {
+    ($($ty:ty),*) => {
+        $(
+            impl FromJson for $ty {
+                fn from_json(value: &Value) -> Result<$ty, FromJsonError> {
+                    let n = value.as_number().ok_or_else(|| {
+                        FromJsonError::expected(concat!("an integer (", stringify!($ty), ")"), value)
+                    })?;
+                    n.as_i64()
+                        .and_then(|n| <$ty>::try_from(n).ok())
+                        .or_else(|| n.as_u64().and_then(|n| <$ty>::try_from(n).ok()))
+                        .ok_or_else(|| {
+                            FromJsonError::expected(concat!("an integer (", stringify!($ty), ")"), value)
+                        })
+                }
+            }
+        )*
+    };
+}
+
+impl_from_json_integer!(i8, i16, i32, i64, u8, u16, u32, u64, usize);
+
+/// `null` は None
+impl<T: FromJson> FromJson for Option<T> {
+    fn from_json(value: &Value) -> Result<Option<T>, FromJsonError> {
+        match value {
+            Value::Null => Ok(None),
+            value => T::from_json(value).map(Some),
+        }
+    }
+}
+
+impl<T: FromJson> FromJson for Vec<T> {
+    fn from_json(value: &Value) -> Result<Vec<T>, FromJsonError> {
+        let array = value
+            .as_array()
+            .ok_or_else(|| FromJsonError::expected("an array", value))?;
+        array
+            .iter()
+            .enumerate()
+            .map(|(i, value)| T::from_json(value).map_err(|e| e.within(&i.to_string())))
+            .collect()
+    }
+}
+
+impl<T: FromJson> FromJson for HashMap<String, T> {
+    fn from_json(value: &Value) -> Result<HashMap<String, T>, FromJsonError> {
+        BTreeMap::from_json(value).map(|object| object.into_iter().collect())
+    }
+}
+
+impl<T: FromJson> FromJson for BTreeMap<String, T> {
+    fn from_json(value: &Value) -> Result<BTreeMap<String, T>, FromJsonError> {
+        let object = value
+            .as_object()
+            .ok_or_else(|| FromJsonError::expected("an object", value))?;
+        object
+            .iter()
+            .map(|(key, value)| {
+                let value = T::from_json(value).map_err(|e| e.within(key))?;
+                Ok((key.clone(), value))
+            })
+            .collect()
+    }
+}
+
+/// タプルは同じ長さの配列から取り出す
+macro_rules! impl_from_json_tuple {
+    ($($len:literal => ($($name:ident $index:tt),*);)*) => {
+        $(
+            impl<$($name: FromJson),*> FromJson for ($($name,)*) {
+                fn from_json(value: &Value) -> Result<Self, FromJsonError> {
+                    match value.as_array() {
+                        Some(array) if array.len() == $len => Ok(($(
+                            $name::from_json(&array[$index])
+                                .map_err(|e| e.within(stringify!($index)))?,
+                        )*)),
+                        _ => Err(FromJsonError::expected(
+                            concat!("an array of length ", $len),
+                            value,
+                        )),
+                    }
+                }
+            }
+        )*
+    };
+}
+
+impl_from_json_tuple! {
+    1 => (A 0);
+    2 => (A 0, B 1);
+    3 => (A 0, B 1, C 2);
+    4 => (A 0, B 1, C 2, D 3);
+}
+
+/// `TryFrom<Value>` は `FromJson` と同じ規則で取り出す。文字列と配列、オブジェクトの中身は複製せずに移す
+impl TryFrom<Value> for String {
+    type Error = FromJsonError;
+
+    fn try_from(value: Value) -> Result<String, FromJsonError> {
+        match value {
+            Value::String(s) => Ok(s),
+            value => Err(FromJsonError::expected("a string", &value)),
+        }
+    }
+}
+
+macro_rules! impl_try_from_value {
+    ($($ty:ty),*) => {
+        $(
+            impl TryFrom<Value> for $ty {
+                type Error = FromJsonError;
+
+                fn try_from(value: Value) -> Result<$ty, FromJsonError> {
+                    from_value(&value)
+                }
+            }
+        )*
+    };
+}
+
+impl_try_from_value!(f64, i64, u64, bool);
+
+impl<T: TryFrom<Value, Error = FromJsonError>> TryFrom<Value> for Vec<T> {
+    type Error = FromJsonError;
+
+    fn try_from(value: Value) -> Result<Vec<T>, FromJsonError> {
+        match value {
+            Value::Array(array) => array
+                .into_iter()
+                .enumerate()
+                .map(|(i, value)| T::try_from(value).map_err(|e| e.within(&i.to_string())))
+                .collect(),
+            value => Err(FromJsonError::expected("an array", &value)),
+        }
+    }
+}
+
+impl<T: TryFrom<Value, Error = FromJsonError>> TryFrom<Value> for HashMap<String, T> {
+    type Error = FromJsonError;
+
+    fn try_from(value: Value) -> Result<HashMap<String, T>, FromJsonError> {
+        match value {
+            Value::Object(object) => object
+                .into_iter()
+                .map(|(key, value)| {
+                    let value = T::try_from(value).map_err(|e| e.within(&key))?;
+                    Ok((key, value))
+                })
+                .collect(),
+            value => Err(FromJsonError::expected("an object", &value)),
+        }
+    }
+}
+
+/// `Value` に変換できる型
+pub trait ToJson {
+    fn to_json(&self) -> Value;
+}
+
+/// `value` を `Value` にする
+pub fn to_value<T: ToJson + ?Sized>(value: &T) -> Value {
+    value.to_json()
+}
+
+impl ToJson for Value {
+    fn to_json(&self) -> Value {
+        self.clone()
+    }
+}
+
+impl<T: ToJson + ?Sized> ToJson for &T {
+    fn to_json(&self) -> Value {
+        (**self).to_json()
+    }
+}
+
+impl ToJson for str {
+    fn to_json(&self) -> Value {
+        Value::from(self)
+    }
+}
+
+/// `From` で `Value` にできる値の型
+macro_rules! impl_to_json {
+    ($($ty:ty),*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self) -> Value {
+                    Value::from(self.clone())
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json!(bool, String, f64, i64, i32, u64, u32);
+
+macro_rules! impl_to_json_cast {
+    ($($ty:ty => $via:ty),*) => {
+        $(
+            impl ToJson for $ty {
+                fn to_json(&self) -> Value {
+                    Value::from(<$via>::from(*self))
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_cast!(f32 => f64, i8 => i64, i16 => i64, u8 => u64, u16 => u64);
+
+impl ToJson for usize {
+    fn to_json(&self) -> Value {
+        Value::from(*self as u64)
+    }
+}
+
+/// None は `null`
+impl<T: ToJson> ToJson for Option<T> {
+    fn to_json(&self) -> Value {
+        self.as_ref().map_or(Value::Null, ToJson::to_json)
+    }
+}
+
+impl<T: ToJson> ToJson for [T] {
+    fn to_json(&self) -> Value {
+        self.iter().map(ToJson::to_json).collect()
+    }
+}
+
+impl<T: ToJson, const N: usize> ToJson for [T; N] {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for Vec<T> {
+    fn to_json(&self) -> Value {
+        self.as_slice().to_json()
+    }
+}
+
+impl<T: ToJson> ToJson for HashMap<String, T> {
+    fn to_json(&self) -> Value {
+        self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect()
+    }
+}
+
+impl<T: ToJson> ToJson for BTreeMap<String, T> {
+    fn to_json(&self) -> Value {
+        self.iter().map(|(k, v)| (k.clone(), v.to_json())).collect()
+    }
+}
+
+/// タプルは配列にする
+macro_rules! impl_to_json_tuple {
+    ($(($($name:ident $index:tt),*);)*) => {
+        $(
+            impl<$($name: ToJson),*> ToJson for ($($name,)*) {
+                fn to_json(&self) -> Value {
+                    Value::Array(vec![$(self.$index.to_json()),*])
+                }
+            }
+        )*
+    };
+}
+
+impl_to_json_tuple! {
+    (A 0);
+    (A 0, B 1);
+    (A 0, B 1, C 2);
+    (A 0, B 1, C 2, D 3);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_from_value() {
+        assert_eq!(from_value::<bool>(&parse("true")), Ok(true));
+        assert_eq!(from_value::<String>(&parse(r#""a""#)), Ok("a".to_string()));
+        assert_eq!(from_value::<f64>(&parse("1")), Ok(1.0));
+        assert_eq!(from_value::<u8>(&parse("255")), Ok(255));
+        assert_eq!(
+            from_value::<u64>(&parse("18446744073709551615")),
+            Ok(u64::MAX)
+        );
+        assert_eq!(from_value::<Option<i32>>(&parse("null")), Ok(None));
+        assert_eq!(
+            from_value::<Vec<Option<i32>>>(&parse("[1, null]")),
+            Ok(vec![Some(1), None])
+        );
+        assert_eq!(
+            from_value::<(String, i64, bool)>(&parse(r#"["a", -1, false]"#)),
+            Ok(("a".to_string(), -1, false))
+        );
+        let map =
+            from_value::<HashMap<String, Vec<u16>>>(&parse(r#"{"a": [1], "b": []}"#)).unwrap();
+        assert_eq!(map["a"], [1]);
+        assert!(map["b"].is_empty());
+    }
+
+    #[test]
+    fn test_to_value() {
+        assert_eq!(to_value("a"), parse(r#""a""#));
+        assert_eq!(to_value(&1u8), parse("1"));
+        assert_eq!(to_value(&-1i16), parse("-1"));
+        assert_eq!(to_value(&1.5f32), parse("1.5"));
+        assert_eq!(to_value(&vec![Some(true), None]), parse("[true, null]"));
+        assert_eq!(to_value(&("a", 1, [2.5])), parse(r#"["a", 1, [2.5]]"#));
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), vec!["x".to_string()]);
+        let value = map.to_json();
+        assert_eq!(value, parse(r#"{"a": ["x"]}"#));
+        // FromJson で元に戻せる
+        assert_eq!(from_value::<HashMap<String, Vec<String>>>(&value), Ok(map));
+    }
+
+    #[test]
+    fn test_error() {
+        let e = from_value::<u8>(&parse("256")).unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "error: an integer (u8) is expected but found 256"
+        );
+        assert!(from_value::<i32>(&parse("1.5")).is_err());
+        assert!(from_value::<(i32, i32)>(&parse("[1]")).is_err());
+
+        let e =
+            from_value::<BTreeMap<String, Vec<bool>>>(&parse(r#"{"a/b": [true, 1]}"#)).unwrap_err();
+        assert_eq!(e.path, "/a~1b/1");
+        assert_eq!(
+            e.to_string(),
+            "error: a boolean is expected but found 1 (at /a~1b/1)"
+        );
+    }
+
+    #[test]
+    fn test_try_from() {
+        assert_eq!(String::try_from(Value::from("a")), Ok("a".to_string()));
+        assert_eq!(f64::try_from(Value::from(2)), Ok(2.0));
+        assert_eq!(i64::try_from(Value::from(-2)), Ok(-2));
+        assert_eq!(bool::try_from(Value::from(true)), Ok(true));
+        let list: Result<Vec<i64>, _> = parse("[1, 2]").try_into();
+        assert_eq!(list, Ok(vec![1, 2]));
+
+        let map: HashMap<String, Vec<String>> =
+            parse(r#"{"a": ["x"], "b": []}"#).try_into().unwrap();
+        assert_eq!(map["a"], ["x"]);
+        assert!(map["b"].is_empty());
+
+        let e = HashMap::<String, Vec<bool>>::try_from(parse(r#"{"a": [true, 1]}"#)).unwrap_err();
+        assert_eq!(e.path, "/a/1");
+        assert_eq!(
+            e.to_string(),
+            "error: a boolean is expected but found 1 (at /a/1)"
+        );
+        assert!(i64::try_from(Value::from(1.5)).is_err());
+        assert!(String::try_from(Value::Null).is_err());
+    }
+}
@@ -0,0 +1,74 @@
+use std::collections::HashSet;
+
+use crate::{parser::ParserError, pointer::ToPointer, value::Value};
+
+impl Value {
+    /// 配列から構造として等しい要素を取り除き、最初のものだけを残す。配列でなければ何もしない
+    ///
+    /// 等しさは `==` と同じで、`1` と `1.0` も等しい。要素は複製せず参照で比べる
+    pub fn dedup_array(&mut self) {
+        if let Value::Array(array) = self {
+            let keep = first_occurrences(array, |item| item);
+            retain_by_mask(array, keep);
+        }
+    }
+
+    /// 配列から `pointer` の指す値が等しい要素を取り除き、最初のものだけを残す
+    ///
+    /// 値がない要素は null として扱う。配列でなければ何もしない。不正な pointer ならエラー
+    pub fn dedup_array_by_pointer<P: ToPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+    ) -> Result<(), ParserError> {
+        let pointer = pointer.to_pointer()?;
+        if let Value::Array(array) = self {
+            let keep =
+                first_occurrences(array, |item| pointer.resolve(item).unwrap_or(&Value::Null));
+            retain_by_mask(array, keep);
+        }
+        Ok(())
+    }
+
+    /// 配列から `f` で取り出した値が等しい要素を取り除き、最初のものだけを残す
+    pub fn dedup_array_by_key<F: FnMut(&Value) -> Value>(&mut self, mut f: F) {
+        if let Value::Array(array) = self {
+            let mut seen = HashSet::new();
+            array.retain(|item| seen.insert(f(item)));
+        }
+    }
+}
+
+/// 各要素について、`key` の値がそれより前に現れていなければ true
+fn first_occurrences<'v>(array: &'v [Value], key: impl Fn(&'v Value) -> &'v Value) -> Vec<bool> {
+    let mut seen = HashSet::new();
+    array.iter().map(|item| seen.insert(key(item))).collect()
+}
+
+/// `keep` が true の要素だけを残す
+fn retain_by_mask(array: &mut Vec<Value>, keep: Vec<bool>) {
+    let mut keep = keep.into_iter();
+    array.retain(|_| keep.next().unwrap_or(false));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_dedup() {
+        let mut value = parse(r#"[1, {"a": [1]}, 1.0, "1", {"a": [1.0]}, null, null]"#);
+        value.dedup_array();
+        assert_eq!(value, parse(r#"[1, {"a": [1]}, "1", null]"#));
+
+        let mut value =
+            parse(r#"[{"id": 1, "v": "a"}, {"id": 2}, {"id": 1, "v": "b"}, {}, {"id": null}]"#);
+        value.dedup_array_by_pointer("/id").unwrap();
+        assert!(value.dedup_array_by_pointer("id").is_err());
+        assert_eq!(value, parse(r#"[{"id": 1, "v": "a"}, {"id": 2}, {}]"#));
+
+        let mut value = Value::from("x");
+        value.dedup_array();
+        assert_eq!(value, Value::from("x"));
+    }
+}
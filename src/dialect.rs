@@ -0,0 +1,200 @@
+use crate::{lexer::LexerOptions, parser::ParserOptions};
+
+/// 入力として受け付ける JSON の方言
+///
+/// `Jsonc` は VS Code の設定ファイルなどで使われる、コメントと末尾のカンマだけを許す形式。
+///
+/// `Json5` は [JSON5](https://spec.json5.org/) の拡張をすべて受け付ける。
+///
+/// - `//` と `/* */` のコメント
+/// - 配列とオブジェクトの末尾のカンマ
+/// - `'single quoted'` の文字列と、行継続による複数行の文字列
+/// - `{foo: 1}` のような識別子のキー
+/// - `0xFF`、`Infinity`、`NaN`、`+1`、`.5`、`5.` の数値
+/// - `\v`、`\0`、`\xHH` などの追加のエスケープ
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Dialect {
+    /// RFC 8259 の JSON
+    #[default]
+    Json,
+    /// コメントと末尾のカンマを許す JSON (JSON with Comments)
+    Jsonc,
+    /// JSON5
+    Json5,
+}
+
+impl From<Dialect> for LexerOptions {
+    fn from(dialect: Dialect) -> LexerOptions {
+        match dialect {
+            Dialect::Json => LexerOptions::default(),
+            Dialect::Jsonc => LexerOptions {
+                allow_comments: true,
+                ..Default::default()
+            },
+            Dialect::Json5 => LexerOptions {
+                allow_comments: true,
+                allow_single_quotes: true,
+                allow_unquoted_keys: true,
+                allow_extended_numbers: true,
+                json5_escapes: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl From<Dialect> for ParserOptions {
+    fn from(dialect: Dialect) -> ParserOptions {
+        match dialect {
+            Dialect::Json => ParserOptions::default(),
+            Dialect::Jsonc | Dialect::Json5 => ParserOptions {
+                allow_trailing_commas: true,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+#[cfg(test)]
+mod json5_suite;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        lexer::Lexer,
+        parser::{Parser, Value},
+    };
+    use std::collections::BTreeMap;
+
+    fn parse(json: &str, dialect: Dialect) -> Option<Value> {
+        let tokens = Lexer::with_options(json, dialect.into()).tokenize().ok()?;
+        Parser::with_options(tokens, dialect.into()).parse().ok()
+    }
+
+    #[test]
+    fn test_json5() {
+        // https://json5.org/ の例
+        let json = r#"
+        // comments
+        {
+          unquoted: 'and you can quote me on that',
+          singleQuotes: 'I can use "double quotes" here',
+          lineBreaks: "Look, Mom! \
+No \\n's!",
+          hexadecimal: 0xdecaf,
+          leadingDecimalPoint: .8675309, andTrailing: 8675309.,
+          positiveSign: +1,
+          trailingComma: 'in objects', andIn: ['arrays',],
+          "backwardsCompatible": "with JSON",
+        }
+        "#;
+        assert_eq!(parse(json, Dialect::Json), None);
+
+        let mut object = BTreeMap::new();
+        let mut insert = |key: &str, value| object.insert(key.to_string(), value);
+        insert(
+            "unquoted",
+            Value::String("and you can quote me on that".to_string()),
+        );
+        insert(
+            "singleQuotes",
+            Value::String(r#"I can use "double quotes" here"#.to_string()),
+        );
+        insert(
+            "lineBreaks",
+            Value::String(r"Look, Mom! No \n's!".to_string()),
+        );
+        insert("hexadecimal", Value::from(0xdecaf));
+        insert("leadingDecimalPoint", Value::from(0.8675309));
+        insert("andTrailing", Value::from(8675309.0));
+        insert("positiveSign", Value::from(1));
+        insert("trailingComma", Value::String("in objects".to_string()));
+        insert(
+            "andIn",
+            Value::Array(vec![Value::String("arrays".to_string())]),
+        );
+        insert(
+            "backwardsCompatible",
+            Value::String("with JSON".to_string()),
+        );
+        assert_eq!(parse(json, Dialect::Json5), Some(Value::Object(object)));
+    }
+
+    #[test]
+    fn test_json5_values() {
+        let cases = [
+            ("/* 先頭 */ null // 末尾", Value::Null),
+            ("[Infinity, -Infinity]", {
+                Value::Array(vec![
+                    Value::from(f64::INFINITY),
+                    Value::from(f64::NEG_INFINITY),
+                ])
+            }),
+            ("-0x10", Value::from(-16)),
+            (r"'\x41\u0042\v'", Value::String("AB\u{b}".to_string())),
+            ("{'a': {b: [1,],},}", {
+                let mut inner = BTreeMap::new();
+                inner.insert("b".to_string(), Value::Array(vec![Value::from(1)]));
+                let mut object = BTreeMap::new();
+                object.insert("a".to_string(), Value::Object(inner));
+                Value::Object(object)
+            }),
+        ];
+        for (json, expected) in cases {
+            assert_eq!(parse(json, Dialect::Json5), Some(expected), "{}", json);
+        }
+
+        // JSON5 でも受け付けないもの
+        for json in [
+            "[1,,]",
+            "{a b: 1}",
+            "'unterminated\n'",
+            "[01]",
+            "{1a: 1}",
+            "[undefined]",
+        ] {
+            assert_eq!(parse(json, Dialect::Json5), None, "{}", json);
+        }
+    }
+
+    #[test]
+    fn test_json5_suite() {
+        for (name, json, valid) in super::json5_suite::CASES {
+            let tokens = Lexer::with_options(json, Dialect::Json5.into()).tokenize();
+            let parsed = tokens.map_err(|_| ()).and_then(|tokens| {
+                Parser::with_options(tokens, Dialect::Json5.into())
+                    .parse_document()
+                    .map_err(|_| ())
+            });
+            assert_eq!(parsed.is_ok(), *valid, "{}: {:?}", name, json);
+        }
+    }
+
+    #[test]
+    fn test_jsonc() {
+        let json = r#"
+        {
+            // エディタの設定
+            "editor.tabSize": 4,
+            /* 末尾のカンマ */
+            "files.exclude": ["target",],
+        }
+        "#;
+        assert_eq!(parse(json, Dialect::Json), None);
+
+        let mut object = BTreeMap::new();
+        object.insert("editor.tabSize".to_string(), Value::from(4));
+        object.insert(
+            "files.exclude".to_string(),
+            Value::Array(vec![Value::String("target".to_string())]),
+        );
+        assert_eq!(parse(json, Dialect::Jsonc), Some(Value::Object(object)));
+
+        // 文字列や数値は JSON のまま
+        for json in ["{'a': 1}", "{a: 1}", "[0xFF]", "[.5]", r#"["\x41"]"#] {
+            assert_eq!(parse(json, Dialect::Jsonc), None, "{}", json);
+            assert!(parse(json, Dialect::Json5).is_some(), "{}", json);
+        }
+    }
+}
@@ -0,0 +1,946 @@
+//! [json5-tests](https://github.com/json5/json5-tests) の入力
+//!
+//! `(分類/名前, 入力, JSON5 として受け付けるか)` の表。受け付けないものには、ES5 としては正しい
+//! もの (`.js`) と、ES5 としても正しくないもの (`.txt`) の両方を含む。
+//! `new-lines` の入力は、元のファイルと同じく CR や CRLF の改行にしてある
+
+pub(super) const CASES: &[(&str, &str, bool)] = &[
+    (
+        "arrays/empty-array",
+        r#"[]"#,
+        true,
+    ),
+    (
+        "arrays/leading-comma-array",
+        r#"[
+    ,null
+]"#,
+        false,
+    ),
+    (
+        "arrays/lone-trailing-comma-array",
+        r#"[
+    ,
+]"#,
+        false,
+    ),
+    (
+        "arrays/no-comma-array",
+        r#"[
+    true
+    false
+]"#,
+        false,
+    ),
+    (
+        "arrays/regular-array",
+        r#"[
+    true,
+    false,
+    null
+]"#,
+        true,
+    ),
+    (
+        "arrays/trailing-comma-array",
+        r#"[
+    null,
+]"#,
+        true,
+    ),
+    (
+        "comments/block-comment-following-array-element",
+        r#"[
+    false
+    /*
+        true
+    */
+]"#,
+        true,
+    ),
+    (
+        "comments/block-comment-following-top-level-value",
+        r#"null
+/*
+    Some non-comment top-level value is needed;
+    we use null above.
+*/"#,
+        true,
+    ),
+    (
+        "comments/block-comment-in-string",
+        r#""This /* block comment */ isn't really a block comment.""#,
+        true,
+    ),
+    (
+        "comments/block-comment-preceding-top-level-value",
+        r#"/*
+    Some non-comment top-level value is needed;
+    we use null below.
+*/
+null"#,
+        true,
+    ),
+    (
+        "comments/block-comment-with-asterisks",
+        r#"/**
+ * This is a JavaDoc-like block comment.
+ * It contains asterisks inside of it.
+ * It might also be closed with multiple asterisks.
+ * Like this:
+ **/
+true"#,
+        true,
+    ),
+    (
+        "comments/inline-comment-following-array-element",
+        r#"[
+    false   // true
+]"#,
+        true,
+    ),
+    (
+        "comments/inline-comment-following-top-level-value",
+        r#"null // Some non-comment top-level value is needed; we use null here."#,
+        true,
+    ),
+    (
+        "comments/inline-comment-in-string",
+        r#""This inline comment // isn't really an inline comment.""#,
+        true,
+    ),
+    (
+        "comments/inline-comment-preceding-top-level-value",
+        r#"// Some non-comment top-level value is needed; we use null below.
+null"#,
+        true,
+    ),
+    (
+        "comments/top-level-block-comment",
+        r#"/*
+    This should fail;
+    comments cannot be the only top-level value.
+*/"#,
+        false,
+    ),
+    (
+        "comments/top-level-inline-comment",
+        r#"// This should fail; comments cannot be the only top-level value."#,
+        false,
+    ),
+    (
+        "comments/unterminated-block-comment",
+        r#"true
+/*
+    This block comment doesn't terminate.
+    There was a legitimate value before this,
+    but this is still invalid JS/JSON5.
+"#,
+        false,
+    ),
+    (
+        "misc/empty",
+        r#""#,
+        false,
+    ),
+    (
+        "misc/npm-package",
+        r#"{
+  "name": "npm",
+  "publishConfig": {
+    "proprietary-attribs": false
+  },
+  "description": "A package manager for node",
+  "keywords": [
+    "package manager",
+    "modules",
+    "install",
+    "package.json"
+  ],
+  "version": "1.1.22",
+  "preferGlobal": true,
+  "config": {
+    "publishtest": false
+  },
+  "homepage": "http://npmjs.org/",
+  "author": "Isaac Z. Schlueter <i@izs.me> (http://blog.izs.me)",
+  "repository": {
+    "type": "git",
+    "url": "https://github.com/isaacs/npm"
+  },
+  "bugs": {
+    "email": "npm-@googlegroups.com",
+    "url": "http://github.com/isaacs/npm/issues"
+  },
+  "directories": {
+    "doc": "./doc",
+    "man": "./man",
+    "lib": "./lib",
+    "bin": "./bin"
+  },
+  "main": "./lib/npm.js",
+  "bin": "./bin/npm-cli.js",
+  "dependencies": {
+    "semver": "~1.0.14",
+    "ini": "1",
+    "slide": "1",
+    "abbrev": "1",
+    "graceful-fs": "~1.1.1",
+    "minimatch": "~0.2",
+    "nopt": "1",
+    "node-uuid": "~1.3",
+    "proto-list": "1",
+    "rimraf": "2",
+    "request": "~2.9",
+    "which": "1",
+    "tar": "~0.1.12",
+    "fstream": "~0.1.17",
+    "block-stream": "*",
+    "inherits": "1",
+    "mkdirp": "0.3",
+    "read": "0",
+    "lru-cache": "1",
+    "node-gyp": "~0.4.1",
+    "fstream-npm": "0 >=0.0.5",
+    "uid-number": "0",
+    "archy": "0",
+    "chownr": "0"
+  },
+  "bundleDependencies": [
+    "slide",
+    "ini",
+    "semver",
+    "abbrev",
+    "graceful-fs",
+    "minimatch",
+    "nopt",
+    "node-uuid",
+    "rimraf",
+    "request",
+    "proto-list",
+    "which",
+    "tar",
+    "fstream",
+    "block-stream",
+    "inherits",
+    "mkdirp",
+    "read",
+    "lru-cache",
+    "node-gyp",
+    "fstream-npm",
+    "uid-number",
+    "archy",
+    "chownr"
+  ],
+  "devDependencies": {
+    "ronn": "https://github.com/isaacs/ronnjs/tarball/master"
+  },
+  "engines": {
+    "node": "0.6 || 0.7 || 0.8",
+    "npm": "1"
+  },
+  "scripts": {
+    "test": "node ./test/run.js",
+    "prepublish": "npm prune; rm -rf node_modules/*/{test,example,bench}*; make -j4 doc",
+    "dumpconf": "env | grep npm | sort | uniq"
+  },
+  "licenses": [
+    {
+      "type": "MIT +no-false-attribs",
+      "url": "http://github.com/isaacs/npm/raw/master/LICENSE"
+    }
+  ]
+}
+"#,
+        true,
+    ),
+    (
+        "misc/npm-package (JSON5)",
+        r#"{
+  name: 'npm',
+  publishConfig: {
+    'proprietary-attribs': false,
+  },
+  description: 'A package manager for node',
+  keywords: [
+    'package manager',
+    'modules',
+    'install',
+    'package.json',
+  ],
+  version: '1.1.22',
+  preferGlobal: true,
+  config: {
+    publishtest: false,
+  },
+  homepage: 'http://npmjs.org/',
+  author: 'Isaac Z. Schlueter <i@izs.me> (http://blog.izs.me)',
+  repository: {
+    type: 'git',
+    url: 'https://github.com/isaacs/npm',
+  },
+  bugs: {
+    email: 'npm-@googlegroups.com',
+    url: 'http://github.com/isaacs/npm/issues',
+  },
+  directories: {
+    doc: './doc',
+    man: './man',
+    lib: './lib',
+    bin: './bin',
+  },
+  main: './lib/npm.js',
+  bin: './bin/npm-cli.js',
+  dependencies: {
+    semver: '~1.0.14',
+    ini: '1',
+    slide: '1',
+    abbrev: '1',
+    'graceful-fs': '~1.1.1',
+    minimatch: '~0.2',
+    nopt: '1',
+    'node-uuid': '~1.3',
+    'proto-list': '1',
+    rimraf: '2',
+    request: '~2.9',
+    which: '1',
+    tar: '~0.1.12',
+    fstream: '~0.1.17',
+    'block-stream': '*',
+    inherits: '1',
+    mkdirp: '0.3',
+    read: '0',
+    'lru-cache': '1',
+    'node-gyp': '~0.4.1',
+    'fstream-npm': '0 >=0.0.5',
+    'uid-number': '0',
+    archy: '0',
+    chownr: '0',
+  },
+  bundleDependencies: [
+    'slide',
+    'ini',
+    'semver',
+    'abbrev',
+    'graceful-fs',
+    'minimatch',
+    'nopt',
+    'node-uuid',
+    'rimraf',
+    'request',
+    'proto-list',
+    'which',
+    'tar',
+    'fstream',
+    'block-stream',
+    'inherits',
+    'mkdirp',
+    'read',
+    'lru-cache',
+    'node-gyp',
+    'fstream-npm',
+    'uid-number',
+    'archy',
+    'chownr',
+  ],
+  devDependencies: {
+    ronn: 'https://github.com/isaacs/ronnjs/tarball/master',
+  },
+  engines: {
+    node: '0.6 || 0.7 || 0.8',
+    npm: '1',
+  },
+  scripts: {
+    test: 'node ./test/run.js',
+    prepublish: 'npm prune; rm -rf node_modules/*/{test,example,bench}*; make -j4 doc',
+    dumpconf: 'env | grep npm | sort | uniq',
+  },
+  licenses: [
+    {
+      type: 'MIT +no-false-attribs',
+      url: 'http://github.com/isaacs/npm/raw/master/LICENSE',
+    },
+  ],
+}
+"#,
+        true,
+    ),
+    (
+        "misc/readme-example",
+        r#"{
+    foo: 'bar',
+    while: true,
+
+    this: 'is a \
+multi-line string',
+
+    // this is an inline comment
+    here: 'is another', // inline comment
+
+    /* this is a block comment
+       that continues on another line */
+
+    hex: 0xDEADbeef,
+    half: .5,
+    delta: +10,
+    to: Infinity,   // and beyond!
+
+    finally: 'a trailing comma',
+    oh: [
+        "we shouldn't forget",
+        'arrays can have',
+        'trailing commas too',
+    ],
+}
+"#,
+        true,
+    ),
+    (
+        "misc/valid-whitespace",
+        r#"{
+    // An invalid form feed character (\x0c) has been entered before this comment.
+    // Be careful not to delete it.
+  "a": true
+}
+"#,
+        true,
+    ),
+    (
+        "new-lines/comment-cr",
+        "{\r    // This comment is terminated with `\\r`.\r}\r",
+        true,
+    ),
+    (
+        "new-lines/comment-crlf",
+        "{\r\n    // This comment is terminated with `\\r\\n`.\r\n}\r\n",
+        true,
+    ),
+    (
+        "new-lines/comment-lf",
+        "{\n    // This comment is terminated with `\\n`.\n}\n",
+        true,
+    ),
+    (
+        "new-lines/escaped-cr",
+        "{\r    // the following string contains an escaped `\\r`\r    a: 'line 1 \\\rline 2'\r}\r",
+        true,
+    ),
+    (
+        "new-lines/escaped-crlf",
+        "{\r\n    // the following string contains an escaped `\\r\\n`\r\n    a: 'line 1 \\\r\nline 2'\r\n}\r\n",
+        true,
+    ),
+    (
+        "new-lines/escaped-lf",
+        "{\n    // the following string contains an escaped `\\n`\n    a: 'line 1 \\\nline 2'\n}\n",
+        true,
+    ),
+    (
+        "numbers/float-leading-decimal-point",
+        r#".5
+"#,
+        true,
+    ),
+    (
+        "numbers/float-leading-zero",
+        r#"0.5
+"#,
+        true,
+    ),
+    (
+        "numbers/float-trailing-decimal-point-with-integer-exponent",
+        r#"5.e4
+"#,
+        true,
+    ),
+    (
+        "numbers/float-trailing-decimal-point",
+        r#"5.
+"#,
+        true,
+    ),
+    (
+        "numbers/float-with-integer-exponent",
+        r#"1.2e3
+"#,
+        true,
+    ),
+    (
+        "numbers/float",
+        r#"1.2
+"#,
+        true,
+    ),
+    (
+        "numbers/hexadecimal-empty",
+        r#"0x
+"#,
+        false,
+    ),
+    (
+        "numbers/hexadecimal-lowercase-letter",
+        r#"0xc8
+"#,
+        true,
+    ),
+    (
+        "numbers/hexadecimal-uppercase-x",
+        r#"0XC8
+"#,
+        true,
+    ),
+    (
+        "numbers/hexadecimal-with-integer-exponent",
+        r#"0xc8e4
+"#,
+        true,
+    ),
+    (
+        "numbers/hexadecimal",
+        r#"0xC8
+"#,
+        true,
+    ),
+    (
+        "numbers/infinity",
+        r#"Infinity
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-float-exponent",
+        r#"1e2.3
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-hexadecimal-exponent",
+        r#"1e0x4
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-integer-exponent",
+        r#"2e23
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-negative-float-exponent",
+        r#"1e-2.3
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-negative-hexadecimal-exponent",
+        r#"1e-0x4
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-negative-integer-exponent",
+        r#"2e-23
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-negative-zero-integer-exponent",
+        r#"5e-0
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-positive-float-exponent",
+        r#"1e+2.3
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-positive-hexadecimal-exponent",
+        r#"1e+0x4
+"#,
+        false,
+    ),
+    (
+        "numbers/integer-with-positive-integer-exponent",
+        r#"1e+2
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-positive-zero-integer-exponent",
+        r#"5e+0
+"#,
+        true,
+    ),
+    (
+        "numbers/integer-with-zero-integer-exponent",
+        r#"5e0
+"#,
+        true,
+    ),
+    (
+        "numbers/integer",
+        r#"15
+"#,
+        true,
+    ),
+    (
+        "numbers/lone-decimal-point",
+        r#".
+"#,
+        false,
+    ),
+    (
+        "numbers/nan",
+        r#"NaN
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-float-leading-decimal-point",
+        r#"-.5
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-float-leading-zero",
+        r#"-0.5
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-float-trailing-decimal-point",
+        r#"-5.
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-float",
+        r#"-1.2
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-hexadecimal",
+        r#"-0xC8
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-infinity",
+        r#"-Infinity
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-integer",
+        r#"-15
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-noctal",
+        r#"-098
+"#,
+        false,
+    ),
+    (
+        "numbers/negative-octal",
+        r#"-0123
+"#,
+        false,
+    ),
+    (
+        "numbers/negative-zero-float-leading-decimal-point",
+        r#"-.0
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-zero-float-trailing-decimal-point",
+        r#"-0.
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-zero-float",
+        r#"-0.0
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-zero-hexadecimal",
+        r#"-0x0
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-zero-integer",
+        r#"-0
+"#,
+        true,
+    ),
+    (
+        "numbers/negative-zero-octal",
+        r#"-00
+"#,
+        false,
+    ),
+    (
+        "numbers/noctal-with-leading-octal-digit",
+        r#"0780
+"#,
+        false,
+    ),
+    (
+        "numbers/noctal",
+        r#"080
+"#,
+        false,
+    ),
+    (
+        "numbers/octal",
+        r#"010
+"#,
+        false,
+    ),
+    (
+        "numbers/positive-float-leading-decimal-point",
+        r#"+.5
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-float-leading-zero",
+        r#"+0.5
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-float-trailing-decimal-point",
+        r#"+5.
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-float",
+        r#"+1.2
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-hexadecimal",
+        r#"+0xC8
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-infinity",
+        r#"+Infinity
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-integer",
+        r#"+15
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-noctal",
+        r#"+098
+"#,
+        false,
+    ),
+    (
+        "numbers/positive-octal",
+        r#"+0123
+"#,
+        false,
+    ),
+    (
+        "numbers/positive-zero-float-leading-decimal-point",
+        r#"+.0
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-zero-float-trailing-decimal-point",
+        r#"+0.
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-zero-float",
+        r#"+0.0
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-zero-hexadecimal",
+        r#"+0x0
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-zero-integer",
+        r#"+0
+"#,
+        true,
+    ),
+    (
+        "numbers/positive-zero-octal",
+        r#"+00
+"#,
+        false,
+    ),
+    (
+        "numbers/zero-float-leading-decimal-point",
+        r#".0
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-float-trailing-decimal-point",
+        r#"0.
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-float",
+        r#"0.0
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-hexadecimal",
+        r#"0x0
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-integer-with-integer-exponent",
+        r#"0e23
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-integer",
+        r#"0
+"#,
+        true,
+    ),
+    (
+        "numbers/zero-octal",
+        r#"00
+"#,
+        false,
+    ),
+    (
+        "objects/duplicate-keys",
+        r#"{
+    "a": true,
+    "a": false
+}
+"#,
+        true,
+    ),
+    (
+        "objects/empty-object",
+        r#"{}"#,
+        true,
+    ),
+    (
+        "objects/illegal-unquoted-key-number",
+        r#"{
+    10twenty: "ten twenty"
+}"#,
+        false,
+    ),
+    (
+        "objects/illegal-unquoted-key-symbol",
+        r#"{
+    multi-word: "multi-word"
+}"#,
+        false,
+    ),
+    (
+        "objects/leading-comma-object",
+        r#"{
+    ,"foo": "bar"
+}"#,
+        false,
+    ),
+    (
+        "objects/lone-trailing-comma-object",
+        r#"{
+    ,
+}"#,
+        false,
+    ),
+    (
+        "objects/no-comma-object",
+        r#"{
+    "foo": "bar"
+    "hello": "world"
+}"#,
+        false,
+    ),
+    (
+        "objects/reserved-unquoted-key",
+        r#"{
+    while: true
+}"#,
+        true,
+    ),
+    (
+        "objects/single-quoted-key",
+        r#"{
+    'hello': "world"
+}"#,
+        true,
+    ),
+    (
+        "objects/trailing-comma-object",
+        r#"{
+    "foo": "bar",
+}"#,
+        true,
+    ),
+    (
+        "objects/unquoted-keys",
+        r#"{
+    hello: "world",
+    _: "underscore",
+    $: "dollar sign",
+    one1: "numerals",
+    _$_: "multiple symbols",
+    $_$hello123world_$_: "mixed"
+}"#,
+        true,
+    ),
+    (
+        "strings/escaped-single-quoted-string",
+        r#"'I can\'t wait'"#,
+        true,
+    ),
+    (
+        "strings/multi-line-string",
+        r#"'hello\
+ world'"#,
+        true,
+    ),
+    (
+        "strings/single-quoted-string",
+        r#"'hello world'"#,
+        true,
+    ),
+    (
+        "strings/unescaped-multi-line-string",
+        r#""foo
+bar"
+"#,
+        false,
+    ),
+];
@@ -0,0 +1,163 @@
+use std::fmt;
+
+use crate::{pointer::JsonPointer, value::Value};
+
+/// 2 つの値の違いの種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DifferenceKind {
+    Added,        // 右にだけある
+    Removed,      // 左にだけある
+    Changed,      // 同じ型で値が違う
+    TypeMismatch, // 型が違う
+}
+
+/// 2 つの値の 1 か所の違い
+#[derive(Debug, Clone, PartialEq)]
+pub struct Difference {
+    pub path: JsonPointer, // 違う箇所
+    pub kind: DifferenceKind,
+    pub left: Option<Value>,
+    pub right: Option<Value>,
+}
+
+impl fmt::Display for Difference {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let path = if self.path.is_root() {
+            "/".to_string()
+        } else {
+            self.path.to_string()
+        };
+        let value = |v: &Option<Value>| v.as_ref().map_or(String::new(), Value::to_string);
+        match self.kind {
+            DifferenceKind::Added => write!(f, "{}: added {}", path, value(&self.right)),
+            DifferenceKind::Removed => write!(f, "{}: removed {}", path, value(&self.left)),
+            DifferenceKind::Changed => write!(
+                f,
+                "{}: changed {} -> {}",
+                path,
+                value(&self.left),
+                value(&self.right)
+            ),
+            DifferenceKind::TypeMismatch => write!(
+                f,
+                "{}: type changed {} -> {}",
+                path,
+                value(&self.left),
+                value(&self.right)
+            ),
+        }
+    }
+}
+
+/// `left` と `right` の違いを、深さ優先の順に列挙する
+///
+/// オブジェクトはキーごとに、配列は同じ添字どうしを比べる
+pub fn diff(left: &Value, right: &Value) -> Vec<Difference> {
+    let mut differences = vec![];
+    diff_at(left, right, &mut JsonPointer::root(), &mut differences);
+    differences
+}
+
+fn diff_at(left: &Value, right: &Value, path: &mut JsonPointer, differences: &mut Vec<Difference>) {
+    let mut child =
+        |path: &mut JsonPointer, token: &str, left: Option<&Value>, right: Option<&Value>| {
+            path.push(token);
+            match (left, right) {
+                (Some(left), Some(right)) => diff_at(left, right, path, differences),
+                (left, right) => differences.push(Difference {
+                    path: path.clone(),
+                    kind: if left.is_some() {
+                        DifferenceKind::Removed
+                    } else {
+                        DifferenceKind::Added
+                    },
+                    left: left.cloned(),
+                    right: right.cloned(),
+                }),
+            }
+            path.pop();
+        };
+    match (left, right) {
+        _ if left == right => (),
+        (Value::Object(l), Value::Object(r)) => {
+            let keys = l.keys().chain(r.keys().filter(|k| !l.contains_key(*k)));
+            for key in keys.collect::<Vec<_>>() {
+                child(path, key, l.get(key), r.get(key));
+            }
+        }
+        (Value::Array(l), Value::Array(r)) => {
+            for i in 0..l.len().max(r.len()) {
+                child(path, &i.to_string(), l.get(i), r.get(i));
+            }
+        }
+        _ => differences.push(Difference {
+            path: path.clone(),
+            kind: if kind(left) == kind(right) {
+                DifferenceKind::Changed
+            } else {
+                DifferenceKind::TypeMismatch
+            },
+            left: Some(left.clone()),
+            right: Some(right.clone()),
+        }),
+    }
+}
+
+/// 型の区別 (整数と小数はどちらも数値とみなす)
+fn kind(value: &Value) -> u8 {
+    match value {
+        Value::String(_) => 0,
+        Value::Number(_) => 1,
+        Value::Bool(_) => 2,
+        Value::Null => 3,
+        Value::Array(_) => 4,
+        Value::Object(_) => 5,
+        Value::Raw(_) => 6,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_diff() {
+        let left = parse(r#"{"a": 1, "b": [1, 2], "c": "x", "d/e": true}"#);
+        let right = parse(r#"{"a": 2, "b": [1], "c": 1, "f": null}"#);
+        let report = diff(&left, &right)
+            .iter()
+            .map(|d| d.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(
+            report,
+            [
+                "/a: changed 1 -> 2",
+                "/b/1: removed 2",
+                r#"/c: type changed "x" -> 1"#,
+                "/d~1e: removed true",
+                "/f: added null",
+            ]
+        );
+
+        let differences = diff(&parse("[1]"), &parse("[1, {}]"));
+        assert_eq!(
+            differences,
+            [Difference {
+                path: JsonPointer::root().index(1),
+                kind: DifferenceKind::Added,
+                left: None,
+                right: Some(parse("{}")),
+            }]
+        );
+        assert_eq!(
+            diff(&parse("1"), &parse("1.5"))[0].kind,
+            DifferenceKind::Changed
+        );
+        assert_eq!(
+            diff(&parse("1"), &parse("[]"))[0].to_string(),
+            "/: type changed 1 -> []"
+        );
+        assert!(diff(&left, &left).is_empty());
+    }
+}
@@ -0,0 +1,134 @@
+use std::fmt;
+
+use crate::{
+    lexer::{LexerError, Position},
+    parser::ParserError,
+};
+
+/// 字句解析・構文解析を通したエラー
+#[derive(Debug)]
+pub enum JsonError {
+    Lexer(LexerError),
+    Parser(ParserError),
+    /// `LexerOptions` で設定した上限を超えた
+    LimitExceeded {
+        limit: Limit,
+        max: usize,
+        position: Position,
+    },
+}
+
+/// 入力に課す上限の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Limit {
+    StringBytes, // 文字列 1 つのバイト数
+    Tokens,      // token の数
+    InputBytes,  // 入力全体のバイト数
+}
+
+impl JsonError {
+    /// エラーの位置 (わかる場合のみ)
+    pub fn position(&self) -> Option<Position> {
+        match self {
+            JsonError::Lexer(e) => Some(e.position),
+            JsonError::Parser(_) => None,
+            JsonError::LimitExceeded { position, .. } => Some(*position),
+        }
+    }
+}
+
+impl From<LexerError> for JsonError {
+    fn from(e: LexerError) -> JsonError {
+        JsonError::Lexer(e)
+    }
+}
+
+impl From<ParserError> for JsonError {
+    fn from(e: ParserError) -> JsonError {
+        JsonError::Parser(e)
+    }
+}
+
+impl fmt::Display for LexerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} (line {}, column {})",
+            self.msg, self.position.line, self.position.column
+        )
+    }
+}
+
+impl fmt::Display for ParserError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl fmt::Display for Limit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Limit::StringBytes => write!(f, "bytes in a string"),
+            Limit::Tokens => write!(f, "tokens"),
+            Limit::InputBytes => write!(f, "bytes of input"),
+        }
+    }
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            JsonError::Lexer(e) => e.fmt(f),
+            JsonError::Parser(e) => e.fmt(f),
+            JsonError::LimitExceeded {
+                limit,
+                max,
+                position,
+            } => write!(
+                f,
+                "error: more than {} {} (line {}, column {})",
+                max, limit, position.line, position.column
+            ),
+        }
+    }
+}
+
+impl std::error::Error for LexerError {}
+
+impl std::error::Error for ParserError {}
+
+impl std::error::Error for JsonError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            JsonError::Lexer(e) => Some(e),
+            JsonError::Parser(e) => Some(e),
+            JsonError::LimitExceeded { .. } => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::lexer::{Lexer, LexerOptions};
+
+    #[test]
+    fn test_display() {
+        let e = Lexer::new("[1,\n x]").tokenize().unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "error: an unexpected char \"x\" (line 2, column 2)"
+        );
+
+        let options = LexerOptions {
+            max_tokens: Some(2),
+            ..Default::default()
+        };
+        let e = Lexer::with_options("[1, 2]", options)
+            .tokenize()
+            .unwrap_err();
+        assert_eq!(
+            e.to_string(),
+            "error: more than 2 tokens (line 1, column 3)"
+        );
+    }
+}
@@ -0,0 +1,401 @@
+use std::{collections::BTreeMap, fmt};
+
+use crate::{pointer::PathSegment, value::Value};
+
+/// `Value::unflatten` のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub struct UnflattenError {
+    pub msg: String,
+    pub key: String, // 失敗したキー
+}
+
+impl fmt::Display for UnflattenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (key \"{}\")", self.msg, self.key)
+    }
+}
+
+impl std::error::Error for UnflattenError {}
+
+/// 平坦化したキーでの配列の添字の書き方
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum IndexStyle {
+    /// `a[0]`
+    #[default]
+    Bracket,
+    /// `a.0` (区切り文字を使う)
+    Separator,
+}
+
+/// `Value::flatten_with` の設定
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FlattenOptions {
+    /// オブジェクトのキーの区切り文字
+    pub separator: String,
+    pub index_style: IndexStyle,
+}
+
+impl Default for FlattenOptions {
+    fn default() -> Self {
+        FlattenOptions {
+            separator: ".".to_string(),
+            index_style: IndexStyle::Bracket,
+        }
+    }
+}
+
+impl Value {
+    /// 入れ子の値を `"a.b[0].c"` のようなキーを持つ 1 段のオブジェクトにする
+    pub fn flatten(&self) -> Value {
+        self.flatten_with(&FlattenOptions::default())
+    }
+
+    /// 入れ子の値を `options` の書き方のキーを持つ 1 段のオブジェクトにする
+    ///
+    /// 空のオブジェクトと配列はそのまま値として残す。根がスカラーならキーは `""` になる。
+    /// キーに含まれる区切り文字、`[`、`]`、`\\` (`IndexStyle::Separator` では数字だけのキーも) は
+    /// `\\` でエスケープする。根の直下の空のキーは、根と区別するため区切り文字 1 つで表す
+    pub fn flatten_with(&self, options: &FlattenOptions) -> Value {
+        let mut flat = BTreeMap::new();
+        flatten_into(self, None, options, &mut flat);
+        Value::Object(flat)
+    }
+
+    /// `flatten` の逆。`{"a.b[0].c": 1}` から入れ子のオブジェクトと配列を組み立てる
+    pub fn unflatten(&self) -> Result<Value, UnflattenError> {
+        self.unflatten_with(&FlattenOptions::default())
+    }
+
+    /// `options` の書き方のキーを持つオブジェクトから入れ子の値を組み立てる
+    ///
+    /// 途中の添字が飛んでいれば null で埋める。`IndexStyle::Separator` では数字だけの段を添字として読む。
+    /// 添字はキーの数より小さくなければならない (`flatten` の出力では必ずそうなる)
+    pub fn unflatten_with(&self, options: &FlattenOptions) -> Result<Value, UnflattenError> {
+        let Value::Object(flat) = self else {
+            return Err(UnflattenError {
+                msg: "error: an object is expected".to_string(),
+                key: String::new(),
+            });
+        };
+        let mut root = Value::Null;
+        for (key, value) in flat {
+            let error = |msg: &str| UnflattenError {
+                msg: format!("error: {}", msg),
+                key: key.clone(),
+            };
+            let segments = split_key(key, options).ok_or_else(|| error("an invalid key"))?;
+            // 巨大な添字で null の埋め草を確保しないよう、キーの数で抑える
+            if let Some(index) = segments.iter().find_map(|segment| match segment {
+                PathSegment::Index(index) if *index >= flat.len() => Some(index),
+                _ => None,
+            }) {
+                return Err(error(&format!("the index {} is too large", index)));
+            }
+            let mut current = &mut root;
+            for segment in segments {
+                current = match (current, segment) {
+                    (current @ Value::Null, PathSegment::Key(key)) => {
+                        *current = Value::Object(BTreeMap::new());
+                        match current {
+                            Value::Object(object) => object.entry(key).or_default(),
+                            _ => unreachable!(),
+                        }
+                    }
+                    (Value::Object(object), PathSegment::Key(key)) => {
+                        object.entry(key).or_default()
+                    }
+                    (current @ Value::Null, PathSegment::Index(index)) => {
+                        *current = Value::Array(vec![]);
+                        match current {
+                            Value::Array(array) => {
+                                array.resize(index + 1, Value::Null);
+                                &mut array[index]
+                            }
+                            _ => unreachable!(),
+                        }
+                    }
+                    (Value::Array(array), PathSegment::Index(index)) => {
+                        if array.len() <= index {
+                            array.resize(index + 1, Value::Null);
+                        }
+                        &mut array[index]
+                    }
+                    _ => return Err(error("the key conflicts with another key")),
+                };
+            }
+            match (&*current, value) {
+                (Value::Null, _) => *current = value.clone(),
+                // 空のコンテナは同じ種類の値の中身と衝突しない
+                (Value::Object(_), Value::Object(v)) if v.is_empty() => (),
+                (Value::Array(_), Value::Array(v)) if v.is_empty() => (),
+                _ => return Err(error("the key conflicts with another key")),
+            }
+        }
+        Ok(root)
+    }
+}
+
+/// 平坦化したキーを段に分ける。`""` は根を表す
+///
+/// `\\` に続く 1 文字はキーの一部として読み、区切り文字や添字とはみなさない。
+/// 先頭の区切り文字は読み飛ばし、最初の段が (空でも) オブジェクトのキーであることだけを示す
+fn split_key(key: &str, options: &FlattenOptions) -> Option<Vec<PathSegment>> {
+    if key.is_empty() {
+        return Some(vec![]);
+    }
+    let separator = options.separator.as_str();
+    let at_separator = |rest: &str| !separator.is_empty() && rest.starts_with(separator);
+    let mut segments = vec![];
+    let mut rest = key;
+    // 区切り文字の後の段は、空でもキーとして読む
+    let mut after_separator = at_separator(key);
+    if after_separator {
+        rest = &rest[separator.len()..];
+    }
+    loop {
+        // 区切り文字までの 1 段を、キーと続く `[n]` に分ける
+        let mut name = String::new();
+        let mut escaped = false;
+        let mut indexes = vec![];
+        while !rest.is_empty() && !at_separator(rest) {
+            let c = rest.chars().next()?;
+            rest = &rest[c.len_utf8()..];
+            match c {
+                '\\' if indexes.is_empty() => {
+                    let c = rest.chars().next()?;
+                    rest = &rest[c.len_utf8()..];
+                    name.push(c);
+                    escaped = true;
+                }
+                '[' if options.index_style == IndexStyle::Bracket => {
+                    let close = rest.find(']')?;
+                    indexes.push(PathSegment::Index(parse_index(&rest[..close])?));
+                    rest = &rest[close + 1..];
+                }
+                ']' if options.index_style == IndexStyle::Bracket => return None,
+                c if indexes.is_empty() => name.push(c),
+                // `a[0]b` のように添字の後にキーが続くことはない
+                _ => return None,
+            }
+        }
+        match options.index_style {
+            IndexStyle::Bracket => {
+                if !name.is_empty() || indexes.is_empty() || after_separator {
+                    segments.push(PathSegment::from(name));
+                }
+                segments.extend(indexes);
+            }
+            IndexStyle::Separator => segments.push(match parse_index(&name) {
+                Some(index) if !escaped => PathSegment::Index(index),
+                _ => PathSegment::from(name),
+            }),
+        }
+        match rest.strip_prefix(separator) {
+            Some(next) if !separator.is_empty() => rest = next,
+            _ => return Some(segments),
+        }
+        after_separator = true;
+    }
+}
+
+/// オブジェクトのキーを、区切り文字や添字と取り違えないよう `\\` でエスケープする
+fn escape_key(key: &str, options: &FlattenOptions) -> String {
+    let separator = options.separator.as_str();
+    let mut escaped = String::with_capacity(key.len());
+    // `IndexStyle::Separator` では数字だけのキーを添字と区別する
+    if options.index_style == IndexStyle::Separator && parse_index(key).is_some() {
+        escaped.push('\\');
+    }
+    let mut rest = key;
+    while let Some(c) = rest.chars().next() {
+        if !separator.is_empty() && rest.starts_with(separator) {
+            for c in separator.chars() {
+                escaped.push('\\');
+                escaped.push(c);
+            }
+            rest = &rest[separator.len()..];
+            continue;
+        }
+        if c == '\\' || (options.index_style == IndexStyle::Bracket && matches!(c, '[' | ']')) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+        rest = &rest[c.len_utf8()..];
+    }
+    escaped
+}
+
+/// 先頭に 0 のない 10 進数の添字
+fn parse_index(s: &str) -> Option<usize> {
+    if s.is_empty() || (s.len() > 1 && s.starts_with('0')) || !s.bytes().all(|b| b.is_ascii_digit())
+    {
+        return None;
+    }
+    s.parse().ok()
+}
+
+/// `prefix` は `value` を指す平坦化したキー。根なら None
+fn flatten_into(
+    value: &Value,
+    prefix: Option<String>,
+    options: &FlattenOptions,
+    flat: &mut BTreeMap<String, Value>,
+) {
+    match value {
+        Value::Object(object) if !object.is_empty() => {
+            for (key, value) in object {
+                let key = escape_key(key, options);
+                let key = match &prefix {
+                    // 根を表す `""` と区別する
+                    None if key.is_empty() => options.separator.clone(),
+                    None => key,
+                    Some(prefix) => format!("{}{}{}", prefix, options.separator, key),
+                };
+                flatten_into(value, Some(key), options, flat);
+            }
+        }
+        Value::Array(array) if !array.is_empty() => {
+            for (i, value) in array.iter().enumerate() {
+                let key = match (options.index_style, &prefix) {
+                    (IndexStyle::Bracket, prefix) => {
+                        format!("{}[{}]", prefix.as_deref().unwrap_or(""), i)
+                    }
+                    (IndexStyle::Separator, None) => i.to_string(),
+                    (IndexStyle::Separator, Some(prefix)) => {
+                        format!("{}{}{}", prefix, options.separator, i)
+                    }
+                };
+                flatten_into(value, Some(key), options, flat);
+            }
+        }
+        value => {
+            flat.insert(prefix.unwrap_or_default(), value.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_flatten() {
+        let value = parse(r#"{"a": {"b": [{"c": 1}, 2]}, "d": "x", "e": {}, "f": []}"#);
+        assert_eq!(
+            value.flatten(),
+            parse(r#"{"a.b[0].c": 1, "a.b[1]": 2, "d": "x", "e": {}, "f": []}"#)
+        );
+
+        let options = FlattenOptions {
+            separator: "/".to_string(),
+            index_style: IndexStyle::Separator,
+        };
+        assert_eq!(
+            value.flatten_with(&options),
+            parse(r#"{"a/b/0/c": 1, "a/b/1": 2, "d": "x", "e": {}, "f": []}"#)
+        );
+
+        assert_eq!(
+            parse("[1, [2]]").flatten(),
+            parse(r#"{"[0]": 1, "[1][0]": 2}"#)
+        );
+        assert_eq!(parse("3").flatten(), parse(r#"{"": 3}"#));
+    }
+
+    #[test]
+    fn test_unflatten() {
+        let value =
+            parse(r#"{"a": {"b": [{"c": 1}, 2]}, "d": "x", "e": {}, "f": [[], {"g": null}]}"#);
+        assert_eq!(value.flatten().unflatten().unwrap(), value);
+        let options = FlattenOptions {
+            separator: "/".to_string(),
+            index_style: IndexStyle::Separator,
+        };
+        assert_eq!(
+            value
+                .flatten_with(&options)
+                .unflatten_with(&options)
+                .unwrap(),
+            value
+        );
+        assert_eq!(
+            parse("[1, [2]]").flatten().unflatten().unwrap(),
+            parse("[1, [2]]")
+        );
+        assert_eq!(parse("3").flatten().unflatten().unwrap(), parse("3"));
+
+        assert_eq!(
+            parse(r#"{"a[2]": 1, "b": 2, "c": 3}"#).unflatten().unwrap(),
+            parse(r#"{"a": [null, null, 1], "b": 2, "c": 3}"#)
+        );
+        let e = parse(r#"{"a[2]": 1}"#).unflatten().unwrap_err();
+        assert_eq!(e.msg, "error: the index 2 is too large");
+        assert!(parse(r#"{"a[18446744073709551615]": 1}"#)
+            .unflatten()
+            .is_err());
+        assert!(parse(r#"{"a[9999999999]": 1}"#).unflatten().is_err());
+
+        let e = parse(r#"{"a": 1, "a.b": 2}"#).unflatten().unwrap_err();
+        assert_eq!(e.key, "a.b");
+        assert!(parse(r#"{"a[0]": 1, "a.b": 2}"#).unflatten().is_err());
+        assert!(parse(r#"{"a[x]": 1}"#).unflatten().is_err());
+        assert!(parse("[1]").unflatten().is_err());
+
+        // 空のキーは根と区別する
+        for json in [
+            r#"{"": {"a": 1}}"#,
+            r#"{"": 1, "b": 2}"#,
+            r#"{"": [1, {"": 2}]}"#,
+            r#"{"": {"": 1}}"#,
+            r#"{"a": {"": [1]}}"#,
+            r#"{".": 1, "": 2, "..": {"": 3}}"#,
+        ] {
+            let value = parse(json);
+            assert_eq!(value.flatten().unflatten().unwrap(), value, "{}", json);
+            assert_eq!(
+                value
+                    .flatten_with(&options)
+                    .unflatten_with(&options)
+                    .unwrap(),
+                value,
+                "{}",
+                json
+            );
+        }
+        assert_eq!(
+            parse(r#"{"": {"a": 1}, "b": [2]}"#).flatten(),
+            parse(r#"{"..a": 1, "b[0]": 2}"#)
+        );
+        assert_eq!(
+            parse(r#"{"": 1, "a": {"": [2]}}"#).flatten(),
+            parse(r#"{".": 1, "a.[0]": 2}"#)
+        );
+    }
+
+    #[test]
+    fn test_escape() {
+        let value = parse(r#"{"a.b": 1, "c[0]": {"d\\e": 2}, "f": {"0": [3], "1.5": 4}}"#);
+        let flat = value.flatten();
+        assert_eq!(
+            flat,
+            parse(r#"{"a\\.b": 1, "c\\[0\\].d\\\\e": 2, "f.0[0]": 3, "f.1\\.5": 4}"#)
+        );
+        assert_eq!(flat.unflatten().unwrap(), value);
+
+        let options = FlattenOptions {
+            separator: "/".to_string(),
+            index_style: IndexStyle::Separator,
+        };
+        let flat = value.flatten_with(&options);
+        assert_eq!(
+            flat,
+            parse(r#"{"a.b": 1, "c[0]/d\\\\e": 2, "f/\\0/0": 3, "f/1.5": 4}"#)
+        );
+        assert_eq!(flat.unflatten_with(&options).unwrap(), value);
+
+        assert!(parse(r#"{"a[0]b": 1}"#).unflatten().is_err());
+        assert!(parse(r#"{"a]": 1}"#).unflatten().is_err());
+        assert!(parse(r#"{"a\\": 1}"#).unflatten().is_err());
+    }
+}
@@ -0,0 +1,297 @@
+//! 書き出しの体裁を差し替えるための `Formatter`
+//!
+//! `write_with` が値を辿って各フックを呼ぶ。既定の実装は空白のない JSON を書くので、
+//! 変えたいフックだけ実装すればよい。`PrinterOptions` による書き出しもこの仕組みを使う
+
+use std::{collections::BTreeMap, fmt};
+
+use crate::{
+    number::Number,
+    printer::JsonPrinter,
+    value::{RawValue, Value},
+};
+
+/// 書き出しの各場面で呼ばれるフック
+///
+/// 配列は `begin_array`、要素ごとに `begin_array_value` → 値 → `end_array_value`、`end_array` の順に、
+/// オブジェクトは `begin_object`、メンバーごとに `begin_object_key` → `write_key` →
+/// `begin_object_value` → 値 → `end_object_value`、`end_object` の順に呼ばれる
+pub trait Formatter {
+    fn write_null(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str("null")
+    }
+
+    fn write_bool(&mut self, w: &mut dyn fmt::Write, b: bool) -> fmt::Result {
+        w.write_str(if b { "true" } else { "false" })
+    }
+
+    /// JSON で表せない NaN や無限大は null にする
+    fn write_number(&mut self, w: &mut dyn fmt::Write, n: &Number) -> fmt::Result {
+        if n.is_f64() && !n.as_f64().is_finite() {
+            w.write_str("null")
+        } else {
+            write!(w, "{}", n)
+        }
+    }
+
+    /// 文字列を `"` で囲み、エスケープして書く
+    fn write_string(&mut self, w: &mut dyn fmt::Write, s: &str) -> fmt::Result {
+        JsonPrinter::write_escaped(w, s, &Default::default())
+    }
+
+    /// 未解析の部分木を元の文字列のまま書く
+    fn write_raw(&mut self, w: &mut dyn fmt::Write, raw: &RawValue) -> fmt::Result {
+        w.write_str(raw.get())
+    }
+
+    /// `array` は書き出す配列 (幅を測るなどの先読みに使える)
+    fn begin_array(&mut self, w: &mut dyn fmt::Write, _array: &[Value]) -> fmt::Result {
+        w.write_char('[')
+    }
+
+    fn end_array(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_char(']')
+    }
+
+    fn begin_array_value(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            w.write_char(',')
+        }
+    }
+
+    fn end_array_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        Ok(())
+    }
+
+    /// `object` は書き出すオブジェクト (幅を測るなどの先読みに使える)
+    fn begin_object(
+        &mut self,
+        w: &mut dyn fmt::Write,
+        _object: &BTreeMap<String, Value>,
+    ) -> fmt::Result {
+        w.write_char('{')
+    }
+
+    fn end_object(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_char('}')
+    }
+
+    fn begin_object_key(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        if first {
+            Ok(())
+        } else {
+            w.write_char(',')
+        }
+    }
+
+    fn write_key(&mut self, w: &mut dyn fmt::Write, key: &str) -> fmt::Result {
+        self.write_string(w, key)
+    }
+
+    fn begin_object_value(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_char(':')
+    }
+
+    fn end_object_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        Ok(())
+    }
+}
+
+/// 空白のない JSON を書く `Formatter`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CompactFormatter;
+
+impl Formatter for CompactFormatter {}
+
+/// 要素ごとに改行して字下げする `Formatter`
+#[derive(Debug, Clone)]
+pub struct PrettyFormatter {
+    indent: String,
+    depth: usize,
+    has_value: bool, // 今のコンテナに要素を書いたか
+}
+
+impl Default for PrettyFormatter {
+    fn default() -> Self {
+        Self::with_indent("  ")
+    }
+}
+
+impl PrettyFormatter {
+    /// 2 文字の空白で字下げする
+    pub fn new() -> PrettyFormatter {
+        Self::default()
+    }
+
+    /// 1 段の字下げに `indent` を使う
+    pub fn with_indent(indent: &str) -> PrettyFormatter {
+        PrettyFormatter {
+            indent: indent.to_string(),
+            depth: 0,
+            has_value: false,
+        }
+    }
+
+    fn newline(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_char('\n')?;
+        for _ in 0..self.depth {
+            w.write_str(&self.indent)?;
+        }
+        Ok(())
+    }
+
+    fn begin(&mut self, w: &mut dyn fmt::Write, open: char) -> fmt::Result {
+        self.depth += 1;
+        self.has_value = false;
+        w.write_char(open)
+    }
+
+    fn end(&mut self, w: &mut dyn fmt::Write, close: char) -> fmt::Result {
+        self.depth -= 1;
+        if self.has_value {
+            self.newline(w)?;
+        }
+        w.write_char(close)
+    }
+
+    fn begin_item(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        if !first {
+            w.write_char(',')?;
+        }
+        self.newline(w)
+    }
+}
+
+impl Formatter for PrettyFormatter {
+    fn begin_array(&mut self, w: &mut dyn fmt::Write, _array: &[Value]) -> fmt::Result {
+        self.begin(w, '[')
+    }
+
+    fn end_array(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.end(w, ']')
+    }
+
+    fn begin_array_value(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        self.begin_item(w, first)
+    }
+
+    fn end_array_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object(
+        &mut self,
+        w: &mut dyn fmt::Write,
+        _object: &BTreeMap<String, Value>,
+    ) -> fmt::Result {
+        self.begin(w, '{')
+    }
+
+    fn end_object(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.end(w, '}')
+    }
+
+    fn begin_object_key(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        self.begin_item(w, first)
+    }
+
+    fn begin_object_value(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str(": ")
+    }
+
+    fn end_object_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+/// `value` を辿り、`formatter` のフックを呼んで `w` に書き出す
+pub fn write_with<F: Formatter + ?Sized>(
+    w: &mut dyn fmt::Write,
+    value: &Value,
+    formatter: &mut F,
+) -> fmt::Result {
+    match value {
+        Value::Null => formatter.write_null(w),
+        Value::Bool(b) => formatter.write_bool(w, *b),
+        Value::Number(n) => formatter.write_number(w, n),
+        Value::String(s) => formatter.write_string(w, s),
+        Value::Raw(raw) => formatter.write_raw(w, raw),
+        Value::Array(array) => {
+            formatter.begin_array(w, array)?;
+            for (i, value) in array.iter().enumerate() {
+                formatter.begin_array_value(w, i == 0)?;
+                write_with(w, value, formatter)?;
+                formatter.end_array_value(w)?;
+            }
+            formatter.end_array(w)
+        }
+        Value::Object(object) => {
+            formatter.begin_object(w, object)?;
+            for (i, (key, value)) in object.iter().enumerate() {
+                formatter.begin_object_key(w, i == 0)?;
+                formatter.write_key(w, key)?;
+                formatter.begin_object_value(w)?;
+                write_with(w, value, formatter)?;
+                formatter.end_object_value(w)?;
+            }
+            formatter.end_object(w)
+        }
+    }
+}
+
+/// `value` を `formatter` で書き出した文字列を返す
+pub fn to_string_with_formatter<F: Formatter + ?Sized>(value: &Value, formatter: &mut F) -> String {
+    let mut buf = String::new();
+    // String への書き込みは失敗しない
+    let _ = write_with(&mut buf, value, formatter);
+    buf
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    /// 区切りの後に空白を入れ、最後に改行を書く
+    struct Spaced;
+
+    impl Formatter for Spaced {
+        fn begin_array_value(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+            w.write_str(if first { "" } else { ", " })
+        }
+
+        fn begin_object_key(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+            w.write_str(if first { "" } else { ", " })
+        }
+
+        fn begin_object_value(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+            w.write_str(": ")
+        }
+
+        fn write_key(&mut self, w: &mut dyn fmt::Write, key: &str) -> fmt::Result {
+            w.write_str(key)
+        }
+    }
+
+    #[test]
+    fn test_formatter() {
+        let value = parse(r#"{"a": [1, 2.5, "x"], "b": {"c": null, "d": []}, "e": {}}"#);
+        assert_eq!(
+            to_string_with_formatter(&value, &mut CompactFormatter),
+            r#"{"a":[1,2.5,"x"],"b":{"c":null,"d":[]},"e":{}}"#
+        );
+        assert_eq!(
+            to_string_with_formatter(&value, &mut Spaced),
+            r#"{a: [1, 2.5, "x"], b: {c: null, d: []}, e: {}}"#
+        );
+        assert_eq!(
+            to_string_with_formatter(&value, &mut PrettyFormatter::with_indent("\t")),
+            "{\n\t\"a\": [\n\t\t1,\n\t\t2.5,\n\t\t\"x\"\n\t],\n\t\"b\": {\n\t\t\"c\": null,\n\t\t\
+             \"d\": []\n\t},\n\t\"e\": {}\n}"
+        );
+    }
+}
@@ -1,207 +1,1163 @@
-use std::{iter::Peekable, str::Chars};
+use std::{borrow::Cow, cell::Cell};
 
+use crate::{
+    error::{JsonError, Limit},
+    scan,
+};
+
+#[derive(Debug, PartialEq, Clone)]
+pub enum Token<'a> {
+    String(Cow<'a, str>), // 文字列 (エスケープがなければ入力を借用する)
+    Number { value: f64, raw: Cow<'a, str> }, // 数値 (raw は元の表記)
+    Integer(i64),         // 整数
+    Bool(bool),           // boolean
+    Null,                 // null
+    WhiteSpace,           // 空白
+    Comment(Cow<'a, str>), // コメント (`//` や `/* */` を含む)
+    LeftBrace,            // {
+    RightBrace,           // }
+    LeftBracket,          // [
+    RightBracket,         // ]
+    Comma,                // ,
+    Colon,                // :
+    Raw(Cow<'a, str>),    // 未解析の JSON
+}
+
+/// 入力上の文字列と位置を伴う `Token`
 #[derive(Debug, PartialEq, Clone)]
-pub enum Token {
-    String(String), // 文字列
-    Number(f64),    // 数値
-    Bool(bool),     // boolean
-    Null,           // null
-    WhiteSpace,     // 空白
-    LeftBrace,      // {
-    RightBrace,     // }
-    LeftBracket,    // [
-    RightBracket,   // ]
-    Comma,          // ,
-    Colon,          // :
+pub struct Lexeme<'a> {
+    pub token: Token<'a>,
+    pub text: &'a str, // 入力上の文字列 (空白やコメントも含め、連結すると入力に戻る)
+    pub offset: usize, // 先頭からのバイト数
+}
+
+impl Token<'_> {
+    /// 入力の借用をやめ、入力より長く使える `Token` にする
+    pub fn into_owned(self) -> Token<'static> {
+        let owned = |s: Cow<'_, str>| Cow::Owned(s.into_owned());
+        match self {
+            Token::String(s) => Token::String(owned(s)),
+            Token::Number { value, raw } => Token::Number {
+                value,
+                raw: owned(raw),
+            },
+            Token::Integer(n) => Token::Integer(n),
+            Token::Bool(b) => Token::Bool(b),
+            Token::Null => Token::Null,
+            Token::WhiteSpace => Token::WhiteSpace,
+            Token::Comment(s) => Token::Comment(owned(s)),
+            Token::LeftBrace => Token::LeftBrace,
+            Token::RightBrace => Token::RightBrace,
+            Token::LeftBracket => Token::LeftBracket,
+            Token::RightBracket => Token::RightBracket,
+            Token::Comma => Token::Comma,
+            Token::Colon => Token::Colon,
+            Token::Raw(s) => Token::Raw(owned(s)),
+        }
+    }
+}
+
+const BOM: char = '\u{FEFF}';
+
+/// 入力中の位置
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Position {
+    pub offset: usize, // 先頭からのバイト数
+    pub line: usize,   // 行 (1 始まり)
+    pub column: usize, // 列 (1 始まり、文字単位)
+}
+
+impl Default for Position {
+    fn default() -> Position {
+        Position {
+            offset: 0,
+            line: 1,
+            column: 1,
+        }
+    }
+}
+
+/// 入力の終端で読みかけになっている token の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LexState {
+    #[default]
+    Ready, // token の途中ではない
+    InString {
+        escape_pending: bool,
+    }, // 文字列 (エスケープの途中かどうか)
+    InNumber,  // 数値
+    InLiteral, // true、false、null、識別子のキー
+    InComment, // コメント
+    InRaw,     // `raw_keys` の値
+}
+
+/// チャンクをまたいで引き継ぐ字句解析の文脈
+#[derive(Debug, Clone, Default)]
+pub(crate) struct Suspended {
+    origin: Position, // 入力の先頭の、ストリーム全体での位置
+    tokens: usize,
+    raw_key: bool,
+    raw_pending: bool,
+    nesting: Vec<bool>,
+    key_position: bool,
+    pending: Option<Pending>, // 読みかけの token
+}
+
+/// チャンクの終端で読みかけになった token の、読み終えた部分
+#[derive(Debug, Clone)]
+struct Pending {
+    start: Position, // token の開始位置
+    partial: Partial,
+}
+
+#[derive(Debug, Clone)]
+enum Partial {
+    String(PartialString),
+    /// 数値、リテラル、識別子のキー (読み終えた入力上の文字列)
+    Word {
+        class: WordClass,
+        text: String,
+    },
+    /// コメント (読み終えた入力上の文字列)
+    Comment {
+        text: String,
+        block: bool,
+    },
+    /// `raw_keys` の値 (読み終えた入力上の文字列、入れ子の深さ、文字列の中かどうか)
+    Raw {
+        text: String,
+        depth: usize,
+        in_string: bool,
+    },
+}
+
+/// 読みかけの文字列
+#[derive(Debug, Clone)]
+struct PartialString {
+    quote: char,
+    buf: String,          // デコード済みの中身
+    utf16: Vec<u16>,      // 結合待ちの `\u` エスケープ
+    bytes: usize,         // 入力上の中身のバイト数
+    escape_pending: bool, // エスケープの途中で途切れたか
+}
+
+/// 語として続けて読む文字の種類
+#[derive(Debug, Clone, Copy)]
+enum WordClass {
+    Number,
+    Literal,
+    Identifier,
+}
+
+impl Partial {
+    fn state(&self) -> LexState {
+        match self {
+            Partial::String(s) => LexState::InString {
+                escape_pending: s.escape_pending,
+            },
+            Partial::Word {
+                class: WordClass::Number,
+                ..
+            } => LexState::InNumber,
+            Partial::Word { .. } => LexState::InLiteral,
+            Partial::Comment { .. } => LexState::InComment,
+            Partial::Raw { .. } => LexState::InRaw,
+        }
+    }
 }
 
 /// 字句解析中のエラー
 #[derive(Debug)]
 pub struct LexerError {
     pub msg: String,
+    pub position: Position,
 }
 
 impl LexerError {
-    fn new(msg: &str) -> LexerError {
+    fn new(msg: &str, position: Position) -> LexerError {
         LexerError {
             msg: msg.to_string(),
+            position,
         }
     }
 }
 
+/// 字句解析の設定
+#[derive(Debug, Clone, Default)]
+pub struct LexerOptions {
+    /// 値を解析せず `Token::Raw` として残すオブジェクトのキー (深さは問わない)
+    pub raw_keys: Vec<String>,
+    /// 未定義のエスケープ (`\q` など) をエラーにせず、そのまま残す
+    pub relaxed_escapes: bool,
+    /// 文字列中のエスケープされていない制御文字 (U+0000 - U+001F) を許す
+    pub allow_control_chars: bool,
+    /// `+1` や `.5`、`1.` のような RFC 8259 の文法に沿わない数値を許す
+    pub relaxed_numbers: bool,
+    /// `//` と `/* */` のコメントを空白と同様に読み飛ばす
+    pub allow_comments: bool,
+    /// `'single quoted'` の文字列 (JSON5) を許す
+    pub allow_single_quotes: bool,
+    /// `{foo: 1}` のような識別子のキー (JSON5) を許す
+    pub allow_unquoted_keys: bool,
+    /// `0xFF`、`Infinity`、`NaN`、`+1`、`.5`、`5.` の数値 (JSON5) を許す
+    pub allow_extended_numbers: bool,
+    /// JSON5 のエスケープ (`\v`、`\0`、`\xHH`、行継続、その他の文字はその文字自身) を許す
+    pub json5_escapes: bool,
+    /// 文字列 1 つの最大バイト数 (エスケープを含む入力上のバイト数)
+    pub max_string_bytes: Option<usize>,
+    /// token の最大数 (空白とコメントは数えない)
+    pub max_tokens: Option<usize>,
+    /// 入力全体の最大バイト数
+    pub max_input_bytes: Option<usize>,
+}
+
 /// 字句解析
 pub struct Lexer<'a> {
-    chars: Peekable<Chars<'a>>,
+    input: &'a str,
+    pos: usize,
+    start: usize,
+    bom: bool,
+    invalid_byte: Option<u8>,
+    input_bytes: usize,
+    tokens: usize,
+    failed: bool,
+    origin: Position,
+    partial: bool,             // 終端で読みかけの token を続きのチャンクに持ち越すか
+    eof: Cell<bool>,           // 現在の token が入力の終端の先を見ようとしたか
+    pending: Option<Pending>,  // 読みかけの token
+    resumed: Option<Position>, // 前のチャンクから続く token の開始位置
+    state: LexState,
+    options: LexerOptions,
+    raw_key: bool,
+    raw_pending: bool,
+    nesting: Vec<bool>, // 入れ子の各段がオブジェクトかどうか
+    key_position: bool,
 }
 
 impl<'a> Lexer<'a> {
-    pub fn new(input: &str) -> Lexer {
+    pub fn new(input: &'a str) -> Lexer<'a> {
+        Self::with_options(input, LexerOptions::default())
+    }
+
+    pub fn with_options(input: &'a str, options: LexerOptions) -> Lexer<'a> {
+        // 先頭の BOM は読み飛ばす
+        let bom = input.starts_with(BOM);
         Lexer {
-            chars: input.chars().peekable(),
+            input,
+            pos: if bom { BOM.len_utf8() } else { 0 },
+            start: 0,
+            bom,
+            invalid_byte: None,
+            input_bytes: input.len(),
+            tokens: 0,
+            failed: false,
+            origin: Position::default(),
+            partial: false,
+            eof: Cell::new(false),
+            pending: None,
+            resumed: None,
+            state: LexState::Ready,
+            options,
+            raw_key: false,
+            raw_pending: false,
+            nesting: vec![],
+            key_position: false,
         }
     }
 
-    pub fn tokenize(&mut self) -> Result<Vec<Token>, LexerError> {
-        let mut tokens = vec![];
-        while let Some(token) = self.next_token()? {
-            match token {
-                Token::WhiteSpace => (),
-                _ => tokens.push(token),
+    /// バイト列から字句解析する。UTF-8 として不正なバイトは、そこまで読み進めた時点でエラーになる
+    pub fn from_bytes(input: &'a [u8]) -> Lexer<'a> {
+        Self::from_bytes_with_options(input, LexerOptions::default())
+    }
+
+    pub fn from_bytes_with_options(input: &'a [u8], options: LexerOptions) -> Lexer<'a> {
+        match std::str::from_utf8(input) {
+            Ok(s) => Self::with_options(s, options),
+            Err(e) => {
+                let (valid, invalid) = input.split_at(e.valid_up_to());
+                // valid_up_to までは UTF-8 として正しいことが保証されている
+                let s = std::str::from_utf8(valid).unwrap_or_default();
+                Lexer {
+                    invalid_byte: invalid.first().copied(),
+                    input_bytes: input.len(),
+                    ..Self::with_options(s, options)
+                }
             }
         }
+    }
+
+    pub fn tokenize(&mut self) -> Result<Vec<Token<'a>>, JsonError> {
+        self.by_ref().collect()
+    }
+
+    /// 空白 (BOM を含む) とコメントも残し、入力上の文字列を伴う `Lexeme` の列にする
+    ///
+    /// 各 `Lexeme::text` を順に連結すると入力と 1 バイトも違わず一致する
+    pub fn tokenize_lossless(&mut self) -> Result<Vec<Lexeme<'a>>, JsonError> {
+        let mut lexemes = vec![];
+        if self.bom && self.pos == BOM.len_utf8() {
+            lexemes.push(Lexeme {
+                token: Token::WhiteSpace,
+                text: &self.input[..self.pos],
+                offset: self.origin.offset,
+            });
+        }
+        while let Some(token) = self.next_counted_token()? {
+            lexemes.push(Lexeme {
+                token,
+                text: &self.input[self.start..self.pos],
+                offset: self.origin.offset + self.start,
+            });
+        }
+        Ok(lexemes)
+    }
+
+    /// 前のチャンクまでの文脈 `suspended` を引き継いで `input` を字句解析する
+    ///
+    /// `partial` の場合、終端で読みかけの token はエラーにせず読まずに残す
+    pub(crate) fn resume(
+        input: &'a str,
+        options: LexerOptions,
+        suspended: Suspended,
+        input_bytes: usize,
+        partial: bool,
+    ) -> Lexer<'a> {
+        let lexer = if suspended.origin.offset == 0 {
+            Self::with_options(input, options)
+        } else {
+            Lexer {
+                pos: 0,
+                bom: false,
+                ..Self::with_options(input, options)
+            }
+        };
+        Lexer {
+            input_bytes,
+            tokens: suspended.tokens,
+            origin: suspended.origin,
+            partial,
+            raw_key: suspended.raw_key,
+            raw_pending: suspended.raw_pending,
+            nesting: suspended.nesting,
+            key_position: suspended.key_position,
+            pending: suspended.pending,
+            ..lexer
+        }
+    }
+
+    /// 読み終えたバイト数、引き継ぐ文脈、読みかけの token の種類を返す
+    pub(crate) fn suspend(self) -> (usize, Suspended, LexState) {
+        let origin = self.position(self.pos);
+        let suspended = Suspended {
+            origin,
+            tokens: self.tokens,
+            raw_key: self.raw_key,
+            raw_pending: self.raw_pending,
+            nesting: self.nesting,
+            key_position: self.key_position,
+            pending: self.pending,
+        };
+        (self.pos, suspended, self.state)
+    }
+
+    /// 入力が BOM で始まっていたか
+    pub fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// 次の 1 文字を返す
+    fn peek(&self) -> Option<char> {
+        let c = self.input[self.pos..].chars().next();
+        if c.is_none() {
+            self.eof.set(true);
+        }
+        c
+    }
 
-        Ok(tokens)
+    /// 1 文字進め、その文字を返す
+    fn bump(&mut self) -> Option<char> {
+        let c = self.peek()?;
+        self.pos += c.len_utf8();
+        Some(c)
     }
 
     /// 1 文字進め、`Token` を返す
-    fn next_return_token(&mut self, token: Token) -> Option<Token> {
-        self.chars.next();
+    fn next_return_token(&mut self, token: Token<'a>) -> Option<Token<'a>> {
+        self.bump();
         Some(token)
     }
 
+    /// `offset` の行と列を求める
+    fn position(&self, offset: usize) -> Position {
+        let before = &self.input[..offset];
+        let (line, column) = match before.rfind('\n') {
+            Some(i) => (
+                self.origin.line + before.matches('\n').count(),
+                before[i + 1..].chars().count() + 1,
+            ),
+            None => {
+                let line_start = if self.bom { BOM.len_utf8() } else { 0 };
+                let column = self.origin.column + before[line_start..].chars().count();
+                (self.origin.line, column)
+            }
+        };
+        Position {
+            offset: self.origin.offset + offset,
+            line,
+            column,
+        }
+    }
+
+    /// 現在の token の開始位置 (前のチャンクから続く token ならその開始位置)
+    fn token_position(&self) -> Position {
+        self.resumed.unwrap_or_else(|| self.position(self.start))
+    }
+
+    /// 現在の token の開始位置を指すエラーを返す
+    fn error(&self, msg: &str) -> JsonError {
+        JsonError::Lexer(LexerError::new(msg, self.token_position()))
+    }
+
+    fn error_at(&self, offset: usize, msg: &str) -> JsonError {
+        JsonError::Lexer(LexerError::new(msg, self.position(offset)))
+    }
+
+    /// 現在の token の開始位置で上限を超えたことを示すエラーを返す
+    fn limit_error(&self, limit: Limit, max: usize) -> JsonError {
+        JsonError::LimitExceeded {
+            limit,
+            max,
+            position: self.token_position(),
+        }
+    }
+
+    /// 空白とコメントを読み飛ばして次の `Token` を返す
+    fn next_significant_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        loop {
+            match self.next_counted_token()? {
+                Some(Token::WhiteSpace | Token::Comment(_)) => continue,
+                token => return Ok(token),
+            }
+        }
+    }
+
+    /// 上限を確かめながら次の `Token` を返す。空白とコメントは数えない
+    fn next_counted_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        if let Some(max) = self.options.max_input_bytes {
+            if self.input_bytes > max {
+                self.start = 0;
+                self.resumed = None;
+                return Err(self.limit_error(Limit::InputBytes, max));
+            }
+        }
+        let token = self.next_token()?;
+        if !matches!(token, Some(Token::WhiteSpace | Token::Comment(_)) | None) {
+            self.tokens += 1;
+            if let Some(max) = self.options.max_tokens {
+                if self.tokens > max {
+                    return Err(self.limit_error(Limit::Tokens, max));
+                }
+            }
+        }
+        Ok(token)
+    }
+
     /// 文字列から `Token` を返す
-    fn next_token(&mut self) -> Result<Option<Token>, LexerError> {
-        match self.chars.peek() {
+    fn next_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        self.start = self.pos;
+        let raw_pending = self.raw_pending;
+        self.eof.set(false);
+        self.resumed = None;
+        let token = if let Some(Pending { start, partial }) = self.pending.take() {
+            self.resumed = Some(start);
+            self.continue_token(partial)
+        } else if self.raw_pending && self.peek().is_some_and(|c| !self.is_trivia_start(c)) {
+            self.raw_pending = false;
+            self.parse_raw_token()
+        } else {
+            self.scan_token()
+        };
+
+        // 読みかけの token は、読み終えた部分を状態として続きのチャンクに持ち越す
+        if let Some(pending) = &self.pending {
+            self.state = pending.partial.state();
+            return Ok(None);
+        }
+        // それ以外で終端の先を見ようとした token (`/` だけのコメントの開始など) は、
+        // 読まずに残して続きのチャンクで読み直す
+        if self.partial && self.eof.get() && self.pos > self.start {
+            self.state = self.pending_state(raw_pending);
+            self.pos = self.start;
+            self.raw_pending = raw_pending;
+            return Ok(None);
+        }
+
+        // 不正なバイトに到達して止まった場合は、そのバイトの位置を指すエラーにする
+        let token = match (token, self.invalid_byte) {
+            (Ok(None) | Err(_), Some(byte)) if self.pos == self.input.len() => {
+                return Err(self.error_at(
+                    self.pos,
+                    &format!("error: an invalid UTF-8 byte 0x{:02X}", byte),
+                ));
+            }
+            (token, _) => token?,
+        };
+        match &token {
+            Some(Token::String(key)) => {
+                self.raw_key = self.options.raw_keys.iter().any(|k| k == key)
+            }
+            Some(Token::Colon) => self.raw_pending = std::mem::take(&mut self.raw_key),
+            Some(Token::WhiteSpace | Token::Comment(_)) | None => (),
+            _ => self.raw_key = false,
+        }
+        // オブジェクトのキーが来る位置かどうかを追跡する
+        match &token {
+            Some(Token::LeftBrace) => {
+                self.nesting.push(true);
+                self.key_position = true;
+            }
+            Some(Token::LeftBracket) => {
+                self.nesting.push(false);
+                self.key_position = false;
+            }
+            Some(Token::RightBrace | Token::RightBracket) => {
+                self.nesting.pop();
+                self.key_position = false;
+            }
+            Some(Token::Comma) => self.key_position = self.nesting.last() == Some(&true),
+            Some(Token::WhiteSpace | Token::Comment(_)) | None => (),
+            _ => self.key_position = false,
+        }
+        Ok(token)
+    }
+
+    /// `self.start` から始まる読みかけの token の種類
+    fn pending_state(&self, raw_pending: bool) -> LexState {
+        match self.input[self.start..].chars().next() {
+            _ if raw_pending => LexState::InRaw,
+            Some('/') => LexState::InComment,
+            Some(c) if c.is_numeric() || matches!(c, '+' | '-' | '.' | 'I' | 'N') => {
+                LexState::InNumber
+            }
+            Some(_) => LexState::InLiteral,
+            None => LexState::Ready,
+        }
+    }
+
+    /// 読みかけの token を続きのチャンクに持ち越す
+    fn suspend_token(&mut self, partial: Partial) -> Result<Option<Token<'a>>, JsonError> {
+        self.pending = Some(Pending {
+            start: self.token_position(),
+            partial,
+        });
+        Ok(None)
+    }
+
+    /// 前のチャンクから続く token の残りを読む
+    fn continue_token(&mut self, partial: Partial) -> Result<Option<Token<'a>>, JsonError> {
+        match partial {
+            Partial::String(s) => self.parse_string_token(s.quote, Some(s)),
+            Partial::Word { class, mut text } => {
+                let len = self.word_len(class);
+                text.push_str(&self.input[..len]);
+                self.pos = len;
+                if self.partial && len == self.input.len() {
+                    return self.suspend_token(Partial::Word { class, text });
+                }
+                self.finish_word(class, &text)
+            }
+            Partial::Comment { text, block } => self.scan_comment(Some(text), block),
+            Partial::Raw {
+                text,
+                depth,
+                in_string,
+            } => self.scan_raw(Some(text), depth, in_string),
+        }
+    }
+
+    /// 前のチャンクまでに読んだ部分 `prefix` に、現在の token の入力上の文字列を続ける
+    fn joined(&self, prefix: Option<String>) -> Cow<'a, str> {
+        let text = &self.input[self.start..self.pos];
+        match prefix {
+            Some(prefix) => Cow::Owned(prefix + text),
+            None => Cow::Borrowed(text),
+        }
+    }
+
+    fn scan_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        match self.peek() {
             Some(c) => match c {
-                // 1 文字の token
-                c if c.is_whitespace() || *c == '\n' => {
-                    Ok(self.next_return_token(Token::WhiteSpace))
+                // 連続する空白はまとめて 1 つの token にする
+                ' ' | '\t' | '\n' | '\r' => {
+                    self.pos += scan::whitespace_run(&self.input.as_bytes()[self.pos..]);
+                    Ok(Some(Token::WhiteSpace))
                 }
+                // 1 文字の token
+                c if c.is_whitespace() => Ok(self.next_return_token(Token::WhiteSpace)),
                 '{' => Ok(self.next_return_token(Token::LeftBrace)),
                 '}' => Ok(self.next_return_token(Token::RightBrace)),
                 '[' => Ok(self.next_return_token(Token::LeftBracket)),
                 ']' => Ok(self.next_return_token(Token::RightBracket)),
                 ',' => Ok(self.next_return_token(Token::Comma)),
                 ':' => Ok(self.next_return_token(Token::Colon)),
+                // コメント
+                '/' if self.options.allow_comments => self.parse_comment_token(),
 
                 // 複数文字の token
+                // 識別子のキー
+                c if self.key_position
+                    && self.options.allow_unquoted_keys
+                    && (c.is_alphabetic() || matches!(c, '_' | '$')) =>
+                {
+                    self.word_token(WordClass::Identifier)
+                }
                 // 文字列
                 '"' => {
-                    self.chars.next();
-                    self.parse_string_token()
+                    self.bump();
+                    self.parse_string_token('"', None)
+                }
+                '\'' if self.options.allow_single_quotes => {
+                    self.bump();
+                    self.parse_string_token('\'', None)
                 }
                 // 数値
-                c if c.is_numeric() || matches!(c, '+' | '-' | '.') => self.parse_number_token(),
-                // boolean
-                't' => self.parse_bool_token(true),
-                'f' => self.parse_bool_token(false),
-                // null
-                'n' => self.parse_null_token(),
+                c if c.is_numeric() || matches!(c, '+' | '-' | '.') => {
+                    self.word_token(WordClass::Number)
+                }
+                // boolean, null
+                't' | 'f' | 'n' => self.word_token(WordClass::Literal),
+                // Infinity, NaN
+                'I' | 'N' if self.options.allow_extended_numbers => {
+                    self.word_token(WordClass::Number)
+                }
 
                 // その他
-                _ => Err(LexerError::new(&format!(
-                    "error: an unexpected char \"{}\"",
-                    c
-                ))),
+                _ => Err(self.error(&format!("error: an unexpected char \"{}\"", c))),
             },
             None => Ok(None),
         }
     }
 
-    fn parse_null_token(&mut self) -> Result<Option<Token>, LexerError> {
-        let s = (0..4).filter_map(|_| self.chars.next()).collect::<String>();
-        if s == "null" {
-            Ok(Some(Token::Null))
-        } else {
-            Err(LexerError::new(&format!(
-                "error: a null value is expected \"{}\"",
-                s
-            )))
+    /// 空白またはコメントの開始文字か
+    fn is_trivia_start(&self, c: char) -> bool {
+        c.is_whitespace() || (c == '/' && self.options.allow_comments)
+    }
+
+    fn parse_comment_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        self.bump();
+        match self.bump() {
+            Some('/') => self.scan_comment(None, false),
+            Some('*') => self.scan_comment(None, true),
+            _ => Err(self.error("error: a // or /* comment is expected")),
         }
     }
 
-    fn parse_bool_token(&mut self, b: bool) -> Result<Option<Token>, LexerError> {
-        if b {
-            let s = (0..4).filter_map(|_| self.chars.next()).collect::<String>();
-            if s == "true" {
-                Ok(Some(Token::Bool(true)))
-            } else {
-                Err(LexerError::new(&format!(
-                    "error: a boolean true is expected \"{}\"",
-                    s
-                )))
+    /// コメントの残りを読む。`prefix` は前のチャンクまでに読んだ部分
+    fn scan_comment(
+        &mut self,
+        prefix: Option<String>,
+        block: bool,
+    ) -> Result<Option<Token<'a>>, JsonError> {
+        if !block {
+            // 改行はコメントに含めない
+            while self
+                .peek()
+                .is_some_and(|c| !matches!(c, '\n' | '\r' | '\u{2028}' | '\u{2029}'))
+            {
+                self.bump();
             }
         } else {
-            let s = (0..5).filter_map(|_| self.chars.next()).collect::<String>();
-            if s == "false" {
-                Ok(Some(Token::Bool(false)))
-            } else {
-                Err(LexerError::new(&format!(
-                    "error: a boolean false is expected \"{}\"",
-                    s
-                )))
+            let body_start = if prefix.is_some() { 0 } else { self.pos };
+            loop {
+                match self.bump() {
+                    Some('*') if self.peek() == Some('/') => {
+                        self.bump();
+                        break;
+                    }
+                    Some(_) => (),
+                    None if self.partial => {
+                        // 終端の `*` は `*/` の途中かもしれないので、続きのチャンクで読み直す
+                        if self.pos > body_start && self.input[..self.pos].ends_with('*') {
+                            self.pos -= 1;
+                        }
+                        break;
+                    }
+                    None => return Err(self.error("error: an unterminated block comment")),
+                }
+            }
+        }
+        if self.partial && self.eof.get() {
+            let text = self.joined(prefix).into_owned();
+            return self.suspend_token(Partial::Comment { text, block });
+        }
+        Ok(Some(Token::Comment(self.joined(prefix))))
+    }
+
+    /// `self.pos` から続く、`class` の語として読む文字のバイト数
+    fn word_len(&self, class: WordClass) -> usize {
+        let extended = self.options.allow_extended_numbers;
+        let rest = &self.input[self.pos..];
+        rest.find(|c: char| match class {
+            WordClass::Number => {
+                !(c.is_numeric()
+                    || matches!(c, '+' | '-' | '.' | 'e' | 'E')
+                    || (extended && c.is_ascii_alphanumeric()))
+            }
+            WordClass::Literal => !(c.is_alphanumeric() || c == '_'),
+            WordClass::Identifier => !(c.is_alphanumeric() || matches!(c, '_' | '$')),
+        })
+        .unwrap_or(rest.len())
+    }
+
+    /// 数値、リテラル、識別子のキーを読む。入力の終端まで続く語は続きのチャンクに持ち越す
+    fn word_token(&mut self, class: WordClass) -> Result<Option<Token<'a>>, JsonError> {
+        if self.partial && self.pos + self.word_len(class) == self.input.len() {
+            let text = self.input[self.start..].to_string();
+            self.pos = self.input.len();
+            return self.suspend_token(Partial::Word { class, text });
+        }
+        match (class, self.peek()) {
+            (WordClass::Number, _) => self.parse_number_token(),
+            (WordClass::Identifier, _) => self.parse_identifier_token(),
+            (WordClass::Literal, Some('t')) => self.parse_bool_token(true),
+            (WordClass::Literal, Some('f')) => self.parse_bool_token(false),
+            (WordClass::Literal, _) => self.parse_null_token(),
+        }
+    }
+
+    /// チャンクをまたいで読み終えた語 `text` を `Token` にする
+    fn finish_word(&self, class: WordClass, text: &str) -> Result<Option<Token<'a>>, JsonError> {
+        let mut lexer = Lexer {
+            origin: self.token_position(),
+            ..Lexer::with_options(text, self.options.clone())
+        };
+        let token = lexer.word_token(class)?.map(Token::into_owned);
+        match text[lexer.pos..].chars().next() {
+            Some(c) => {
+                Err(lexer.error_at(lexer.pos, &format!("error: an unexpected char \"{}\"", c)))
             }
+            None => Ok(token),
+        }
+    }
+
+    /// 識別子を読み、キーとして `Token::String` を返す
+    fn parse_identifier_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        while self
+            .peek()
+            .is_some_and(|c| c.is_alphanumeric() || matches!(c, '_' | '$'))
+        {
+            self.bump();
+        }
+        let ident = &self.input[self.start..self.pos];
+        Ok(Some(Token::String(Cow::Borrowed(ident))))
+    }
+
+    fn parse_null_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        self.parse_literal_token("null", Token::Null)
+    }
+
+    fn parse_bool_token(&mut self, b: bool) -> Result<Option<Token<'a>>, JsonError> {
+        if b {
+            self.parse_literal_token("true", Token::Bool(true))
+        } else {
+            self.parse_literal_token("false", Token::Bool(false))
+        }
+    }
+
+    /// 英数字の並びを 1 語として読み、`keyword` と一致すれば `token` を返す
+    ///
+    /// 一致しなければ読んだ語をそのまま示すエラーにする。後続の `,` や `]` などは読まない
+    fn parse_literal_token(
+        &mut self,
+        keyword: &str,
+        token: Token<'a>,
+    ) -> Result<Option<Token<'a>>, JsonError> {
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.bump();
+        }
+        let word = &self.input[self.start..self.pos];
+        if word == keyword {
+            Ok(Some(token))
+        } else {
+            Err(self.error(&format!(
+                "error: \"{}\" is expected but found \"{}\"",
+                keyword, word
+            )))
         }
     }
 
-    fn parse_number_token(&mut self) -> Result<Option<Token>, LexerError> {
-        let mut num_buf = String::new();
-        while let Some(&c) = self.chars.peek() {
+    fn parse_number_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        if self.options.allow_extended_numbers {
+            if let Some(token) = self.parse_extended_number_token()? {
+                return Ok(Some(token));
+            }
+        }
+        while let Some(c) = self.peek() {
             if c.is_numeric() || matches!(c, '+' | '-' | 'e' | 'E' | '.') {
-                self.chars.next();
-                num_buf.push(c);
+                self.bump();
             } else {
                 break;
             }
         }
+        let num_buf = &self.input[self.start..self.pos];
+        if !self.options.relaxed_numbers
+            && !Self::is_json_number(num_buf, self.options.allow_extended_numbers)
+        {
+            return Err(self.error(&format!("error: an invalid number \"{}\"", num_buf)));
+        }
+        // `-0` は整数の 0 と書き方が異なるので、元の表記を残せるよう `Token::Number` にする
+        if !num_buf.contains(['.', 'e', 'E']) && num_buf != "-0" {
+            if let Ok(integer) = num_buf.parse::<i64>() {
+                return Ok(Some(Token::Integer(integer)));
+            }
+        }
         match num_buf.parse::<f64>() {
-            Ok(number) => Ok(Some(Token::Number(number))),
-            Err(e) => Err(LexerError::new(&format!("error: {}", e))),
+            Ok(value) => Ok(Some(Token::Number {
+                value,
+                raw: Cow::Borrowed(num_buf),
+            })),
+            Err(e) => Err(self.error(&format!("error: {}", e))),
         }
     }
 
-    fn parse_string_token(&mut self) -> Result<Option<Token>, LexerError> {
-        let mut str_buf = String::new();
-        let mut utf16_buf = vec![];
+    /// 符号付きを含む `0xFF`、`Infinity`、`NaN` を読む。どれでもなければ何も読まずに None を返す
+    fn parse_extended_number_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        let rest = &self.input[self.pos..];
+        let (negative, unsigned) = match rest.strip_prefix('-') {
+            Some(unsigned) => (true, unsigned),
+            None => (false, rest.strip_prefix('+').unwrap_or(rest)),
+        };
+        let sign_len = rest.len() - unsigned.len();
+
+        let value = if unsigned.starts_with("Infinity") {
+            self.pos += sign_len + "Infinity".len();
+            f64::INFINITY
+        } else if unsigned.starts_with("NaN") {
+            self.pos += sign_len + "NaN".len();
+            f64::NAN
+        } else if unsigned.starts_with("0x") || unsigned.starts_with("0X") {
+            let digits = &unsigned[2..];
+            let digits = &digits[..digits.bytes().take_while(u8::is_ascii_hexdigit).count()];
+            if digits.len() == unsigned.len() - 2 {
+                self.eof.set(true);
+            }
+            self.pos += sign_len + 2 + digits.len();
+            return match i64::from_str_radix(digits, 16) {
+                Ok(n) => Ok(Some(Token::Integer(if negative { -n } else { n }))),
+                Err(_) => Err(self.error(&format!(
+                    "error: an invalid hexadecimal number \"{}\"",
+                    &self.input[self.start..self.pos]
+                ))),
+            };
+        } else {
+            // 入力の残りが Infinity や NaN の途中までなら、続きがあるかもしれない
+            if !unsigned.is_empty()
+                && ("Infinity".starts_with(unsigned) || "NaN".starts_with(unsigned))
+            {
+                self.eof.set(true);
+            }
+            return Ok(None);
+        };
+
+        Ok(Some(Token::Number {
+            value: if negative { -value } else { value },
+            raw: Cow::Borrowed(&self.input[self.start..self.pos]),
+        }))
+    }
+
+    /// RFC 8259 の数値の文法 `[ - ] int [ frac ] [ exp ]` に沿っているか
+    ///
+    /// `json5` の場合は `+` の符号と、小数点の前後どちらかの数字の省略も許す
+    pub(crate) fn is_json_number(s: &str, json5: bool) -> bool {
+        let mut bytes = s.bytes().peekable();
+        let digits = |bytes: &mut std::iter::Peekable<std::str::Bytes>| {
+            let mut count = 0;
+            while bytes.next_if(u8::is_ascii_digit).is_some() {
+                count += 1;
+            }
+            count
+        };
 
-        while let Some(c1) = self.chars.next() {
+        if json5 {
+            bytes.next_if(|b| matches!(b, b'+' | b'-'));
+        } else {
+            bytes.next_if_eq(&b'-');
+        }
+        // int: 0 または 0 以外で始まる数字列
+        let int_digits = match bytes.next_if_eq(&b'0') {
+            Some(_) => 1,
+            None => digits(&mut bytes),
+        };
+        // frac
+        let frac_digits = bytes.next_if_eq(&b'.').map(|_| digits(&mut bytes));
+        let valid = match frac_digits {
+            Some(frac_digits) if json5 => int_digits + frac_digits > 0,
+            Some(frac_digits) => int_digits > 0 && frac_digits > 0,
+            None => int_digits > 0,
+        };
+        if !valid {
+            return false;
+        }
+        // exp
+        if bytes.next_if(|b| matches!(b, b'e' | b'E')).is_some() {
+            bytes.next_if(|b| matches!(b, b'+' | b'-'));
+            if digits(&mut bytes) == 0 {
+                return false;
+            }
+        }
+        bytes.next().is_none()
+    }
+
+    /// 開始の `quote` の直後から、対応する `quote` までを文字列として読む
+    ///
+    /// `resumed` は前のチャンクまでに読んだ部分。続きのチャンクがある場合、
+    /// 入力の終端で途切れた文字列は読み終えた部分を持ち越す
+    fn parse_string_token(
+        &mut self,
+        quote: char,
+        resumed: Option<PartialString>,
+    ) -> Result<Option<Token<'a>>, JsonError> {
+        // エスケープが現れるまでは入力を借用し、現れたら str_buf に複写する
+        let content_start = self.pos;
+        let (mut escaped, mut str_buf, mut utf16_buf, prior_bytes) = match resumed {
+            Some(s) => (true, s.buf, s.utf16, s.bytes),
+            None => (false, String::new(), vec![], 0),
+        };
+
+        loop {
+            // 特別な扱いの要らない部分はまとめて読み飛ばす
+            let run = scan::string_run(&self.input.as_bytes()[self.pos..], quote as u8);
+            if escaped && run > 0 {
+                self.push_utf16(&mut str_buf, &mut utf16_buf)?;
+                str_buf.push_str(&self.input[self.pos..self.pos + run]);
+            }
+            self.pos += run;
+            if let Some(max) = self.options.max_string_bytes {
+                if prior_bytes + self.pos - content_start > max {
+                    return Err(self.limit_error(Limit::StringBytes, max));
+                }
+            }
+            let Some(c1) = self.bump() else {
+                break;
+            };
             match c1 {
                 '\\' => {
-                    let c2 = self
-                        .chars
-                        .next()
-                        .ok_or_else(|| LexerError::new("error: a next char is expected"))?;
-                    if matches!(c2, '"' | '\\' | '/' | 'b' | 'f' | 'n' | 'r' | 't') {
-                        Self::push_utf16(&mut str_buf, &mut utf16_buf)?;
-                        str_buf.push('\\');
-                        str_buf.push(c2);
-                    } else if c2 == 'u' {
-                        let hexs = (0..4)
-                            .filter_map(|_| {
-                                let c = self.chars.next()?;
-                                if c.is_ascii_hexdigit() {
-                                    Some(c)
-                                } else {
-                                    None
-                                }
-                            })
-                            .collect::<Vec<_>>();
-
-                        match u16::from_str_radix(&hexs.iter().collect::<String>(), 16) {
-                            Ok(code_point) => utf16_buf.push(code_point),
-                            Err(e) => {
-                                return Err(LexerError::new(&format!(
-                                    "error: a unicode character is expected {}",
-                                    e
-                                )))
-                            }
-                        };
+                    let escape_start = self.pos - 1;
+                    if !escaped {
+                        escaped = true;
+                        str_buf.push_str(&self.input[content_start..escape_start]);
                     }
+                    let result = self.parse_escape(&mut str_buf, &mut utf16_buf);
+                    // 終端で途切れたエスケープは、`\` から続きのチャンクで読み直す
+                    if self.partial && self.eof.get() {
+                        self.pos = escape_start;
+                        return self.suspend_token(Partial::String(PartialString {
+                            quote,
+                            buf: str_buf,
+                            utf16: utf16_buf,
+                            bytes: prior_bytes + escape_start - content_start,
+                            escape_pending: true,
+                        }));
+                    }
+                    result?;
+                }
+                c1 if c1 == quote && !escaped => {
+                    let s = &self.input[content_start..self.pos - 1];
+                    return Ok(Some(Token::String(Cow::Borrowed(s))));
                 }
-                '\"' => {
-                    Self::push_utf16(&mut str_buf, &mut utf16_buf)?;
-                    return Ok(Some(Token::String(str_buf)));
+                c1 if c1 == quote => {
+                    self.push_utf16(&mut str_buf, &mut utf16_buf)?;
+                    return Ok(Some(Token::String(Cow::Owned(str_buf))));
                 }
-                _ => {
-                    Self::push_utf16(&mut str_buf, &mut utf16_buf)?;
+                c1 if (c1 as u32) < 0x20 && !self.options.allow_control_chars => {
+                    return Err(self.error_at(
+                        self.pos - 1,
+                        &format!("error: an unescaped control character U+{:04X}", c1 as u32),
+                    ));
+                }
+                _ if escaped => {
+                    self.push_utf16(&mut str_buf, &mut utf16_buf)?;
                     str_buf.push(c1);
                 }
+                _ => (),
             }
         }
-        Ok(None)
+        if self.partial {
+            if !escaped {
+                str_buf.push_str(&self.input[content_start..self.pos]);
+            }
+            return self.suspend_token(Partial::String(PartialString {
+                quote,
+                buf: str_buf,
+                utf16: utf16_buf,
+                bytes: prior_bytes + self.pos - content_start,
+                escape_pending: false,
+            }));
+        }
+        Err(self.error("error: an unterminated string"))
+    }
+
+    /// `\` に続くエスケープを読み、元の文字を `str_buf` (`\u` は `utf16_buf`) に加える
+    ///
+    /// 続きのチャンクがあり、入力の終端の先を見ようとした場合は何も加えない
+    fn parse_escape(
+        &mut self,
+        str_buf: &mut String,
+        utf16_buf: &mut Vec<u16>,
+    ) -> Result<(), JsonError> {
+        let c2 = self
+            .bump()
+            .ok_or_else(|| self.error("error: a next char is expected"))?;
+        if let Some(decoded) = self.decode_escape(c2) {
+            if self.partial && self.eof.get() {
+                return Ok(());
+            }
+            self.push_utf16(str_buf, utf16_buf)?;
+            str_buf.push(decoded);
+        } else if c2 == 'u' {
+            let code_point = self.parse_unicode_escape(self.pos - 2)?;
+            utf16_buf.push(code_point);
+        } else if c2 == 'x' && self.options.json5_escapes {
+            let code_point = self.parse_hex_escape(self.pos - 2, 2)?;
+            self.push_utf16(str_buf, utf16_buf)?;
+            str_buf.extend(char::from_u32(code_point.into()));
+        } else if self.options.json5_escapes && matches!(c2, '\n' | '\r' | '\u{2028}' | '\u{2029}')
+        {
+            // 行継続: `\` と改行は取り除く
+            if c2 == '\r' && self.peek() == Some('\n') {
+                self.bump();
+            }
+        } else if self.options.json5_escapes && !c2.is_ascii_digit() {
+            self.push_utf16(str_buf, utf16_buf)?;
+            str_buf.push(c2);
+        } else if self.options.relaxed_escapes {
+            self.push_utf16(str_buf, utf16_buf)?;
+            str_buf.push('\\');
+            str_buf.push(c2);
+        } else {
+            let escape_start = self.pos - 1 - c2.len_utf8();
+            return Err(self.error_at(
+                escape_start,
+                &format!("error: an unknown escape sequence \"\\{}\"", c2),
+            ));
+        }
+        Ok(())
+    }
+
+    /// `\u` に続く 4 桁の 16 進数を読む
+    fn parse_unicode_escape(&mut self, escape_start: usize) -> Result<u16, JsonError> {
+        self.parse_hex_escape(escape_start, 4)
+    }
+
+    /// `\u` や `\x` に続く `len` 桁の 16 進数を読む
+    fn parse_hex_escape(&mut self, escape_start: usize, len: usize) -> Result<u16, JsonError> {
+        let mut code_point = 0;
+        for _ in 0..len {
+            match self.peek().and_then(|c| c.to_digit(16)) {
+                Some(digit) => {
+                    self.bump();
+                    code_point = code_point * 16 + digit as u16;
+                }
+                None => {
+                    let found = &self.input[escape_start..self.pos];
+                    return Err(self.error_at(
+                        escape_start,
+                        &format!("error: {} hex digits are expected \"{}\"", len, found),
+                    ));
+                }
+            }
+        }
+        Ok(code_point)
+    }
+
+    /// `\` に続く 1 文字のエスケープを元の文字に戻す
+    fn decode_escape(&self, c: char) -> Option<char> {
+        match c {
+            '\'' if self.options.allow_single_quotes => Some('\''),
+            '"' => Some('"'),
+            '\\' => Some('\\'),
+            '/' => Some('/'),
+            'b' => Some('\u{8}'),
+            'f' => Some('\u{c}'),
+            'n' => Some('\n'),
+            'r' => Some('\r'),
+            't' => Some('\t'),
+            'v' if self.options.json5_escapes => Some('\u{b}'),
+            '0' if self.options.json5_escapes
+                && !self.peek().is_some_and(|c| c.is_ascii_digit()) =>
+            {
+                Some('\0')
+            }
+            _ => None,
+        }
+    }
+
+    /// 値の構造だけを読み飛ばし、元の文字列のまま `Token::Raw` として返す
+    fn parse_raw_token(&mut self) -> Result<Option<Token<'a>>, JsonError> {
+        self.scan_raw(None, 0, false)
+    }
+
+    /// raw 値の残りを読む。`prefix`、`depth`、`in_string` は前のチャンクまでに読んだ部分とその状態
+    fn scan_raw(
+        &mut self,
+        prefix: Option<String>,
+        mut depth: usize,
+        mut in_string: bool,
+    ) -> Result<Option<Token<'a>>, JsonError> {
+        while let Some(c) = self.peek() {
+            if in_string {
+                self.bump();
+                match c {
+                    // 終端で途切れたエスケープは、`\` から続きのチャンクで読み直す
+                    '\\' if self.bump().is_none() => {
+                        self.pos -= 1;
+                        break;
+                    }
+                    '"' => {
+                        in_string = false;
+                        if depth == 0 {
+                            break;
+                        }
+                    }
+                    _ => (),
+                }
+                continue;
+            }
+            match c {
+                '"' => in_string = true,
+                '{' | '[' => depth += 1,
+                '}' | ']' if depth > 0 => depth -= 1,
+                c if depth == 0 && (c.is_whitespace() || matches!(c, ',' | '}' | ']')) => break,
+                _ => (),
+            }
+            self.bump();
+            if depth == 0 && matches!(c, '}' | ']') {
+                break;
+            }
+        }
+
+        if self.partial && self.eof.get() {
+            let text = self.joined(prefix).into_owned();
+            return self.suspend_token(Partial::Raw {
+                text,
+                depth,
+                in_string,
+            });
+        }
+        if in_string {
+            return Err(self.error("error: an unterminated string"));
+        }
+        if depth > 0 {
+            return Err(self.error("error: an unterminated raw value"));
+        }
+        let raw = self.joined(prefix);
+        if raw.is_empty() {
+            return Err(self.error("error: a raw value is expected"));
+        }
+        Ok(Some(Token::Raw(raw)))
     }
 
-    fn push_utf16(str_buf: &mut String, utf16: &mut Vec<u16>) -> Result<(), LexerError> {
+    fn push_utf16(&self, str_buf: &mut String, utf16: &mut Vec<u16>) -> Result<(), JsonError> {
         if utf16.is_empty() {
             return Ok(());
         }
@@ -211,13 +1167,29 @@ impl<'a> Lexer<'a> {
                 utf16.clear();
             }
             Err(e) => {
-                return Err(LexerError::new(&format!("error: {}", e)));
+                return Err(self.error(&format!("error: {}", e)));
             }
         };
         Ok(())
     }
 }
 
+/// 空白を除いた `Token` を 1 つずつ返す。エラーの後は `None` を返す
+impl<'a> Iterator for Lexer<'a> {
+    type Item = Result<Token<'a>, JsonError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.failed {
+            return None;
+        }
+        let result = self.next_significant_token();
+        if result.is_err() {
+            self.failed = true;
+        }
+        result.transpose()
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -245,56 +1217,145 @@ mod tests {
         // integer
         let num = "1234567890";
         let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(1234567890f64));
-
-        let num = "+123";
-        let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(123f64));
+        assert_eq!(tokens[0], Token::Integer(1234567890));
 
         // float
         let num = "-0.001";
         let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(-0.001));
-
-        let num = ".001";
-        let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(0.001));
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: -0.001,
+                raw: "-0.001".into()
+            }
+        );
 
         // exponent
         let num = "1e-10";
         let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(0.0000000001));
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 0.0000000001,
+                raw: "1e-10".into()
+            }
+        );
 
-        let num = "+2E10";
+        let num = "2E+10";
+        let tokens = Lexer::new(num).tokenize().unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 20000000000f64,
+                raw: "2E+10".into()
+            }
+        );
+
+        let num = "0";
+        let tokens = Lexer::new(num).tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Integer(0));
+
+        let num = "9007199254740993";
         let tokens = Lexer::new(num).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::Number(20000000000f64));
+        assert_eq!(tokens[0], Token::Integer(9007199254740993));
+
+        let num = "-9223372036854775808";
+        let tokens = Lexer::new(num).tokenize().unwrap();
+        assert_eq!(tokens[0], Token::Integer(i64::MIN));
+
+        // i64 で表せない整数は f64 になる
+        let num = "9223372036854775808";
+        let tokens = Lexer::new(num).tokenize().unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 9223372036854775808f64,
+                raw: "9223372036854775808".into()
+            }
+        );
+
+        // 元の表記が残る
+        let num = "1.50";
+        let tokens = Lexer::new(num).tokenize().unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 1.5,
+                raw: "1.50".into()
+            }
+        );
+    }
+
+    #[test]
+    fn strict_number_token() {
+        for num in [
+            "+123", ".5", "1.", "--3", "1e+e2", "01", "-", "1e", "-.5", "1.5.3",
+        ] {
+            assert!(Lexer::new(num).tokenize().is_err(), "{}", num);
+        }
+
+        let options = LexerOptions {
+            relaxed_numbers: true,
+            ..Default::default()
+        };
+        let num = "+123";
+        let tokens = Lexer::with_options(num, options.clone())
+            .tokenize()
+            .unwrap();
+        assert_eq!(tokens[0], Token::Integer(123));
+
+        let num = ".001";
+        let tokens = Lexer::with_options(num, options.clone())
+            .tokenize()
+            .unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 0.001,
+                raw: ".001".into()
+            }
+        );
+
+        let num = "+2E10";
+        let tokens = Lexer::with_options(num, options).tokenize().unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::Number {
+                value: 20000000000f64,
+                raw: "+2E10".into()
+            }
+        );
     }
 
     #[test]
     fn test_string() {
         let s = "\"togatoga123\"";
         let tokens = Lexer::new(s).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::String("togatoga123".to_string()));
+        assert_eq!(tokens[0], Token::String("togatoga123".into()));
 
         let s = "\"あいうえお\"";
         let tokens = Lexer::new(s).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::String("あいうえお".to_string()));
+        assert_eq!(tokens[0], Token::String("あいうえお".into()));
 
         let s = r#""\u3042\u3044\u3046abc""#; //あいうabc
 
         let tokens = Lexer::new(s).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::String("あいうabc".to_string()));
+        assert_eq!(tokens[0], Token::String("あいうabc".into()));
 
         let s = r#" " \b \f \n \r \t \/ \" ""#;
         let tokens = Lexer::new(s).tokenize().unwrap();
         assert_eq!(
             tokens[0],
-            Token::String(r#" \b \f \n \r \t \/ \" "#.to_string())
+            Token::String(" \u{8} \u{c} \n \r \t / \" ".into())
         );
 
+        let s = r#""C:\\path\\to""#;
+        let tokens = Lexer::new(s).tokenize().unwrap();
+        assert_eq!(tokens[0], Token::String(r"C:\path\to".into()));
+
         let s = r#""\uD83D\uDE04\uD83D\uDE07\uD83D\uDC7A""#;
         let tokens = Lexer::new(s).tokenize().unwrap();
-        assert_eq!(tokens[0], Token::String(r#"😄😇👺"#.to_string()));
+        assert_eq!(tokens[0], Token::String(r#"😄😇👺"#.into()));
     }
 
     #[test]
@@ -316,34 +1377,37 @@ mod tests {
             // start {
             Token::LeftBrace,
             // begin: "number": 123,
-            Token::String("number".to_string()),
+            Token::String("number".into()),
             Token::Colon,
-            Token::Number(123f64),
+            Token::Integer(123),
             Token::Comma,
             // end
 
             // begin: "boolean": true,
-            Token::String("boolean".to_string()),
+            Token::String("boolean".into()),
             Token::Colon,
             Token::Bool(true),
             Token::Comma,
             // end
 
             // begin: "string": "togatoga",
-            Token::String("string".to_string()),
+            Token::String("string".into()),
             Token::Colon,
-            Token::String("togatoga".to_string()),
+            Token::String("togatoga".into()),
             Token::Comma,
             // end
 
             // begin: "object": {
-            Token::String("object".to_string()),
+            Token::String("object".into()),
             Token::Colon,
             Token::LeftBrace,
             // begin: "number": 2E10,
-            Token::String("number".to_string()),
+            Token::String("number".into()),
             Token::Colon,
-            Token::Number(20000000000f64),
+            Token::Number {
+                value: 20000000000f64,
+                raw: "2E10".into(),
+            },
             // end
             Token::RightBrace,
             // end
@@ -366,7 +1430,7 @@ mod tests {
             Token::Bool(true),
             Token::Comma,
             Token::LeftBrace,
-            Token::String("キー".to_string()),
+            Token::String("キー".into()),
             Token::Colon,
             Token::Null,
             Token::RightBrace,
@@ -377,4 +1441,414 @@ mod tests {
             .zip(result_tokens.iter())
             .for_each(|(x, y)| assert_eq!(x, y));
     }
+
+    #[test]
+    fn raw_token() {
+        let options = LexerOptions {
+            raw_keys: vec!["payload".to_string()],
+            ..Default::default()
+        };
+        let s = r#"{"id": 1, "payload": {"a": [1, "}]"], "b": null}, "rest": "payload"}"#;
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(
+            tokens[7],
+            Token::Raw(r#"{"a": [1, "}]"], "b": null}"#.into())
+        );
+        assert_eq!(tokens[11], Token::String("payload".into()));
+
+        let s = r#"[{"payload": 3.14}, {"payload" : "\"x\""}]"#;
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(tokens[4], Token::Raw("3.14".into()));
+        assert_eq!(tokens[10], Token::Raw(r#""\"x\"""#.into()));
+
+        let s = r#"{"payload": [1, 2"#;
+        assert!(Lexer::with_options(s, options).tokenize().is_err());
+    }
+
+    #[test]
+    fn unicode_escape_error() {
+        let s = r#""\u12G4""#;
+        let e = Lexer::new(s).tokenize().unwrap_err();
+        assert_eq!(
+            e.position().unwrap(),
+            Position {
+                offset: 1,
+                line: 1,
+                column: 2
+            }
+        );
+
+        let s = "[\n  \"ab\\u12\"]";
+        let e = Lexer::new(s).tokenize().unwrap_err();
+        assert_eq!(
+            e.position().unwrap(),
+            Position {
+                offset: 7,
+                line: 2,
+                column: 6
+            }
+        );
+    }
+
+    #[test]
+    fn unknown_escape() {
+        let s = r#""a\qb""#;
+        let e = Lexer::new(s).tokenize().unwrap_err();
+        assert_eq!(e.position().unwrap().offset, 2);
+
+        let options = LexerOptions {
+            relaxed_escapes: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options).tokenize().unwrap();
+        assert_eq!(tokens[0], Token::String(r"a\qb".into()));
+    }
+
+    #[test]
+    fn control_char() {
+        let s = "\"line\nbreak\"";
+        let e = Lexer::new(s).tokenize().unwrap_err();
+        assert_eq!(e.position().unwrap().offset, 5);
+        assert_eq!(e.position().unwrap().line, 1);
+
+        let options = LexerOptions {
+            allow_control_chars: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options).tokenize().unwrap();
+        assert_eq!(tokens[0], Token::String("line\nbreak".into()));
+    }
+
+    #[test]
+    fn bom() {
+        let s = "\u{FEFF}{\"key\": null}";
+        let mut lexer = Lexer::new(s);
+        let tokens = lexer.tokenize().unwrap();
+        assert!(lexer.has_bom());
+        assert_eq!(tokens[0], Token::LeftBrace);
+        assert_eq!(tokens.len(), 5);
+
+        let mut lexer = Lexer::new("null");
+        lexer.tokenize().unwrap();
+        assert!(!lexer.has_bom());
+
+        let s = "\u{FEFF}[x]";
+        let e = Lexer::new(s).tokenize().unwrap_err();
+        assert_eq!(e.position().unwrap().offset, 4);
+        assert_eq!(e.position().unwrap().column, 2);
+
+        // 先頭以外の BOM はエラー
+        let s = "[\u{FEFF}]";
+        assert!(Lexer::new(s).tokenize().is_err());
+    }
+
+    #[test]
+    fn iterator() {
+        let s = "[1, true, \"x\"]";
+        let mut lexer = Lexer::new(s);
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::LeftBracket);
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::Integer(1));
+        let rest = lexer.collect::<Result<Vec<_>, _>>().unwrap();
+        assert_eq!(rest.len(), 5);
+
+        let tokens = Lexer::new(s)
+            .map(Result::unwrap)
+            .take_while(|token| *token != Token::Comma)
+            .collect::<Vec<_>>();
+        assert_eq!(tokens, vec![Token::LeftBracket, Token::Integer(1)]);
+
+        // エラーの後は何も返さない
+        let mut lexer = Lexer::new("[x, 1]");
+        assert!(lexer.next().unwrap().is_ok());
+        assert!(lexer.next().unwrap().is_err());
+        assert!(lexer.next().is_none());
+    }
+
+    #[test]
+    fn borrowed_string() {
+        let s = r#"["plain", "esc\naped", "\u3042"]"#;
+        let tokens = Lexer::new(s).tokenize().unwrap();
+        assert!(matches!(&tokens[1], Token::String(Cow::Borrowed("plain"))));
+        assert!(matches!(&tokens[3], Token::String(Cow::Owned(s)) if s == "esc\naped"));
+        assert!(matches!(&tokens[5], Token::String(Cow::Owned(s)) if s == "あ"));
+    }
+
+    #[test]
+    fn from_bytes() {
+        let bytes = "{\"キー\": [1, true]}".as_bytes();
+        let tokens = Lexer::from_bytes(bytes).tokenize().unwrap();
+        assert_eq!(
+            tokens,
+            Lexer::new("{\"キー\": [1, true]}").tokenize().unwrap()
+        );
+
+        let bytes = b"[1,\n \"ab\xFFc\"]";
+        let mut lexer = Lexer::from_bytes(bytes);
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::LeftBracket);
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::Integer(1));
+        assert_eq!(lexer.next().unwrap().unwrap(), Token::Comma);
+        let e = lexer.next().unwrap().unwrap_err();
+        assert_eq!(
+            e.position().unwrap(),
+            Position {
+                offset: 8,
+                line: 2,
+                column: 5
+            }
+        );
+
+        let bytes = b"[1, 2]\xC3";
+        let e = Lexer::from_bytes(bytes).tokenize().unwrap_err();
+        assert_eq!(e.position().unwrap().offset, 6);
+    }
+
+    #[test]
+    fn comment() {
+        let s = r#"
+        // 行コメント
+        {
+            "key": /* ブロック
+                      コメント */ 1, // 末尾
+            "url": "http://example.com/*"
+        }
+        "#;
+        assert!(Lexer::new(s).tokenize().is_err());
+
+        let options = LexerOptions {
+            allow_comments: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(
+            tokens,
+            vec![
+                Token::LeftBrace,
+                Token::String("key".into()),
+                Token::Colon,
+                Token::Integer(1),
+                Token::Comma,
+                Token::String("url".into()),
+                Token::Colon,
+                Token::String("http://example.com/*".into()),
+                Token::RightBrace,
+            ]
+        );
+
+        let s = "[1 /* 閉じていない";
+        let e = Lexer::with_options(s, options.clone())
+            .tokenize()
+            .unwrap_err();
+        assert_eq!(e.position().unwrap().offset, 3);
+
+        let s = "[1 / 2]";
+        assert!(Lexer::with_options(s, options).tokenize().is_err());
+    }
+
+    #[test]
+    fn single_quoted_string() {
+        let s = r#"['single', 'it\'s "quoted"', "it\'s"]"#;
+        assert!(Lexer::new(s).tokenize().is_err());
+
+        let options = LexerOptions {
+            allow_single_quotes: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options).tokenize().unwrap();
+        assert_eq!(tokens[1], Token::String("single".into()));
+        assert_eq!(tokens[3], Token::String(r#"it's "quoted""#.into()));
+        assert_eq!(tokens[5], Token::String("it's".into()));
+    }
+
+    #[test]
+    fn unquoted_key() {
+        let s = "{foo: 1, bar_baz: [true, {$x1: null}], null: 2}";
+        assert!(Lexer::new(s).tokenize().is_err());
+
+        let options = LexerOptions {
+            allow_unquoted_keys: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(tokens[1], Token::String("foo".into()));
+        assert_eq!(tokens[5], Token::String("bar_baz".into()));
+        assert_eq!(tokens[8], Token::Bool(true));
+        assert_eq!(tokens[11], Token::String("$x1".into()));
+        assert_eq!(tokens[13], Token::Null);
+        assert_eq!(tokens[17], Token::String("null".into()));
+
+        // 値の位置では識別子にならない
+        let s = "{foo: bar}";
+        assert!(Lexer::with_options(s, options.clone()).tokenize().is_err());
+        let s = "[foo]";
+        assert!(Lexer::with_options(s, options).tokenize().is_err());
+    }
+
+    #[test]
+    fn extended_number_token() {
+        let s = "[0xFF, -0x10, Infinity, -Infinity, +Infinity, NaN]";
+        assert!(Lexer::new(s).tokenize().is_err());
+
+        let options = LexerOptions {
+            allow_extended_numbers: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(tokens[1], Token::Integer(255));
+        assert_eq!(tokens[3], Token::Integer(-16));
+        assert_eq!(
+            tokens[5],
+            Token::Number {
+                value: f64::INFINITY,
+                raw: "Infinity".into()
+            }
+        );
+        assert_eq!(
+            tokens[7],
+            Token::Number {
+                value: f64::NEG_INFINITY,
+                raw: "-Infinity".into()
+            }
+        );
+        assert!(matches!(tokens[9], Token::Number { value, .. } if value == f64::INFINITY));
+        assert!(matches!(tokens[11], Token::Number { value, .. } if value.is_nan()));
+
+        for s in ["0x", "0xFFFFFFFFFFFFFFFFF", "Inf", "NAN"] {
+            assert!(
+                Lexer::with_options(s, options.clone()).tokenize().is_err(),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn json5_escape() {
+        let s = "\"\\v\\0\\x41\\q line\\\ncontinued\\\r\nand\"";
+        assert!(Lexer::new(s).tokenize().is_err());
+
+        let options = LexerOptions {
+            json5_escapes: true,
+            ..Default::default()
+        };
+        let tokens = Lexer::with_options(s, options.clone()).tokenize().unwrap();
+        assert_eq!(
+            tokens[0],
+            Token::String("\u{b}\0Aq linecontinuedand".into())
+        );
+
+        for s in [r#""\01""#, r#""\1""#, r#""\x4""#] {
+            assert!(
+                Lexer::with_options(s, options.clone()).tokenize().is_err(),
+                "{}",
+                s
+            );
+        }
+    }
+
+    #[test]
+    fn limits() {
+        fn lex(json: &str, options: LexerOptions) -> Result<Vec<Token<'_>>, JsonError> {
+            Lexer::with_options(json, options).tokenize()
+        }
+        let limit = |e: JsonError| match e {
+            JsonError::LimitExceeded {
+                limit, position, ..
+            } => (limit, position.offset),
+            e => panic!("{:?}", e),
+        };
+
+        let options = LexerOptions {
+            max_string_bytes: Some(3),
+            ..Default::default()
+        };
+        assert!(lex(r#"["abc", "a\n"]"#, options.clone()).is_ok());
+        let e = lex(r#"["abc", "abcd"]"#, options.clone()).unwrap_err();
+        assert_eq!(limit(e), (Limit::StringBytes, 8));
+        let e = lex(r#""\u0041""#, options).unwrap_err();
+        assert_eq!(limit(e), (Limit::StringBytes, 0));
+
+        let options = LexerOptions {
+            max_tokens: Some(5),
+            allow_comments: true,
+            ..Default::default()
+        };
+        assert!(lex("[1, /* c */ 2]  ", options.clone()).is_ok());
+        let e = lex("[1, 2, 3]", options).unwrap_err();
+        assert_eq!(limit(e), (Limit::Tokens, 7));
+
+        let options = LexerOptions {
+            max_input_bytes: Some(4),
+            ..Default::default()
+        };
+        assert!(lex("[12]", options.clone()).is_ok());
+        let e = lex("[123]", options.clone()).unwrap_err();
+        assert_eq!(limit(e), (Limit::InputBytes, 0));
+        let e = Lexer::from_bytes_with_options(b"[1]\xff\xff", options)
+            .tokenize()
+            .unwrap_err();
+        assert_eq!(limit(e), (Limit::InputBytes, 0));
+    }
+
+    #[test]
+    fn literal_error() {
+        let error = |json: &str| {
+            let e = Lexer::new(json).tokenize().unwrap_err();
+            let JsonError::Lexer(e) = e else {
+                panic!("{:?}", e)
+            };
+            (e.msg, e.position.offset)
+        };
+        assert_eq!(
+            error("[tr, 1]"),
+            (
+                "error: \"true\" is expected but found \"tr\"".to_string(),
+                1
+            )
+        );
+        assert_eq!(
+            error("[1, nulll x]"),
+            (
+                "error: \"null\" is expected but found \"nulll\"".to_string(),
+                4
+            )
+        );
+        assert_eq!(
+            error("fals"),
+            (
+                "error: \"false\" is expected but found \"fals\"".to_string(),
+                0
+            )
+        );
+        assert_eq!(
+            error("nul\"x\""),
+            (
+                "error: \"null\" is expected but found \"nul\"".to_string(),
+                0
+            )
+        );
+        assert!(Lexer::new("[true,false,null]").tokenize().is_ok());
+    }
+
+    #[test]
+    fn lossless() {
+        let json = "\u{FEFF}{ \"a\\n\" :\t[1.50, 0x1F, 'b'] // end\r\n}\n";
+        let options = LexerOptions::from(crate::dialect::Dialect::Json5);
+        let lexemes = Lexer::with_options(json, options)
+            .tokenize_lossless()
+            .unwrap();
+        let restored = lexemes.iter().map(|l| l.text).collect::<String>();
+        assert_eq!(restored, json);
+
+        let texts = lexemes.iter().map(|l| l.text).collect::<Vec<_>>();
+        assert_eq!(
+            texts,
+            [
+                "\u{FEFF}", "{", " ", "\"a\\n\"", " ", ":", "\t", "[", "1.50", ",", " ", "0x1F",
+                ",", " ", "'b'", "]", " ", "// end", "\r\n", "}", "\n"
+            ]
+        );
+        assert_eq!(lexemes[3].token, Token::String("a\n".into()));
+        assert_eq!(lexemes[3].offset, 5);
+        assert_eq!(lexemes[17].token, Token::Comment("// end".into()));
+    }
 }
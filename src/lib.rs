@@ -1,3 +1,39 @@
+pub mod arc;
+pub mod builder;
+pub mod canonical;
+pub mod chunked;
+pub mod coerce;
+pub mod comments;
+pub mod convert;
+pub mod dedup;
+pub mod dialect;
+pub mod diff;
+pub mod error;
+pub mod flatten;
+pub mod formatter;
 pub mod lexer;
+pub mod merge;
+pub mod ndjson;
+pub mod number;
 pub mod parser;
-pub mod printer;
\ No newline at end of file
+pub mod patch;
+pub mod pointer;
+pub mod printer;
+pub mod query;
+pub mod redact;
+pub mod reshape;
+pub mod sample;
+mod scan;
+#[cfg(feature = "serde")]
+pub mod serde;
+pub mod sort;
+pub mod stats;
+pub mod strip;
+#[cfg(test)]
+mod test_util;
+pub mod transform;
+pub mod truncate;
+pub mod value;
+pub mod visit;
+pub mod walk;
+pub mod writer;
\ No newline at end of file
@@ -0,0 +1,80 @@
+use crate::value::Value;
+
+/// `Value::merge` で両方が配列だったときの扱い
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MergeStrategy {
+    /// 後の配列で置き換える
+    #[default]
+    Replace,
+    /// 後の配列の要素を末尾に加える
+    Concat,
+    /// 後の配列の要素のうち、まだないものだけを末尾に加える
+    Union,
+}
+
+impl Value {
+    /// `other` を再帰的に重ねる
+    ///
+    /// 両方がオブジェクトならキーごとに重ね、両方が配列なら `strategy` に従う。
+    /// それ以外は `other` で置き換える
+    pub fn merge(&mut self, other: Value, strategy: MergeStrategy) {
+        match (self, other) {
+            (Value::Object(object), Value::Object(other)) => {
+                for (key, value) in other {
+                    match object.get_mut(&key) {
+                        Some(current) => current.merge(value, strategy),
+                        None => {
+                            object.insert(key, value);
+                        }
+                    }
+                }
+            }
+            (Value::Array(array), Value::Array(other)) => match strategy {
+                MergeStrategy::Replace => *array = other,
+                MergeStrategy::Concat => array.extend(other),
+                MergeStrategy::Union => {
+                    for value in other {
+                        if !array.contains(&value) {
+                            array.push(value);
+                        }
+                    }
+                }
+            },
+            (current, other) => *current = other,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_merge() {
+        let defaults =
+            r#"{"server": {"host": "localhost", "port": 80}, "tags": ["a", "b"], "debug": false}"#;
+        let overrides = r#"{"server": {"port": 8080}, "tags": ["b", "c"], "debug": {"level": 1}}"#;
+
+        let mut value = parse(defaults);
+        value.merge(parse(overrides), MergeStrategy::Replace);
+        assert_eq!(
+            value,
+            parse(
+                r#"{"server": {"host": "localhost", "port": 8080}, "tags": ["b", "c"], "debug": {"level": 1}}"#
+            )
+        );
+
+        let mut value = parse(defaults);
+        value.merge(parse(overrides), MergeStrategy::Concat);
+        assert_eq!(value["tags"], parse(r#"["a", "b", "b", "c"]"#));
+
+        let mut value = parse(defaults);
+        value.merge(parse(overrides), MergeStrategy::Union);
+        assert_eq!(value["tags"], parse(r#"["a", "b", "c"]"#));
+
+        let mut value = parse(defaults);
+        value.merge(Value::Null, MergeStrategy::default());
+        assert_eq!(value, Value::Null);
+    }
+}
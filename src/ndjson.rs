@@ -0,0 +1,115 @@
+use std::io;
+
+use crate::{
+    printer::{write_value_with, PrinterOptions},
+    value::Value,
+};
+
+/// 1 行に 1 つの JSON を書く NDJSON (改行区切りの JSON) の書き出し
+///
+/// `W` の渡し方は [`write_value`](crate::printer::write_value) と同じ。
+/// 落ちる前に書いた行を確実に残すには `flush_each_line` を使う
+pub struct NdjsonWriter<W: io::Write> {
+    inner: W,
+    flush_each_line: bool,
+    lines: usize,
+}
+
+impl<W: io::Write> NdjsonWriter<W> {
+    pub fn new(inner: W) -> NdjsonWriter<W> {
+        NdjsonWriter {
+            inner,
+            flush_each_line: false,
+            lines: 0,
+        }
+    }
+
+    /// 1 行書くごとに `flush` する (ログの監視などで、書いた行をすぐ読ませたい場合)
+    pub fn flush_each_line(mut self, flush: bool) -> NdjsonWriter<W> {
+        self.flush_each_line = flush;
+        self
+    }
+
+    /// `value` を空白のない JSON で 1 行に書く
+    pub fn write(&mut self, value: &Value) -> io::Result<()> {
+        // 未解析の部分木は改行を含むことがあるので、解析し直して詰めて書く
+        let options = PrinterOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        write_value_with(&mut self.inner, value, &options)?;
+        self.inner.write_all(b"\n")?;
+        self.lines += 1;
+        if self.flush_each_line {
+            self.inner.flush()?;
+        }
+        Ok(())
+    }
+
+    /// 書いた行数
+    pub fn lines(&self) -> usize {
+        self.lines
+    }
+
+    pub fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    /// `flush` してから中の `W` を返す
+    pub fn into_inner(mut self) -> io::Result<W> {
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+    use crate::value::RawValue;
+
+    #[test]
+    fn test_ndjson_writer() {
+        let mut writer = NdjsonWriter::new(vec![]);
+        writer
+            .write(&parse(r#"{"a": [1, 2], "b": "x\ny"}"#))
+            .unwrap();
+        writer.write(&parse("null")).unwrap();
+        writer
+            .write(&Value::Raw(RawValue::new("[\n  1,\n  2\n]".to_string())))
+            .unwrap();
+        assert_eq!(writer.lines(), 3);
+        let out = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+        assert_eq!(out, "{\"a\":[1,2],\"b\":\"x\\ny\"}\nnull\n[1,2]\n");
+        let values = out.lines().map(parse).collect::<Vec<_>>();
+        assert_eq!(values[0], parse(r#"{"a": [1, 2], "b": "x\ny"}"#));
+    }
+
+    #[test]
+    fn test_flush_each_line() {
+        struct Flushes(usize);
+
+        impl io::Write for Flushes {
+            fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+                Ok(buf.len())
+            }
+
+            fn flush(&mut self) -> io::Result<()> {
+                self.0 += 1;
+                Ok(())
+            }
+        }
+
+        let mut writer = NdjsonWriter::new(Flushes(0)).flush_each_line(true);
+        writer.write(&Value::Null).unwrap();
+        writer.write(&Value::Null).unwrap();
+        assert_eq!(writer.get_ref().0, 2);
+        let mut writer = NdjsonWriter::new(Flushes(0));
+        writer.write(&Value::Null).unwrap();
+        assert_eq!(writer.get_ref().0, 0);
+    }
+}
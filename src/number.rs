@@ -0,0 +1,331 @@
+use std::{
+    fmt,
+    hash::{Hash, Hasher},
+    ops::Range,
+};
+
+/// JSON の数値
+///
+/// 整数は `i64` や `u64` のまま、それ以外は `f64` で保持するので、2^53 を超える整数や
+/// `u64` の ID も失わずに表せる
+#[derive(Debug, Clone)]
+pub struct Number {
+    n: N,
+    text: Option<Box<str>>, // 解析したときの元の表記
+}
+
+#[derive(Debug, Clone, Copy)]
+enum N {
+    PosInt(u64), // 0 以上の整数
+    NegInt(i64), // 負の整数
+    Float(f64),  // 整数以外 (NaN や無限大も含む)
+}
+
+impl Number {
+    /// `i64` で表せる整数か
+    pub fn is_i64(&self) -> bool {
+        match self.n {
+            N::PosInt(n) => n <= i64::MAX as u64,
+            N::NegInt(_) => true,
+            N::Float(_) => false,
+        }
+    }
+
+    /// `u64` で表せる整数か
+    pub fn is_u64(&self) -> bool {
+        matches!(self.n, N::PosInt(_))
+    }
+
+    /// 整数でない (`f64` で保持している) か
+    pub fn is_f64(&self) -> bool {
+        matches!(self.n, N::Float(_))
+    }
+
+    pub fn as_i64(&self) -> Option<i64> {
+        match self.n {
+            N::PosInt(n) => i64::try_from(n).ok(),
+            N::NegInt(n) => Some(n),
+            N::Float(_) => None,
+        }
+    }
+
+    pub fn as_u64(&self) -> Option<u64> {
+        match self.n {
+            N::PosInt(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// `f64` で返す。整数は最も近い `f64` に丸める
+    pub fn as_f64(&self) -> f64 {
+        match self.n {
+            N::PosInt(n) => n as f64,
+            N::NegInt(n) => n as f64,
+            N::Float(n) => n,
+        }
+    }
+
+    /// 解析したときの元の表記 (`1.50` や `1e3` など)。`ParserOptions::keep_number_text` で
+    /// 解析した小数や指数表記の数値と `-0` のうち、JSON の数値の文法に合う表記にだけある。
+    /// 比較やハッシュには使わない
+    pub fn text(&self) -> Option<&str> {
+        self.text.as_deref()
+    }
+
+    /// 元の表記 `text` を覚えた数値を返す
+    pub(crate) fn with_text(mut self, text: &str) -> Number {
+        self.text = Some(text.into());
+        self
+    }
+}
+
+impl From<u64> for Number {
+    fn from(n: u64) -> Number {
+        Number {
+            n: N::PosInt(n),
+            text: None,
+        }
+    }
+}
+
+impl From<i64> for Number {
+    fn from(n: i64) -> Number {
+        match u64::try_from(n) {
+            Ok(n) => Number::from(n),
+            Err(_) => Number {
+                n: N::NegInt(n),
+                text: None,
+            },
+        }
+    }
+}
+
+impl From<i32> for Number {
+    fn from(n: i32) -> Number {
+        Number::from(i64::from(n))
+    }
+}
+
+impl From<u32> for Number {
+    fn from(n: u32) -> Number {
+        Number::from(u64::from(n))
+    }
+}
+
+impl From<f64> for Number {
+    fn from(n: f64) -> Number {
+        Number {
+            n: N::Float(n),
+            text: None,
+        }
+    }
+}
+
+/// 数値として等しいかを比べる。整数どうしは厳密に、小数は値が一致する整数と等しい
+///
+/// `Eq` を満たすよう NaN どうしは等しいとする。-0.0 は 0.0 や 0 と等しい
+impl PartialEq for Number {
+    fn eq(&self, other: &Number) -> bool {
+        match (self.n, other.n) {
+            (N::PosInt(a), N::PosInt(b)) => a == b,
+            (N::NegInt(a), N::NegInt(b)) => a == b,
+            (N::Float(a), N::Float(b)) => a == b || (a.is_nan() && b.is_nan()),
+            (N::Float(f), _) => float_eq_int(f, other),
+            (_, N::Float(f)) => float_eq_int(f, self),
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Number {}
+
+/// 等しい数値は同じハッシュ値になる。整数と一致する小数は、その整数として扱う
+impl Hash for Number {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        match self.n {
+            N::PosInt(n) => i128::from(n).hash(state),
+            N::NegInt(n) => i128::from(n).hash(state),
+            N::Float(f) => match float_as_int(f) {
+                Some(n) => n.hash(state),
+                None if f.is_nan() => f64::NAN.to_bits().hash(state),
+                None => f.to_bits().hash(state),
+            },
+        }
+    }
+}
+
+/// 小数 `f` が `i64` か `u64` の整数と値として一致するなら、その整数を返す
+fn float_as_int(f: f64) -> Option<i128> {
+    // 2^64 以上や -2^63 未満の小数は、どの整数とも一致しない
+    if f.fract() != 0.0 || !(-9.223_372_036_854_776e18..1.844_674_407_370_955_2e19).contains(&f) {
+        return None;
+    }
+    Some(f as i128)
+}
+
+/// 小数 `f` が整数 `int` と値として一致するか (`f64` に丸めた整数と比べると誤るので避ける)
+fn float_eq_int(f: f64, int: &Number) -> bool {
+    match int.n {
+        N::PosInt(n) => float_as_int(f) == Some(i128::from(n)),
+        N::NegInt(n) => float_as_int(f) == Some(i128::from(n)),
+        N::Float(_) => false,
+    }
+}
+
+impl fmt::Display for Number {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.n {
+            N::PosInt(n) => write!(f, "{}", n),
+            N::NegInt(n) => write!(f, "{}", n),
+            N::Float(n) if n.is_finite() => f.write_str(&format_f64(n)),
+            N::Float(n) => write!(f, "{}", n),
+        }
+    }
+}
+
+/// 有限の `f64` を、同じ値に読み戻せる最短の桁で表す
+///
+/// ECMAScript の `Number.prototype.toString` と同じく、10 進の指数が -6 以上 21 未満なら
+/// 小数点表記 (`1.5`、`100`、`0.000001`)、それ以外は指数表記 (`1e+21`、`1.5e-7`) にする。
+/// 整数の値に `.0` は付けない
+pub(crate) fn format_f64(n: f64) -> String {
+    format_f64_with(n, None, &(-6..21))
+}
+
+/// 有限の `f64` を、10 進の指数が `decimal_exponents` の範囲なら小数点表記、それ以外は指数表記で表す
+///
+/// `precision` があれば小数点 (指数表記では仮数の小数点) 以下をその桁数に丸める。
+/// なければ同じ値に読み戻せる最短の桁にする
+pub(crate) fn format_f64_with(
+    n: f64,
+    precision: Option<usize>,
+    decimal_exponents: &Range<i32>,
+) -> String {
+    let sign = if n.is_sign_negative() { "-" } else { "" };
+    let n = n.abs();
+    // `{:e}` は最短の桁を `d.ddde-7` の形で返す
+    let exp_form = match precision {
+        Some(p) => format!("{:.*e}", p, n),
+        None => format!("{:e}", n),
+    };
+    let (mantissa, exponent) = exp_form.split_once('e').unwrap_or((&exp_form, "0"));
+    let e = exponent.parse::<i32>().unwrap_or(0);
+    if !decimal_exponents.contains(&e) {
+        let e_sign = if e < 0 { "-" } else { "+" };
+        return format!("{}{}e{}{}", sign, mantissa, e_sign, e.abs());
+    }
+    if let Some(p) = precision {
+        return format!("{}{:.*}", sign, p, n);
+    }
+    let digits: String = mantissa.chars().filter(|c| *c != '.').collect();
+    let k = digits.len() as i32;
+    let point = e + 1; // 小数点の位置
+    let body = if k <= point {
+        format!("{}{}", digits, "0".repeat((point - k) as usize))
+    } else if 0 < point {
+        let (int, frac) = digits.split_at(point as usize);
+        format!("{}.{}", int, frac)
+    } else {
+        format!("0.{}{}", "0".repeat(-point as usize), digits)
+    };
+    format!("{}{}", sign, body)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_number() {
+        let big = Number::from(u64::MAX);
+        assert!(big.is_u64() && !big.is_i64());
+        assert_eq!(big.as_u64(), Some(u64::MAX));
+        assert_eq!(big.as_i64(), None);
+        assert_eq!(big.to_string(), "18446744073709551615");
+
+        let negative = Number::from(-3);
+        assert!(negative.is_i64() && !negative.is_u64());
+        assert_eq!(negative.as_i64(), Some(-3));
+        assert_eq!(negative.as_f64(), -3.0);
+
+        let float = Number::from(1.5);
+        assert!(float.is_f64());
+        assert_eq!(float.as_i64(), None);
+        assert_eq!(float.to_string(), "1.5");
+        assert_eq!(Number::from(3i64), Number::from(3u64));
+    }
+
+    #[test]
+    fn test_eq() {
+        assert_eq!(Number::from(1), Number::from(1.0));
+        assert_eq!(Number::from(-1.0), Number::from(-1));
+        assert_ne!(Number::from(1), Number::from(1.5));
+        assert_ne!(Number::from(1), Number::from(-1.0));
+        // 2^53 + 1 は f64 に丸めると 2^53 になるが、値としては等しくない
+        assert_ne!(
+            Number::from(9_007_199_254_740_993u64),
+            Number::from(9_007_199_254_740_992.0)
+        );
+        assert_eq!(
+            Number::from(9_007_199_254_740_992u64),
+            Number::from(9_007_199_254_740_992.0)
+        );
+        assert_ne!(Number::from(u64::MAX), Number::from(1.8446744073709552e19));
+        assert_eq!(Number::from(f64::NAN), Number::from(f64::NAN));
+        assert_eq!(Number::from(-0.0), Number::from(0));
+        assert_eq!(Number::from(-0.0), Number::from(0.0));
+    }
+
+    #[test]
+    fn test_format_f64() {
+        let cases = [
+            (1.0, "1"),
+            (-2.5, "-2.5"),
+            (100.0, "100"),
+            (0.1, "0.1"),
+            (0.000001, "0.000001"),
+            (0.0000001, "1e-7"),
+            (1.5e-7, "1.5e-7"),
+            (1e20, "100000000000000000000"),
+            (1e21, "1e+21"),
+            (1.2345e300, "1.2345e+300"),
+            (123456789.125, "123456789.125"),
+            (0.30000000000000004, "0.30000000000000004"),
+            (f64::MAX, "1.7976931348623157e+308"),
+            (f64::MIN_POSITIVE, "2.2250738585072014e-308"),
+            (5e-324, "5e-324"),
+            (0.0, "0"),
+            (-0.0, "-0"),
+        ];
+        for (n, expected) in cases {
+            assert_eq!(format_f64(n), expected);
+            assert_eq!(format_f64(n).parse::<f64>().unwrap(), n);
+        }
+        assert_eq!(Number::from(1e21).to_string(), "1e+21");
+
+        assert_eq!(format_f64_with(1.23456, Some(2), &(-6..21)), "1.23");
+        assert_eq!(format_f64_with(-2.0, Some(3), &(-6..21)), "-2.000");
+        assert_eq!(format_f64_with(1234.5, None, &(-3..3)), "1.2345e+3");
+        assert_eq!(format_f64_with(0.00012, Some(1), &(-3..3)), "1.2e-4");
+        assert_eq!(format_f64_with(0.5, None, &(0..0)), "5e-1");
+    }
+
+    #[test]
+    fn test_hash() {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(n: Number) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            n.hash(&mut hasher);
+            hasher.finish()
+        }
+        assert_eq!(hash(Number::from(1)), hash(Number::from(1.0)));
+        assert_eq!(hash(Number::from(-3)), hash(Number::from(-3.0)));
+        assert_eq!(hash(Number::from(-0.0)), hash(Number::from(0)));
+        assert_eq!(hash(Number::from(f64::NAN)), hash(Number::from(-f64::NAN)));
+        assert_eq!(
+            hash(Number::from(9_007_199_254_740_992u64)),
+            hash(Number::from(9_007_199_254_740_992.0))
+        );
+    }
+}
@@ -1,16 +1,12 @@
-use std::collections::BTreeMap;
-
-use crate::lexer::Token;
-
-#[derive(Debug, Clone, PartialEq)]
-pub enum Value {
-    String(String),                  // 文字列
-    Number(f64),                     // 数値
-    Bool(bool),                      // 真偽値
-    Null,                            // Null
-    Array(Vec<Value>),               // JSON Array
-    Object(BTreeMap<String, Value>), // JSON Object
-}
+use std::{borrow::Cow, collections::BTreeMap};
+
+pub use crate::value::{RawValue, Value};
+use crate::{
+    arc::{ArcValue, KeyInterner},
+    lexer::{Lexer, Token},
+    number::Number,
+    pointer::ToPointer,
+};
 
 #[derive(Debug, Clone)]
 pub struct ParserError {
@@ -25,114 +21,227 @@ impl ParserError {
     }
 }
 
-pub struct Parser {
-    tokens: Vec<Token>,
+/// 構文解析の設定
+#[derive(Debug, Clone, Default)]
+pub struct ParserOptions {
+    /// `[1, 2,]` や `{"a": 1,}` のような末尾のカンマを許す
+    pub allow_trailing_commas: bool,
+    /// 小数や指数表記の数値と `-0` の元の表記 (`1.50`、`1e3`) を `Number::text` に残し、書き出すときに使う。
+    /// JSON の数値の文法に合わない表記 (JSON5 の `.5` や `+1.5` など) は残さない
+    pub keep_number_text: bool,
+}
+
+pub struct Parser<'a> {
+    tokens: Vec<Token<'a>>,
     index: usize,
+    options: ParserOptions,
 }
 
-impl Parser {
-    pub fn new(tokens: Vec<Token>) -> Parser {
-        Parser { tokens, index: 0 }
+impl<'a> Parser<'a> {
+    pub fn new(tokens: Vec<Token<'a>>) -> Parser<'a> {
+        Self::with_options(tokens, ParserOptions::default())
+    }
+
+    pub fn with_options(tokens: Vec<Token<'a>>, options: ParserOptions) -> Parser<'a> {
+        Parser {
+            tokens,
+            index: 0,
+            options,
+        }
     }
 
-    fn peek(&self) -> Option<&Token> {
+    fn peek(&self) -> Option<&Token<'a>> {
         self.tokens.get(self.index)
     }
 
-    fn peek_expect(&self) -> Result<&Token, ParserError> {
+    fn peek_expect(&self) -> Result<&Token<'a>, ParserError> {
         self.peek()
             .ok_or_else(|| ParserError::new("error: a token isn't peekable"))
     }
 
-    fn next(&mut self) -> Option<&Token> {
+    fn next(&mut self) -> Option<&Token<'a>> {
         self.index += 1;
         self.tokens.get(self.index - 1)
     }
 
-    fn next_expect(&mut self) -> Result<&Token, ParserError> {
+    fn next_expect(&mut self) -> Result<&Token<'a>, ParserError> {
         self.next()
             .ok_or_else(|| ParserError::new("error: a token isn't peekable"))
     }
 
-    fn parse_array(&mut self) -> Result<Value, ParserError> {
+    /// 末尾のカンマが許されていて次が `close` なら、それを読み進めて true を返す
+    fn trailing_comma(&mut self, close: Token) -> Result<bool, ParserError> {
+        if self.options.allow_trailing_commas && *self.peek_expect()? == close {
+            self.next_expect()?;
+            return Ok(true);
+        }
+        Ok(false)
+    }
+
+    /// `open` (`[` か `{`) で始まるコンテナを閉じ括弧まで読み、要素ごとに `element` を呼ぶ
+    ///
+    /// オブジェクトでは `element` がキーと `:` (`parse_key`) から読む
+    fn parse_elements(
+        &mut self,
+        open: Token,
+        mut element: impl FnMut(&mut Self) -> Result<(), ParserError>,
+    ) -> Result<(), ParserError> {
+        let (close, kind, bracket) = match open {
+            Token::LeftBracket => (Token::RightBracket, "array", "["),
+            _ => (Token::RightBrace, "object", "{"),
+        };
         let token = self.peek_expect()?;
-        if *token != Token::LeftBracket {
+        if *token != open {
             return Err(ParserError::new(&format!(
-                "error: JSON array must starts [ {:?}",
-                token
+                "error: JSON {} must starts {} {:?}",
+                kind, bracket, token
             )));
         }
 
         self.next_expect()?;
 
-        let mut array = vec![];
-        let token = self.peek_expect()?;
-        if *token == Token::RightBracket {
+        if *self.peek_expect()? == close {
             self.next_expect()?;
-            return Ok(Value::Array(array));
+            return Ok(());
         }
 
         loop {
-            let value = self.parse()?;
-            array.push(value);
+            element(self)?;
 
             let token = self.next_expect()?;
             match token {
-                Token::RightBracket => return Ok(Value::Array(array)),
-                Token::Comma => continue,
+                token if *token == close => return Ok(()),
+                Token::Comma => {
+                    if self.trailing_comma(close.clone())? {
+                        return Ok(());
+                    }
+                }
                 _ => {
                     return Err(ParserError::new(&format!(
-                        "error: a [ or , token is expected {:?}",
-                        token
+                        "error: a {} or , token is expected {:?}",
+                        bracket, token
                     )));
                 }
             }
         }
     }
 
-    fn parse_object(&mut self) -> Result<Value, ParserError> {
-        let token = self.peek_expect()?;
-        if *token != Token::LeftBrace {
-            return Err(ParserError::new(&format!(
-                "error: JSON object must starts {{ {:?}",
-                token
-            )));
+    /// オブジェクトのキーと続く `:` を読む
+    fn parse_key(&mut self) -> Result<Cow<'a, str>, ParserError> {
+        let token1 = self.next_expect()?.clone();
+        let token2 = self.next_expect()?;
+
+        match (token1, token2) {
+            (Token::String(key), Token::Colon) => Ok(key),
+            _ => Err(ParserError::new(
+                "error: a pair (key(string) and : token) token is expected",
+            )),
         }
+    }
 
-        self.next_expect()?;
+    fn parse_array(&mut self) -> Result<Value, ParserError> {
+        let mut array = vec![];
+        self.parse_elements(Token::LeftBracket, |parser| {
+            array.push(parser.parse()?);
+            Ok(())
+        })?;
+        Ok(Value::Array(array))
+    }
 
+    fn parse_object(&mut self) -> Result<Value, ParserError> {
         let mut object = BTreeMap::new();
+        self.parse_elements(Token::LeftBrace, |parser| {
+            let key = parser.parse_key()?.into_owned();
+            object.insert(key, parser.parse()?);
+            Ok(())
+        })?;
+        Ok(Value::Object(object))
+    }
 
-        if *self.peek_expect()? == Token::RightBrace {
-            self.next_expect()?;
-            return Ok(Value::Object(object));
-        }
+    /// JSON Pointer で指定された部分木だけを解析する
+    pub fn parse_at<P: ToPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+    ) -> Result<Option<Value>, ParserError> {
+        Ok(self.parse_at_all(&[pointer])?.pop().flatten())
+    }
 
-        loop {
-            let token1 = self.next_expect()?.clone();
-            let token2 = self.next_expect()?;
+    /// 複数の JSON Pointer で指定された部分木だけを解析し、それ以外は読み飛ばす
+    pub fn parse_at_all<P: ToPointer>(
+        &mut self,
+        pointers: &[P],
+    ) -> Result<Vec<Option<Value>>, ParserError> {
+        let targets = pointers
+            .iter()
+            .map(|pointer| Ok(pointer.to_pointer()?.tokens().to_vec()))
+            .collect::<Result<Vec<_>, ParserError>>()?;
+        let mut found = vec![None; targets.len()];
+        self.select(&mut vec![], &targets, &mut found)?;
+        Ok(found)
+    }
 
-            match (token1, token2) {
-                (Token::String(key), Token::Colon) => {
-                    object.insert(key, self.parse()?);
-                }
-                _ => {
-                    return Err(ParserError::new(
-                        "error: a pair (key(string) and : token) token is expected",
-                    ));
-                }
+    fn select(
+        &mut self,
+        path: &mut Vec<String>,
+        targets: &[Vec<String>],
+        found: &mut [Option<Value>],
+    ) -> Result<(), ParserError> {
+        if targets.iter().any(|target| target == path) {
+            let value = self.parse()?;
+            targets
+                .iter()
+                .zip(found.iter_mut())
+                .filter(|(target, _)| *target == path)
+                .for_each(|(_, slot)| *slot = Some(value.clone()));
+            return Ok(());
+        }
+        if !targets.iter().any(|target| target.starts_with(path)) {
+            return self.skip_value();
+        }
+
+        match self.peek_expect()? {
+            Token::LeftBrace => self.parse_elements(Token::LeftBrace, |parser| {
+                path.push(parser.parse_key()?.into_owned());
+                parser.select(path, targets, found)?;
+                path.pop();
+                Ok(())
+            }),
+            Token::LeftBracket => {
+                let mut index = 0;
+                self.parse_elements(Token::LeftBracket, |parser| {
+                    path.push(index.to_string());
+                    parser.select(path, targets, found)?;
+                    path.pop();
+                    index += 1;
+                    Ok(())
+                })
             }
+            _ => self.skip_value(),
+        }
+    }
 
-            let token3 = self.next_expect()?;
-            match token3 {
-                Token::RightBrace => return Ok(Value::Object(object)),
-                Token::Comma => continue,
-                _ => {
+    /// `Value` を作らずに、括弧の対応だけを見て値を 1 つ読み飛ばす
+    fn skip_value(&mut self) -> Result<(), ParserError> {
+        let mut closers = vec![];
+        loop {
+            let token = self.next_expect()?;
+            match token {
+                Token::LeftBrace => closers.push(Token::RightBrace),
+                Token::LeftBracket => closers.push(Token::RightBracket),
+                Token::RightBrace | Token::RightBracket if closers.last() == Some(token) => {
+                    closers.pop();
+                }
+                Token::Comma | Token::Colon if !closers.is_empty() => (),
+                Token::RightBrace | Token::RightBracket | Token::Comma | Token::Colon => {
                     return Err(ParserError::new(&format!(
-                        "error: a {{ or , token is expected {:?}",
-                        token3
+                        "error: an unexpected token {:?}",
+                        token
                     )));
                 }
+                _ => (),
+            }
+            if closers.is_empty() {
+                return Ok(());
             }
         }
     }
@@ -144,11 +253,26 @@ impl Parser {
             Token::LeftBracket => self.parse_array(),
             Token::String(s) => {
                 self.next_expect()?;
-                Ok(Value::String(s))
+                Ok(Value::String(s.into_owned()))
+            }
+            Token::Number { value, raw } => {
+                self.next_expect()?;
+                // i64 に収まらない正の整数や `-0` のような表記の整数は、整数として読む
+                let number = match raw.parse::<u64>() {
+                    Ok(n) => Number::from(n),
+                    Err(_) => raw.parse::<i64>().map_or(Number::from(value), Number::from),
+                };
+                let keep_text = (number.is_f64() || number.to_string() != raw)
+                    && Lexer::is_json_number(&raw, false);
+                if self.options.keep_number_text && keep_text {
+                    Ok(Value::Number(number.with_text(&raw)))
+                } else {
+                    Ok(Value::Number(number))
+                }
             }
-            Token::Number(n) => {
+            Token::Integer(n) => {
                 self.next_expect()?;
-                Ok(Value::Number(n))
+                Ok(Value::from(n))
             }
             Token::Bool(b) => {
                 self.next_expect()?;
@@ -158,18 +282,77 @@ impl Parser {
                 self.next_expect()?;
                 Ok(Value::Null)
             }
+            Token::Raw(json) => {
+                self.next_expect()?;
+                Ok(Value::Raw(RawValue::new(json.into_owned())))
+            }
             _ => Err(ParserError::new(&format!(
                 "error: a token must start {{ or [ or string or number or bool or null {:?}",
                 token
             ))),
         }
     }
+
+    /// `ArcValue` として解析する。オブジェクトのキーは `interner` で共有する
+    pub fn parse_interned(&mut self, interner: &mut KeyInterner) -> Result<ArcValue, ParserError> {
+        match self.peek_expect()? {
+            Token::LeftBracket => {
+                let mut array = vec![];
+                self.parse_elements(Token::LeftBracket, |parser| {
+                    array.push(parser.parse_interned(interner)?);
+                    Ok(())
+                })?;
+                Ok(ArcValue::Array(array.into()))
+            }
+            Token::LeftBrace => {
+                let mut object = BTreeMap::new();
+                self.parse_elements(Token::LeftBrace, |parser| {
+                    let key = interner.intern(&parser.parse_key()?);
+                    object.insert(key, parser.parse_interned(interner)?);
+                    Ok(())
+                })?;
+                Ok(ArcValue::Object(object.into()))
+            }
+            _ => Ok(ArcValue::from(self.parse()?)),
+        }
+    }
+
+    /// 値を 1 つ解析し、その後に token が残っていればエラーにする
+    pub fn parse_document(&mut self) -> Result<Value, ParserError> {
+        let value = self.parse()?;
+        self.expect_end()?;
+        Ok(value)
+    }
+
+    /// `parse_interned` で値を 1 つ解析し、その後に token が残っていればエラーにする
+    pub fn parse_interned_document(
+        &mut self,
+        interner: &mut KeyInterner,
+    ) -> Result<ArcValue, ParserError> {
+        let value = self.parse_interned(interner)?;
+        self.expect_end()?;
+        Ok(value)
+    }
+
+    /// token が残っていればエラーにする
+    fn expect_end(&self) -> Result<(), ParserError> {
+        match self.tokens.get(self.index) {
+            Some(token) => Err(ParserError::new(&format!(
+                "error: an unexpected token after the value {:?}",
+                token
+            ))),
+            None => Ok(()),
+        }
+    }
 }
 
 #[cfg(test)]
 mod test {
-    use super::Parser;
-    use crate::{lexer::Lexer, parser::Value};
+    use super::{Parser, ParserOptions, RawValue};
+    use crate::{
+        lexer::{Lexer, LexerOptions},
+        parser::Value,
+    };
     use std::collections::BTreeMap;
 
     #[test]
@@ -211,7 +394,7 @@ mod test {
             .unwrap();
         let array = Value::Array(vec![
             Value::Null,
-            Value::Number(1.0),
+            Value::from(1),
             Value::Bool(true),
             Value::String("monkey-json".to_string()),
         ]);
@@ -223,7 +406,7 @@ mod test {
             .unwrap();
         let array = Value::Array(vec![Value::Array(vec![
             Value::String("togatoga".to_string()),
-            Value::Number(123.0),
+            Value::from(123),
         ])]);
         assert_eq!(value, array);
     }
@@ -237,10 +420,20 @@ mod test {
         let mut object = BTreeMap::new();
         object.insert(
             "key".to_string(),
-            Value::Array(vec![Value::Number(1.0), Value::String("value".to_string())]),
+            Value::Array(vec![Value::from(1), Value::String("value".to_string())]),
         );
         assert_eq!(value, Value::Object(object));
 
+        // 2^53 を超える整数も失わない
+        let json = "[18446744073709551615, 9007199254740993, -9223372036854775808, 1.5]";
+        let value = Parser::new(Lexer::new(json).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        assert_eq!(value[0].as_u64(), Some(u64::MAX));
+        assert_eq!(value[1].as_i64(), Some(9007199254740993));
+        assert_eq!(value[2].as_i64(), Some(i64::MIN));
+        assert!(value[3].is_f64());
+
         let json = r#"[{"key": "value"}]"#;
         let value = Parser::new(Lexer::new(json).tokenize().unwrap())
             .parse()
@@ -251,4 +444,115 @@ mod test {
         let array = Value::Array(vec![Value::Object(object)]);
         assert_eq!(value, array);
     }
+
+    #[test]
+    fn test_parse_raw() {
+        let json = r#"{"type": "event", "payload": {"items": [1, 2, 3]}}"#;
+        let options = LexerOptions {
+            raw_keys: vec!["payload".to_string()],
+            ..Default::default()
+        };
+        let value = Parser::new(Lexer::with_options(json, options).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        let raw = RawValue::new(r#"{"items": [1, 2, 3]}"#.to_string());
+        let mut object = BTreeMap::new();
+        object.insert("type".to_string(), Value::String("event".to_string()));
+        object.insert("payload".to_string(), Value::Raw(raw.clone()));
+        assert_eq!(value, Value::Object(object));
+
+        let mut payload = BTreeMap::new();
+        payload.insert(
+            "items".to_string(),
+            Value::Array(vec![Value::from(1), Value::from(2), Value::from(3)]),
+        );
+        assert_eq!(raw.parse().unwrap(), Value::Object(payload));
+    }
+
+    #[test]
+    fn test_parse_at() {
+        let json = r#"
+        {
+            "meta": {"skip": [1, {"deep": true}]},
+            "data": {
+                "items": [{"id": 1}, {"id": 2}],
+                "a/b": "slash",
+                "m~n": "tilde"
+            }
+        }
+        "#;
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        let value = parser.parse_at("/data/items/1/id").unwrap();
+        assert_eq!(value, Some(Value::from(2)));
+
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        let values = parser
+            .parse_at_all(&["/data/a~1b", "/data/m~0n", "/missing", "/meta/skip/0"])
+            .unwrap();
+        assert_eq!(
+            values,
+            vec![
+                Some(Value::String("slash".to_string())),
+                Some(Value::String("tilde".to_string())),
+                None,
+                Some(Value::from(1)),
+            ]
+        );
+
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        let value = parser.parse_at("").unwrap().unwrap();
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        assert_eq!(value, parser.parse().unwrap());
+
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        assert!(parser.parse_at("data").is_err());
+
+        let json = r#"{"skip": [1, }, "data": 1}"#;
+        let mut parser = Parser::new(Lexer::new(json).tokenize().unwrap());
+        assert!(parser.parse_at("/data").is_err());
+    }
+
+    #[test]
+    fn test_trailing_comma() {
+        let options = ParserOptions {
+            allow_trailing_commas: true,
+            ..Default::default()
+        };
+        for json in [r#"{"a": 1,}"#, "[1, 2,]", r#"[{"a": [1,],},]"#] {
+            let tokens = Lexer::new(json).tokenize().unwrap();
+            assert!(Parser::new(tokens.clone()).parse().is_err(), "{}", json);
+            assert!(
+                Parser::with_options(tokens, options.clone())
+                    .parse()
+                    .is_ok(),
+                "{}",
+                json
+            );
+        }
+
+        let json = r#"{"a": [1, 2,], "b": {"c": true,},}"#;
+        let value = Parser::with_options(Lexer::new(json).tokenize().unwrap(), options.clone())
+            .parse()
+            .unwrap();
+        let expected = Parser::new(
+            Lexer::new(r#"{"a": [1, 2], "b": {"c": true}}"#)
+                .tokenize()
+                .unwrap(),
+        )
+        .parse()
+        .unwrap();
+        assert_eq!(value, expected);
+
+        let mut parser =
+            Parser::with_options(Lexer::new(json).tokenize().unwrap(), options.clone());
+        assert_eq!(parser.parse_at("/a/1").unwrap(), Some(Value::from(2)));
+
+        // カンマだけの配列は許さない
+        for json in ["[,]", "{,}", "[1,,]"] {
+            let tokens = Lexer::new(json).tokenize().unwrap();
+            assert!(Parser::with_options(tokens, options.clone())
+                .parse()
+                .is_err());
+        }
+    }
 }
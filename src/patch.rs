@@ -0,0 +1,609 @@
+use std::fmt;
+
+use crate::{
+    pointer::{array_index, JsonPointer},
+    value::Value,
+};
+
+/// JSON Patch (RFC 6902) の操作
+#[derive(Debug, Clone, PartialEq)]
+pub enum Operation {
+    Add {
+        path: JsonPointer,
+        value: Value,
+    },
+    Remove {
+        path: JsonPointer,
+    },
+    Replace {
+        path: JsonPointer,
+        value: Value,
+    },
+    Move {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Copy {
+        from: JsonPointer,
+        path: JsonPointer,
+    },
+    Test {
+        path: JsonPointer,
+        value: Value,
+    },
+}
+
+/// JSON Patch (RFC 6902) の操作の列
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Patch {
+    pub operations: Vec<Operation>,
+}
+
+/// パッチの読み込みや適用のエラー
+#[derive(Debug, Clone)]
+pub struct PatchError {
+    pub msg: String,
+    pub index: usize, // 失敗した操作の位置
+}
+
+impl PatchError {
+    pub(crate) fn new(index: usize, msg: &str) -> PatchError {
+        PatchError {
+            msg: msg.to_string(),
+            index,
+        }
+    }
+}
+
+impl fmt::Display for PatchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (operation {})", self.msg, self.index)
+    }
+}
+
+impl std::error::Error for PatchError {}
+
+impl Patch {
+    pub fn new(operations: Vec<Operation>) -> Patch {
+        Patch { operations }
+    }
+
+    /// `[{"op": "add", "path": "/a", "value": 1}, ...]` の形の JSON からパッチを読む
+    pub fn from_value(value: &Value) -> Result<Patch, PatchError> {
+        let array = value
+            .as_array()
+            .ok_or_else(|| PatchError::new(0, "error: a patch must be an array"))?;
+        let operations = array
+            .iter()
+            .enumerate()
+            .map(|(index, operation)| Self::operation(index, operation))
+            .collect::<Result<_, _>>()?;
+        Ok(Patch { operations })
+    }
+
+    fn operation(index: usize, operation: &Value) -> Result<Operation, PatchError> {
+        let member = |name: &str| {
+            operation
+                .get(name)
+                .ok_or_else(|| PatchError::new(index, &format!("error: \"{}\" is missing", name)))
+        };
+        let string = |name: &str| {
+            member(name)?.as_str().map(str::to_string).ok_or_else(|| {
+                PatchError::new(index, &format!("error: \"{}\" must be a string", name))
+            })
+        };
+        let pointer = |name: &str| {
+            JsonPointer::parse(&string(name)?).map_err(|e| PatchError::new(index, &e.msg))
+        };
+        let path = pointer("path")?;
+        match string("op")?.as_str() {
+            "add" => Ok(Operation::Add {
+                path,
+                value: member("value")?.clone(),
+            }),
+            "remove" => Ok(Operation::Remove { path }),
+            "replace" => Ok(Operation::Replace {
+                path,
+                value: member("value")?.clone(),
+            }),
+            "move" => Ok(Operation::Move {
+                from: pointer("from")?,
+                path,
+            }),
+            "copy" => Ok(Operation::Copy {
+                from: pointer("from")?,
+                path,
+            }),
+            "test" => Ok(Operation::Test {
+                path,
+                value: member("value")?.clone(),
+            }),
+            op => Err(PatchError::new(
+                index,
+                &format!("error: an unknown operation \"{}\"", op),
+            )),
+        }
+    }
+
+    /// `from` を `to` に変えるパッチを作る
+    ///
+    /// オブジェクトはキーごとに、配列は最長共通部分列で残す要素を決めて差分をとる。
+    /// 同じ位置で取り除いて加える要素は `replace` (入れ子の場合はその中の差分) にまとめる
+    pub fn diff(from: &Value, to: &Value) -> Patch {
+        let mut operations = vec![];
+        diff_value(from, to, &JsonPointer::root(), &mut operations);
+        Patch { operations }
+    }
+
+    /// `[{"op": "add", "path": "/a", "value": 1}, ...]` の形の JSON にする
+    pub fn to_value(&self) -> Value {
+        self.operations
+            .iter()
+            .map(|operation| {
+                let (op, path, from, value) = match operation {
+                    Operation::Add { path, value } => ("add", path, None, Some(value)),
+                    Operation::Remove { path } => ("remove", path, None, None),
+                    Operation::Replace { path, value } => ("replace", path, None, Some(value)),
+                    Operation::Move { from, path } => ("move", path, Some(from), None),
+                    Operation::Copy { from, path } => ("copy", path, Some(from), None),
+                    Operation::Test { path, value } => ("test", path, None, Some(value)),
+                };
+                let mut object = Value::object()
+                    .field("op", op)
+                    .field("path", path.to_string());
+                if let Some(from) = from {
+                    object = object.field("from", from.to_string());
+                }
+                if let Some(value) = value {
+                    object = object.field("value", value.clone());
+                }
+                object.build()
+            })
+            .collect()
+    }
+
+    /// パッチを `value` に適用する。途中で失敗した場合 `value` は変更しない
+    pub fn apply(&self, value: &mut Value) -> Result<(), PatchError> {
+        let mut patched = value.clone();
+        for (index, operation) in self.operations.iter().enumerate() {
+            Self::apply_operation(&mut patched, operation)
+                .map_err(|msg| PatchError::new(index, &msg))?;
+        }
+        *value = patched;
+        Ok(())
+    }
+
+    fn apply_operation(value: &mut Value, operation: &Operation) -> Result<(), String> {
+        match operation {
+            Operation::Add { path, value: new } => add(value, path, new.clone()),
+            Operation::Remove { path } => remove(value, path).map(|_| ()),
+            Operation::Replace { path, value: new } => {
+                *target_mut(value, path)? = new.clone();
+                Ok(())
+            }
+            Operation::Move { from, path } => {
+                if path.starts_with(from) && path != from {
+                    return Err(format!(
+                        "error: cannot move \"{}\" into its own child \"{}\"",
+                        from, path
+                    ));
+                }
+                let moved = remove(value, from)?;
+                add(value, path, moved)
+            }
+            Operation::Copy { from, path } => {
+                let copied = target_mut(value, from)?.clone();
+                add(value, path, copied)
+            }
+            Operation::Test {
+                path,
+                value: expected,
+            } => {
+                let actual = target_mut(value, path)?;
+                if actual == expected {
+                    Ok(())
+                } else {
+                    Err(format!(
+                        "error: test failed at \"{}\": expected {} but found {}",
+                        path, expected, actual
+                    ))
+                }
+            }
+        }
+    }
+}
+
+fn diff_value(from: &Value, to: &Value, path: &JsonPointer, operations: &mut Vec<Operation>) {
+    match (from, to) {
+        _ if from == to => (),
+        (Value::Object(from), Value::Object(to)) => {
+            for (key, value) in from {
+                let path = path.child(key.as_str());
+                match to.get(key) {
+                    Some(to) => diff_value(value, to, &path, operations),
+                    None => operations.push(Operation::Remove { path }),
+                }
+            }
+            for (key, value) in to {
+                if !from.contains_key(key) {
+                    operations.push(Operation::Add {
+                        path: path.child(key.as_str()),
+                        value: value.clone(),
+                    });
+                }
+            }
+        }
+        (Value::Array(from), Value::Array(to)) => diff_array(from, to, path, operations),
+        _ => operations.push(Operation::Replace {
+            path: path.clone(),
+            value: to.clone(),
+        }),
+    }
+}
+
+/// 配列の差分に最長共通部分列を使う、表の要素数の上限 (超えると同じ添字どうしを比べる)
+const MAX_LCS_CELLS: usize = 1 << 20;
+
+/// 配列の編集操作
+enum Edit<'v> {
+    Keep,
+    Remove,
+    Add(&'v Value),
+    Change(&'v Value, &'v Value),
+}
+
+fn diff_array(from: &[Value], to: &[Value], path: &JsonPointer, operations: &mut Vec<Operation>) {
+    // 先頭と末尾の共通部分は比べない
+    let prefix = from.iter().zip(to).take_while(|(a, b)| a == b).count();
+    let (from_rest, to_rest) = (&from[prefix..], &to[prefix..]);
+    let suffix = from_rest
+        .iter()
+        .rev()
+        .zip(to_rest.iter().rev())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let from_mid = &from_rest[..from_rest.len() - suffix];
+    let to_mid = &to_rest[..to_rest.len() - suffix];
+
+    let edits = if (from_mid.len() + 1).saturating_mul(to_mid.len() + 1) <= MAX_LCS_CELLS {
+        lcs_edits(from_mid, to_mid)
+    } else {
+        pairwise_edits(from_mid, to_mid)
+    };
+
+    let mut index = prefix;
+    for edit in edits {
+        let item = path.child(index.to_string());
+        match edit {
+            Edit::Keep => index += 1,
+            Edit::Remove => operations.push(Operation::Remove { path: item }),
+            Edit::Add(value) => {
+                operations.push(Operation::Add {
+                    path: item,
+                    value: value.clone(),
+                });
+                index += 1;
+            }
+            Edit::Change(from, to) => {
+                diff_value(from, to, &item, operations);
+                index += 1;
+            }
+        }
+    }
+}
+
+/// 最長共通部分列から編集操作を求める。表は `(from.len() + 1) * (to.len() + 1)` 要素になる
+fn lcs_edits<'v>(from_mid: &'v [Value], to_mid: &'v [Value]) -> Vec<Edit<'v>> {
+    // lcs[i][j] は from_mid[i..] と to_mid[j..] の最長共通部分列の長さ
+    let (n, m) = (from_mid.len(), to_mid.len());
+    let mut lcs = vec![vec![0usize; m + 1]; n + 1];
+    for i in (0..n).rev() {
+        for j in (0..m).rev() {
+            lcs[i][j] = if from_mid[i] == to_mid[j] {
+                lcs[i + 1][j + 1] + 1
+            } else {
+                lcs[i + 1][j].max(lcs[i][j + 1])
+            };
+        }
+    }
+    let mut edits = vec![];
+    let (mut i, mut j) = (0, 0);
+    while i < n || j < m {
+        if i < n && j < m && from_mid[i] == to_mid[j] {
+            edits.push(Edit::Keep);
+            i += 1;
+            j += 1;
+        } else if i < n && j < m && lcs[i + 1][j + 1] == lcs[i][j] {
+            edits.push(Edit::Change(&from_mid[i], &to_mid[j]));
+            i += 1;
+            j += 1;
+        } else if j < m && (i == n || lcs[i][j + 1] >= lcs[i + 1][j]) {
+            edits.push(Edit::Add(&to_mid[j]));
+            j += 1;
+        } else {
+            edits.push(Edit::Remove);
+            i += 1;
+        }
+    }
+    edits
+}
+
+/// 同じ添字の要素どうしを比べ、長さの差は末尾への追加か末尾からの削除にする
+fn pairwise_edits<'v>(from_mid: &'v [Value], to_mid: &'v [Value]) -> Vec<Edit<'v>> {
+    let mut edits: Vec<Edit> = from_mid
+        .iter()
+        .zip(to_mid)
+        .map(|(from, to)| {
+            if from == to {
+                Edit::Keep
+            } else {
+                Edit::Change(from, to)
+            }
+        })
+        .collect();
+    edits.extend(to_mid.iter().skip(from_mid.len()).map(Edit::Add));
+    edits.extend(from_mid.iter().skip(to_mid.len()).map(|_| Edit::Remove));
+    edits
+}
+
+/// pointer を親の pointer と最後の参照トークンに分ける。値全体を指す場合は None
+fn split(pointer: &JsonPointer) -> Option<(JsonPointer, String)> {
+    Some((pointer.parent()?, pointer.last()?.to_string()))
+}
+
+fn target_mut<'v>(value: &'v mut Value, pointer: &JsonPointer) -> Result<&'v mut Value, String> {
+    pointer
+        .resolve_mut(value)
+        .ok_or_else(|| format!("error: \"{}\" does not exist", pointer))
+}
+
+fn parent_mut<'v>(
+    value: &'v mut Value,
+    pointer: &JsonPointer,
+    parent: &JsonPointer,
+) -> Result<&'v mut Value, String> {
+    parent
+        .resolve_mut(value)
+        .ok_or_else(|| format!("error: the parent of \"{}\" does not exist", pointer))
+}
+
+fn add(value: &mut Value, pointer: &JsonPointer, new: Value) -> Result<(), String> {
+    let Some((parent, last)) = split(pointer) else {
+        *value = new;
+        return Ok(());
+    };
+    match parent_mut(value, pointer, &parent)? {
+        Value::Object(object) => {
+            object.insert(last, new);
+            Ok(())
+        }
+        Value::Array(array) if last == "-" => {
+            array.push(new);
+            Ok(())
+        }
+        Value::Array(array) => match array_index(&last) {
+            Some(index) if index <= array.len() => {
+                array.insert(index, new);
+                Ok(())
+            }
+            _ => Err(format!("error: an invalid array index \"{}\"", pointer)),
+        },
+        _ => Err(format!(
+            "error: the parent of \"{}\" is not a container",
+            pointer
+        )),
+    }
+}
+
+fn remove(value: &mut Value, pointer: &JsonPointer) -> Result<Value, String> {
+    let Some((parent, last)) = split(pointer) else {
+        return Ok(value.take());
+    };
+    let removed = match parent_mut(value, pointer, &parent)? {
+        Value::Object(object) => object.remove(&last),
+        Value::Array(array) => match array_index(&last) {
+            Some(index) if index < array.len() => Some(array.remove(index)),
+            _ => None,
+        },
+        _ => None,
+    };
+    removed.ok_or_else(|| format!("error: \"{}\" does not exist", pointer))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    fn apply(document: &str, patch: &str) -> Result<Value, PatchError> {
+        let mut value = parse(document);
+        Patch::from_value(&parse(patch))?.apply(&mut value)?;
+        Ok(value)
+    }
+
+    #[test]
+    fn test_apply() {
+        // RFC 6902 の付録 A の例
+        let cases = [
+            (
+                r#"{"foo": "bar"}"#,
+                r#"[{"op": "add", "path": "/baz", "value": "qux"}]"#,
+                r#"{"baz": "qux", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "baz"]}"#,
+                r#"[{"op": "add", "path": "/foo/1", "value": "qux"}]"#,
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "remove", "path": "/baz"}]"#,
+                r#"{"foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": ["bar", "qux", "baz"]}"#,
+                r#"[{"op": "remove", "path": "/foo/1"}]"#,
+                r#"{"foo": ["bar", "baz"]}"#,
+            ),
+            (
+                r#"{"baz": "qux", "foo": "bar"}"#,
+                r#"[{"op": "replace", "path": "/baz", "value": "boo"}]"#,
+                r#"{"baz": "boo", "foo": "bar"}"#,
+            ),
+            (
+                r#"{"foo": {"bar": "baz", "waldo": "fred"}, "qux": {"corge": "grault"}}"#,
+                r#"[{"op": "move", "from": "/foo/waldo", "path": "/qux/thud"}]"#,
+                r#"{"foo": {"bar": "baz"}, "qux": {"corge": "grault", "thud": "fred"}}"#,
+            ),
+            (
+                r#"{"foo": ["all", "grass", "cows", "eat"]}"#,
+                r#"[{"op": "move", "from": "/foo/1", "path": "/foo/3"}]"#,
+                r#"{"foo": ["all", "cows", "eat", "grass"]}"#,
+            ),
+            (
+                r#"{"foo": ["bar"]}"#,
+                r#"[{"op": "add", "path": "/foo/-", "value": ["abc", "def"]}]"#,
+                r#"{"foo": ["bar", ["abc", "def"]]}"#,
+            ),
+            (
+                r#"{"foo": 1}"#,
+                r#"[{"op": "copy", "from": "/foo", "path": "/bar"}, {"op": "test", "path": "/bar", "value": 1}]"#,
+                r#"{"foo": 1, "bar": 1}"#,
+            ),
+            (
+                r#"{"foo": 1}"#,
+                r#"[{"op": "add", "path": "", "value": [1]}]"#,
+                "[1]",
+            ),
+        ];
+        for (document, patch, expected) in cases {
+            assert_eq!(
+                apply(document, patch).unwrap(),
+                parse(expected),
+                "{}",
+                patch
+            );
+        }
+    }
+
+    #[test]
+    fn test_diff() {
+        let cases = [
+            (
+                r#"{"a": 1, "b": {"c": 2}}"#,
+                r#"{"a": 1, "b": {"c": 3}, "d/e": true}"#,
+            ),
+            (r#"{"a": 1, "b": 2}"#, r#"{"b": 2}"#),
+            ("[1, 2, 3, 4, 5]", "[1, 3, 4, 6, 5]"),
+            ("[1, 2, 3]", "[]"),
+            ("[]", "[1, 2, 3]"),
+            ("[1, 2, 3]", "[3, 2, 1]"),
+            (
+                r#"[{"id": 1, "v": "a"}, {"id": 2}]"#,
+                r#"[{"id": 1, "v": "b"}, {"id": 2}]"#,
+            ),
+            (
+                r#"{"a": [1, {"b": [true]}]}"#,
+                r#"{"a": [0, 1, {"b": [false, true]}]}"#,
+            ),
+            ("[1]", r#"{"a": 1}"#),
+            ("1", "2"),
+        ];
+        for (from, to) in cases {
+            let mut value = parse(from);
+            let patch = Patch::diff(&value, &parse(to));
+            patch.apply(&mut value).unwrap();
+            assert_eq!(value, parse(to), "{} -> {}", from, to);
+            // JSON にしたパッチを読み直しても同じになる
+            assert_eq!(Patch::from_value(&patch.to_value()).unwrap(), patch);
+        }
+
+        let patch = Patch::diff(&parse("[1, 2, 3, 4, 5]"), &parse("[1, 3, 4, 6, 5]"));
+        assert_eq!(
+            patch.to_value(),
+            parse(r#"[{"op": "remove", "path": "/1"}, {"op": "add", "path": "/3", "value": 6}]"#)
+        );
+        let patch = Patch::diff(
+            &parse(r#"[{"id": 1, "v": "a"}, {"id": 2}]"#),
+            &parse(r#"[{"id": 1, "v": "b"}, {"id": 2}]"#),
+        );
+        assert_eq!(
+            patch.to_value(),
+            parse(r#"[{"op": "replace", "path": "/0/v", "value": "b"}]"#)
+        );
+        assert!(Patch::diff(&parse("[1]"), &parse("[1]"))
+            .operations
+            .is_empty());
+
+        // 大きな配列は表を作らず、同じ添字の要素どうしを比べる
+        let from = Value::Array((0..3000).map(|i| Value::from(i as i64)).collect());
+        let to = Value::Array((1..=2500).map(|i| Value::from(-i as i64)).collect());
+        for (from, to) in [(&from, &to), (&to, &from)] {
+            let mut value = from.clone();
+            let patch = Patch::diff(&value, to);
+            assert_eq!(patch.operations.len(), 3000);
+            patch.apply(&mut value).unwrap();
+            assert_eq!(&value, to);
+        }
+    }
+
+    #[test]
+    fn test_error() {
+        let e = apply(
+            r#"{"baz": "qux"}"#,
+            r#"[{"op": "add", "path": "/a", "value": 1}, {"op": "test", "path": "/baz", "value": "bar"}]"#,
+        )
+        .unwrap_err();
+        assert_eq!(e.index, 1);
+        assert_eq!(
+            e.msg,
+            r#"error: test failed at "/baz": expected "bar" but found "qux""#
+        );
+
+        let e = apply(
+            r#"{"foo": "bar"}"#,
+            r#"[{"op": "add", "path": "/baz/bat", "value": "qux"}]"#,
+        )
+        .unwrap_err();
+        assert_eq!(e.msg, r#"error: the parent of "/baz/bat" does not exist"#);
+
+        let e = apply(
+            r#"{"foo": [1]}"#,
+            r#"[{"op": "add", "path": "/foo/2", "value": 1}]"#,
+        )
+        .unwrap_err();
+        assert_eq!(e.msg, r#"error: an invalid array index "/foo/2""#);
+
+        let e = apply(
+            r#"{"foo": {}}"#,
+            r#"[{"op": "move", "from": "/foo", "path": "/foo/a"}]"#,
+        )
+        .unwrap_err();
+        assert!(e.msg.contains("its own child"));
+
+        let e = apply("{}", r#"[{"op": "jump", "path": "/a"}]"#).unwrap_err();
+        assert_eq!(e.msg, r#"error: an unknown operation "jump""#);
+        let e = apply("{}", r#"[{"op": "add", "path": "/a"}]"#).unwrap_err();
+        assert_eq!(e.msg, r#"error: "value" is missing"#);
+        let e = apply("{}", r#"[{"op": "add", "path": "a", "value": 1}]"#).unwrap_err();
+        assert_eq!(e.msg, r#"error: a JSON pointer must start with / "a""#);
+
+        let patch = Patch::new(vec![Operation::Add {
+            path: JsonPointer::root().key("a/b").index(0),
+            value: Value::from(1),
+        }]);
+        let mut value = parse(r#"{"a/b": []}"#);
+        patch.apply(&mut value).unwrap();
+        assert_eq!(value, parse(r#"{"a/b": [1]}"#));
+        assert_eq!(patch.to_value()[0]["path"], "/a~1b/0");
+
+        // 失敗したパッチは何も変更しない
+        let mut value = parse(r#"{"a": 1}"#);
+        let patch = Patch::from_value(&parse(
+            r#"[{"op": "remove", "path": "/a"}, {"op": "remove", "path": "/a"}]"#,
+        ))
+        .unwrap();
+        assert!(patch.apply(&mut value).is_err());
+        assert_eq!(value, parse(r#"{"a": 1}"#));
+    }
+}
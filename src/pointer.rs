@@ -0,0 +1,540 @@
+use std::{borrow::Cow, collections::BTreeMap, fmt, str::FromStr};
+
+use crate::{parser::ParserError, patch::PatchError, value::Value};
+
+/// JSON Pointer (RFC 6901)。エスケープを解いた参照トークンの列として保持する
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct JsonPointer {
+    tokens: Vec<String>,
+}
+
+impl JsonPointer {
+    /// 値全体を指す pointer (`""`)
+    pub fn root() -> JsonPointer {
+        Self::default()
+    }
+
+    /// `/a/b~1c` のような文字列を読む。空文字列は値全体を指す
+    pub fn parse(pointer: &str) -> Result<JsonPointer, ParserError> {
+        parse_pointer(pointer).map(|tokens| JsonPointer { tokens })
+    }
+
+    /// 末尾にオブジェクトのキーを加えた pointer を返す
+    ///
+    /// `JsonPointer::root().key("users").index(0).key("name")` は `/users/0/name` を指す
+    pub fn key(mut self, key: impl Into<String>) -> JsonPointer {
+        self.push(key);
+        self
+    }
+
+    /// 末尾に配列の添字を加えた pointer を返す
+    pub fn index(mut self, index: usize) -> JsonPointer {
+        self.push(index.to_string());
+        self
+    }
+
+    /// 末尾に参照トークンを加えた複製を返す
+    pub fn child(&self, token: impl Into<String>) -> JsonPointer {
+        self.clone().key(token)
+    }
+
+    /// 親を指す pointer。値全体を指していれば None
+    pub fn parent(&self) -> Option<JsonPointer> {
+        let (_, parent) = self.tokens.split_last()?;
+        Some(JsonPointer {
+            tokens: parent.to_vec(),
+        })
+    }
+
+    /// 末尾の参照トークン
+    pub fn last(&self) -> Option<&str> {
+        self.tokens.last().map(String::as_str)
+    }
+
+    /// 値全体を指すか
+    pub fn is_root(&self) -> bool {
+        self.tokens.is_empty()
+    }
+
+    /// `other` 自身か、その子孫を指すか
+    pub fn starts_with(&self, other: &JsonPointer) -> bool {
+        self.tokens.starts_with(&other.tokens)
+    }
+
+    /// `value` の中でこの pointer の指す値を返す
+    pub fn resolve<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.tokens
+            .iter()
+            .try_fold(value, |value, token| match value {
+                Value::Object(object) => object.get(token),
+                Value::Array(array) => array.get(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    pub fn resolve_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.tokens
+            .iter()
+            .try_fold(value, |value, token| match value {
+                Value::Object(object) => object.get_mut(token),
+                Value::Array(array) => array.get_mut(array_index(token)?),
+                _ => None,
+            })
+    }
+
+    /// 参照トークンの列 (エスケープは解いたもの)
+    pub fn tokens(&self) -> &[String] {
+        &self.tokens
+    }
+
+    /// 末尾に参照トークンを加える
+    pub fn push(&mut self, token: impl Into<String>) {
+        self.tokens.push(token.into());
+    }
+
+    /// 末尾の参照トークンを取り除いて返す
+    pub fn pop(&mut self) -> Option<String> {
+        self.tokens.pop()
+    }
+}
+
+/// `/a/b~1c` のようにエスケープして表示する
+impl fmt::Display for JsonPointer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for token in &self.tokens {
+            write!(f, "/{}", escape(token))?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for JsonPointer {
+    type Err = ParserError;
+
+    fn from_str(s: &str) -> Result<JsonPointer, ParserError> {
+        JsonPointer::parse(s)
+    }
+}
+
+/// `Value::pointer` などに渡せる pointer (`JsonPointer` とその文字列表記)
+pub trait ToPointer {
+    /// 不正な文字列ならエラー
+    fn to_pointer(&self) -> Result<Cow<'_, JsonPointer>, ParserError>;
+}
+
+impl ToPointer for JsonPointer {
+    fn to_pointer(&self) -> Result<Cow<'_, JsonPointer>, ParserError> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+impl ToPointer for str {
+    fn to_pointer(&self) -> Result<Cow<'_, JsonPointer>, ParserError> {
+        JsonPointer::parse(self).map(Cow::Owned)
+    }
+}
+
+impl ToPointer for String {
+    fn to_pointer(&self) -> Result<Cow<'_, JsonPointer>, ParserError> {
+        self.as_str().to_pointer()
+    }
+}
+
+impl<P: ToPointer + ?Sized> ToPointer for &P {
+    fn to_pointer(&self) -> Result<Cow<'_, JsonPointer>, ParserError> {
+        (**self).to_pointer()
+    }
+}
+
+/// JSON Pointer (RFC 6901) を参照トークンの列に分解する
+pub(crate) fn parse_pointer(pointer: &str) -> Result<Vec<String>, ParserError> {
+    if pointer.is_empty() {
+        return Ok(vec![]);
+    }
+    let rest = pointer.strip_prefix('/').ok_or_else(|| {
+        ParserError::new(&format!(
+            "error: a JSON pointer must start with / \"{}\"",
+            pointer
+        ))
+    })?;
+    rest.split('/')
+        .map(|token| {
+            unescape(token).ok_or_else(|| {
+                ParserError::new(&format!(
+                    "error: ~ must be followed by 0 or 1 in a JSON pointer \"{}\"",
+                    pointer
+                ))
+            })
+        })
+        .collect()
+}
+
+/// キーを参照トークンにする (`~` を `~0` に、`/` を `~1` に)
+pub(crate) fn escape(key: &str) -> String {
+    key.replace('~', "~0").replace('/', "~1")
+}
+
+/// 参照トークンの `~1` を `/` に、`~0` を `~` に戻す。`~` の後がそれ以外なら None
+pub(crate) fn unescape(token: &str) -> Option<String> {
+    let mut unescaped = String::with_capacity(token.len());
+    let mut chars = token.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '~' => match chars.next() {
+                Some('0') => unescaped.push('~'),
+                Some('1') => unescaped.push('/'),
+                _ => return None,
+            },
+            c => unescaped.push(c),
+        }
+    }
+    Some(unescaped)
+}
+
+/// 配列の添字を表す参照トークン (先頭に 0 のない 10 進数) を数値にする
+pub(crate) fn array_index(token: &str) -> Option<usize> {
+    if token.is_empty()
+        || !token.bytes().all(|b| b.is_ascii_digit())
+        || (token.len() > 1 && token.starts_with('0'))
+    {
+        return None;
+    }
+    token.parse().ok()
+}
+
+/// `Value::get_segments` に渡す経路の 1 段
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PathSegment {
+    Key(String),  // オブジェクトのキー
+    Index(usize), // 配列の添字
+}
+
+impl From<&str> for PathSegment {
+    fn from(key: &str) -> PathSegment {
+        PathSegment::Key(key.to_string())
+    }
+}
+
+impl From<String> for PathSegment {
+    fn from(key: String) -> PathSegment {
+        PathSegment::Key(key)
+    }
+}
+
+impl From<usize> for PathSegment {
+    fn from(index: usize) -> PathSegment {
+        PathSegment::Index(index)
+    }
+}
+
+impl Value {
+    /// キーの列で辿った値を返す。配列では各段を添字として読む
+    ///
+    /// `get_path(&["spec", "containers", "0", "image"])` は `/spec/containers/0/image` と同じ値を指す
+    pub fn get_path<S: AsRef<str>>(&self, path: &[S]) -> Option<&Value> {
+        path.iter().try_fold(self, |value, segment| {
+            let segment = segment.as_ref();
+            match value {
+                Value::Object(object) => object.get(segment),
+                Value::Array(array) => array.get(array_index(segment)?),
+                _ => None,
+            }
+        })
+    }
+
+    pub fn get_path_mut<S: AsRef<str>>(&mut self, path: &[S]) -> Option<&mut Value> {
+        path.iter().try_fold(self, |value, segment| {
+            let segment = segment.as_ref();
+            match value {
+                Value::Object(object) => object.get_mut(segment),
+                Value::Array(array) => array.get_mut(array_index(segment)?),
+                _ => None,
+            }
+        })
+    }
+
+    /// `PathSegment` の列で辿った値を返す。キーはオブジェクトにだけ、添字は配列にだけ一致する
+    pub fn get_segments(&self, path: &[PathSegment]) -> Option<&Value> {
+        path.iter()
+            .try_fold(self, |value, segment| match (value, segment) {
+                (Value::Object(object), PathSegment::Key(key)) => object.get(key),
+                (Value::Array(array), PathSegment::Index(index)) => array.get(*index),
+                _ => None,
+            })
+    }
+
+    /// JSON Pointer (RFC 6901) の指す値を返す。指す値がないか、pointer が不正なら None
+    ///
+    /// `""` は値全体を、`"/users/0/name"` は `users` の 0 番目の `name` を指す
+    pub fn pointer<P: ToPointer + ?Sized>(&self, pointer: &P) -> Option<&Value> {
+        pointer.to_pointer().ok()?.resolve(self)
+    }
+
+    /// JSON Pointer (RFC 6901) の指す可変の値を返す
+    pub fn pointer_mut<P: ToPointer + ?Sized>(&mut self, pointer: &P) -> Option<&mut Value> {
+        pointer.to_pointer().ok()?.resolve_mut(self)
+    }
+
+    /// `(pointer, value)` の組を順に設定 (追加または置き換え) する。途中のオブジェクトや配列がなければ作る
+    ///
+    /// 作るのは、次の参照トークンが添字か `-` なら配列、それ以外ならオブジェクト。
+    /// 途中で失敗した場合 `self` は変更せず、`PatchError::index` は失敗した組の位置になる。
+    /// そのために呼ぶたびに最初に `self` 全体を複製するので、大きな値に少しずつ何度も設定する
+    /// よりは、まとめて 1 回で設定するほうがよい
+    pub fn set_all<P: ToPointer>(&mut self, updates: &[(P, Value)]) -> Result<(), PatchError> {
+        self.set_all_with(updates, true)
+    }
+
+    /// `set_all` と同じ。`create_missing` が false なら、途中の値がないときはエラーにする
+    pub fn set_all_with<P: ToPointer>(
+        &mut self,
+        updates: &[(P, Value)],
+        create_missing: bool,
+    ) -> Result<(), PatchError> {
+        let mut updated = self.clone();
+        for (index, (pointer, value)) in updates.iter().enumerate() {
+            pointer
+                .to_pointer()
+                .map_err(|e| e.msg)
+                .and_then(|pointer| set(&mut updated, &pointer, value.clone(), create_missing))
+                .map_err(|msg| PatchError::new(index, &msg))?;
+        }
+        *self = updated;
+        Ok(())
+    }
+}
+
+/// 値を設定する。配列の添字が要素数と同じか `-` なら末尾に加え、それ以外は置き換える
+fn set(
+    value: &mut Value,
+    pointer: &JsonPointer,
+    new: Value,
+    create_missing: bool,
+) -> Result<(), String> {
+    let (Some(parent), Some(last)) = (pointer.parent(), pointer.last()) else {
+        *value = new;
+        return Ok(());
+    };
+    let last = last.to_string();
+    let parent = if create_missing {
+        let mut current = value;
+        for token in parent.tokens() {
+            current = child_or_create(current, token)
+                .ok_or_else(|| format!("error: cannot create \"{}\"", pointer))?;
+        }
+        vivify(current, &last);
+        current
+    } else {
+        parent
+            .resolve_mut(value)
+            .ok_or_else(|| format!("error: the parent of \"{}\" does not exist", pointer))?
+    };
+    match parent {
+        Value::Object(object) => {
+            object.insert(last, new);
+            Ok(())
+        }
+        Value::Array(array) => {
+            let index = if last == "-" {
+                Some(array.len())
+            } else {
+                array_index(&last)
+            };
+            match index {
+                Some(index) if index < array.len() => array[index] = new,
+                Some(index) if index == array.len() => array.push(new),
+                _ => return Err(format!("error: an invalid array index \"{}\"", pointer)),
+            }
+            Ok(())
+        }
+        _ => Err(format!(
+            "error: the parent of \"{}\" is not a container",
+            pointer
+        )),
+    }
+}
+
+/// null なら `token` で辿れるコンテナ (添字か `-` なら配列、それ以外はオブジェクト) にする
+fn vivify(value: &mut Value, token: &str) {
+    if value.is_null() {
+        *value = if token == "-" || array_index(token).is_some() {
+            Value::Array(vec![])
+        } else {
+            Value::Object(BTreeMap::new())
+        };
+    }
+}
+
+/// `token` の指す子を返す。なければ null の子を加えて返す
+fn child_or_create<'v>(value: &'v mut Value, token: &str) -> Option<&'v mut Value> {
+    vivify(value, token);
+    match value {
+        Value::Object(object) => Some(object.entry(token.to_string()).or_default()),
+        Value::Array(array) => {
+            let index = if token == "-" {
+                array.len()
+            } else {
+                array_index(token)?
+            };
+            if index == array.len() {
+                array.push(Value::Null);
+            }
+            array.get_mut(index)
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_pointer() {
+        // RFC 6901 の例
+        let value: Value = r#"{
+            "foo": ["bar", "baz"],
+            "": 0,
+            "a/b": 1,
+            "c%d": 2,
+            "e^f": 3,
+            "g|h": 4,
+            "i\\j": 5,
+            "k\"l": 6,
+            " ": 7,
+            "m~n": 8
+        }"#
+        .parse()
+        .unwrap();
+        assert_eq!(value.pointer(""), Some(&value));
+        assert_eq!(
+            value.pointer("/foo"),
+            Some(&Value::from(vec!["bar", "baz"]))
+        );
+        assert_eq!(value.pointer("/foo/0"), Some(&Value::from("bar")));
+        assert_eq!(value.pointer("/"), Some(&Value::from(0)));
+        assert_eq!(value.pointer("/a~1b"), Some(&Value::from(1)));
+        assert_eq!(value.pointer("/c%d"), Some(&Value::from(2)));
+        assert_eq!(value.pointer("/i\\j"), Some(&Value::from(5)));
+        assert_eq!(value.pointer("/k\"l"), Some(&Value::from(6)));
+        assert_eq!(value.pointer("/ "), Some(&Value::from(7)));
+        assert_eq!(value.pointer("/m~0n"), Some(&Value::from(8)));
+
+        assert_eq!(value.pointer("foo"), None);
+        assert_eq!(value.pointer("/foo/2"), None);
+        assert_eq!(value.pointer("/foo/01"), None);
+        assert_eq!(value.pointer("/foo/-"), None);
+        assert_eq!(value.pointer("/foo/0/x"), None);
+    }
+
+    #[test]
+    fn test_json_pointer() {
+        let mut pointer = JsonPointer::root();
+        assert_eq!(pointer.to_string(), "");
+        pointer.push("a/b");
+        pointer.push("0");
+        pointer.push("m~n");
+        assert_eq!(pointer.to_string(), "/a~1b/0/m~0n");
+        assert_eq!(pointer.tokens(), ["a/b", "0", "m~n"]);
+
+        let parsed: JsonPointer = "/a~1b/0/m~0n".parse().unwrap();
+        assert_eq!(parsed, pointer);
+        assert_eq!(parsed.last(), Some("m~n"));
+        assert_eq!(parsed.parent().unwrap().to_string(), "/a~1b/0");
+        assert!(parsed.starts_with(&JsonPointer::root().key("a/b")));
+        assert!(!parsed.starts_with(&JsonPointer::root().key("a")));
+        assert!(JsonPointer::parse("a").is_err());
+        assert!(JsonPointer::parse("/a~2").is_err());
+        assert!(JsonPointer::parse("/a~").is_err());
+        assert!(JsonPointer::parse("/~9/b").is_err());
+        assert_eq!(JsonPointer::parse("/~01").unwrap().tokens(), ["~1"]);
+        assert!(JsonPointer::root().is_root() && JsonPointer::root().parent().is_none());
+
+        let value: Value = r#"{"users": [{"name": "alice"}]}"#.parse().unwrap();
+        let name = JsonPointer::root().key("users").index(0).key("name");
+        assert_eq!(name.to_string(), "/users/0/name");
+        assert_eq!(value.pointer(&name), Some(&Value::from("alice")));
+        assert_eq!(
+            value.pointer(&name.to_string()),
+            value.pointer("/users/0/name")
+        );
+    }
+
+    #[test]
+    fn test_get_path() {
+        let mut value: Value = r#"{"spec": {"containers": [{"image": "nginx"}]}}"#.parse().unwrap();
+        let path = ["spec", "containers", "0", "image"];
+        assert_eq!(value.get_path(&path), Some(&Value::from("nginx")));
+        assert_eq!(value.get_path(&["spec", "containers", "1"]), None);
+        assert_eq!(value.get_path::<&str>(&[]), Some(&value));
+        let path = vec!["spec".to_string(), "missing".to_string()];
+        assert_eq!(value.get_path(&path), None);
+
+        let segments = [
+            PathSegment::from("spec"),
+            "containers".into(),
+            0.into(),
+            "image".into(),
+        ];
+        assert_eq!(value.get_segments(&segments), Some(&Value::from("nginx")));
+        let segments = [PathSegment::from("spec"), "containers".into(), "0".into()];
+        assert_eq!(value.get_segments(&segments), None);
+
+        *value
+            .get_path_mut(&["spec", "containers", "0", "image"])
+            .unwrap() = "redis".into();
+        assert_eq!(
+            value.pointer("/spec/containers/0/image"),
+            Some(&"redis".into())
+        );
+    }
+
+    #[test]
+    fn test_pointer_mut() {
+        let mut value: Value = r#"{"users": [{"name": "alice"}]}"#.parse().unwrap();
+        *value.pointer_mut("/users/0/name").unwrap() = Value::from("bob");
+        assert_eq!(value["users"][0]["name"], "bob");
+        assert!(value.pointer_mut("/users/1").is_none());
+    }
+
+    #[test]
+    fn test_set_all() {
+        let mut value = parse(r#"{"a": {"b": 1}, "list": [1, 2]}"#);
+        value
+            .set_all(&[
+                ("/a/b", Value::from(2)),
+                ("/a/c/d", Value::from("x")),
+                ("/list/1", Value::from(3)),
+                ("/list/-", Value::from(4)),
+                ("/new/0/name", Value::from("n")),
+                ("/null", Value::Null),
+            ])
+            .unwrap();
+        assert_eq!(
+            value,
+            parse(
+                r#"{"a": {"b": 2, "c": {"d": "x"}}, "list": [1, 3, 4],
+                    "new": [{"name": "n"}], "null": null}"#
+            )
+        );
+
+        // 失敗したら変更しない
+        let before = value.clone();
+        let e = value
+            .set_all(&[("/a/b", Value::from(0)), ("/a/b/c", Value::from(1))])
+            .unwrap_err();
+        assert_eq!(e.index, 1);
+        assert_eq!(value, before);
+        assert!(value.set_all(&[("/list/9", Value::from(1))]).is_err());
+
+        let e = value
+            .set_all_with(&[("/a/b", Value::from(5)), ("/x/y", Value::from(1))], false)
+            .unwrap_err();
+        assert_eq!(e.index, 1);
+        value
+            .set_all_with(&[("/a/b", Value::from(5))], false)
+            .unwrap();
+        assert_eq!(value["a"]["b"], 5);
+    }
+}
@@ -1,9 +1,748 @@
-use crate::parser::Value;
+use std::{
+    collections::BTreeMap,
+    env,
+    ffi::{OsStr, OsString},
+    fmt, fs,
+    io::{self, IsTerminal},
+    ops::Range,
+    path::{Path, PathBuf},
+    process,
+    sync::atomic::{self, AtomicU64},
+};
+
+use crate::{
+    formatter::{write_with, Formatter},
+    number::{format_f64, format_f64_with, Number},
+    parser::Value,
+    pointer::ToPointer,
+    value::RawValue,
+};
 
 pub struct JsonPrinter {}
 
+/// JSON の書き出しの設定
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrinterOptions {
+    /// 要素ごとに改行して字下げする
+    pub pretty: bool,
+    /// 字下げに使う文字
+    pub indent_char: char,
+    /// 字下げ 1 段の文字数
+    pub indent_width: usize,
+    /// `Infinity` や `NaN` を null にせずそのまま書き出す (JSON5 形式)
+    pub json5: bool,
+    /// ASCII 以外の文字を `\uXXXX` (U+10000 以上はサロゲートペア) にエスケープする
+    pub escape_non_ascii: bool,
+    /// `/` を `\/` にエスケープする (HTML の `<script>` に埋め込む場合や、古い実装と出力をそろえる場合)
+    pub escape_slashes: bool,
+    /// `<`、`>`、`&`、U+2028、U+2029 を `\uXXXX` にエスケープし、HTML や JavaScript に
+    /// そのまま埋め込めるようにする
+    pub escape_html: bool,
+    /// 整数以外の数値を、小数点以下この桁数に丸めて書き出す。None なら読み戻せる最短の桁にする
+    pub float_precision: Option<usize>,
+    /// 10 進の指数がこの範囲なら小数点表記、外なら指数表記 (`1.5e+21`) にする。既定は `-6..21`
+    pub decimal_exponents: Range<i32>,
+    /// 小数部の末尾の 0 (と残った小数点) を取り除く。`float_precision` と合わせて使う
+    pub trim_trailing_zeros: bool,
+    /// キーを並べた順で書き出す。オブジェクトは常にキーの順に並んでいるので、これは未解析の
+    /// 部分木 (`Value::Raw`) を解析し直して、全体の出力をキーの順にそろえるかを決める
+    pub sort_keys: bool,
+    /// キーや値を ANSI エスケープシーケンスで色付けする (端末向け)
+    pub color: bool,
+    /// 整形するとき、1 行に書いた幅がこの文字数以下の配列やオブジェクトは改行せずに
+    /// `[1, 2]` や `{"a": 1}` のように書く。None なら常に要素ごとに改行する
+    pub compact_width: Option<usize>,
+    /// 整形するとき、1 行にまとめない数値や文字列などだけの配列を、字下げを含めた 1 行が
+    /// この文字数に収まるように詰めて折り返す。None なら要素ごとに改行する
+    pub max_width: Option<usize>,
+    /// 整形するときの改行
+    pub line_ending: LineEnding,
+    /// この深さより深い空でないオブジェクトや配列を `{…}` や `[…]` と書く (ログ向け)
+    pub max_depth: Option<usize>,
+    /// 配列やオブジェクトの要素をこの数まで書き、残りを `… N more` と書く (ログ向け)。
+    /// `max_depth` か `max_items` を指定した出力は、要約と区別できるように JSON の値と
+    /// 紛れない記号を使うので、JSON として解析し直すことはできない
+    pub max_items: Option<usize>,
+    /// 数値に解析したときの元の表記 (`Number::text`) があれば、`float_precision` などより優先して
+    /// そのまま書く (JSON の数値の文法に合わない表記は、解析したときに残していない)
+    pub keep_number_text: bool,
+}
+
+/// 整形した出力の改行の種類
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LineEnding {
+    #[default]
+    Lf, // `\n`
+    CrLf, // `\r\n`
+}
+
+impl LineEnding {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            LineEnding::Lf => "\n",
+            LineEnding::CrLf => "\r\n",
+        }
+    }
+}
+
+/// 色付けに使う ANSI エスケープシーケンス
+const KEY_COLOR: &str = "\x1b[34;1m"; // 太字の青
+const STRING_COLOR: &str = "\x1b[32m"; // 緑
+const NUMBER_COLOR: &str = "\x1b[36m"; // シアン
+const BOOL_COLOR: &str = "\x1b[33m"; // 黄
+const NULL_COLOR: &str = "\x1b[90m"; // 灰
+const RESET_COLOR: &str = "\x1b[0m";
+
+impl Default for PrinterOptions {
+    fn default() -> Self {
+        PrinterOptions {
+            pretty: false,
+            indent_char: ' ',
+            indent_width: 2,
+            json5: false,
+            escape_non_ascii: false,
+            escape_slashes: false,
+            escape_html: false,
+            float_precision: None,
+            decimal_exponents: -6..21,
+            trim_trailing_zeros: false,
+            sort_keys: false,
+            color: false,
+            compact_width: None,
+            max_width: None,
+            line_ending: LineEnding::Lf,
+            max_depth: None,
+            max_items: None,
+            keep_number_text: true,
+        }
+    }
+}
+
+impl PrinterOptions {
+    /// 2 文字の空白で字下げする整形の設定
+    pub fn pretty() -> PrinterOptions {
+        PrinterOptions {
+            pretty: true,
+            ..Default::default()
+        }
+    }
+
+    /// タブ 1 文字で字下げする整形の設定
+    pub fn pretty_with_tabs() -> PrinterOptions {
+        PrinterOptions {
+            indent_char: '\t',
+            indent_width: 1,
+            ..PrinterOptions::pretty()
+        }
+    }
+
+    /// 標準出力が端末で、環境変数 `NO_COLOR` が空でなく設定されていなければ色付けする整形の設定
+    pub fn pretty_for_terminal() -> PrinterOptions {
+        PrinterOptions {
+            color: color_enabled(),
+            ..PrinterOptions::pretty()
+        }
+    }
+}
+
+/// `PrinterOptions` に従って書き出す `Formatter`
+struct OptionsFormatter<'a> {
+    options: &'a PrinterOptions,
+    depth: usize,
+    has_value: bool,                  // 今のコンテナに要素を書いたか
+    inline_depth: usize,              // 1 行にまとめているコンテナの深さ (0 ならまとめていない)
+    plain: bool,                      // 幅を測るために色付けしない
+    wrap: Option<(Vec<bool>, usize)>, // 折り返す配列の、要素ごとに改行するか と 次の要素の位置
+}
+
+impl<'a> OptionsFormatter<'a> {
+    fn new(options: &'a PrinterOptions) -> OptionsFormatter<'a> {
+        OptionsFormatter {
+            options,
+            depth: 0,
+            has_value: false,
+            inline_depth: 0,
+            plain: false,
+            wrap: None,
+        }
+    }
+
+    /// `max_width` に収まるように詰めたとき、各要素の前で改行するかを返す。
+    /// 折り返さない配列 (コンテナを含むもの、空のもの) なら None
+    fn wrap_points(&self, array: &[Value]) -> Option<Vec<bool>> {
+        let max_width = self.options.max_width?;
+        let scalar = |v: &Value| match v {
+            Value::Array(_) | Value::Object(_) => false,
+            // sort_keys では解析し直してコンテナとして書くことがある
+            Value::Raw(_) => !self.options.sort_keys,
+            _ => true,
+        };
+        if !self.options.pretty || array.is_empty() || !array.iter().all(scalar) {
+            return None;
+        }
+        let mut measure = OptionsFormatter {
+            plain: true,
+            ..OptionsFormatter::new(self.options)
+        };
+        let indent = (self.depth + 1) * self.options.indent_width;
+        let max_items = self.options.max_items.unwrap_or(usize::MAX);
+        let mut width = 0; // 今の行の幅 (字下げを除く)
+        let mut breaks = Vec::with_capacity(array.len().min(max_items) + 1);
+        // 省く要素は測らず、代わりに `… N more` を測る
+        let more = array.len().saturating_sub(max_items);
+        let items = array.iter().take(max_items).map(Some);
+        for value in items.chain((more > 0).then_some(None)) {
+            let mut buf = String::new();
+            // String への書き込みは失敗しない
+            let _ = match value {
+                Some(value) => write_item(&mut buf, value, &mut measure, self.depth + 1),
+                None => write_more(&mut buf, more),
+            };
+            let len = buf.chars().count();
+            // 末尾のカンマの分も数える
+            let start = width == 0 || indent + width + 2 + len + 1 > max_width;
+            width = if start { len } else { width + 2 + len };
+            breaks.push(start);
+        }
+        Some(breaks)
+    }
+
+    /// 要素 (オブジェクトならキーも) を 1 行に書いた幅が `compact_width` 以下かを返す
+    ///
+    /// `max_items` を超える要素は測らず、代わりに `… N more` を測る
+    fn fits_inline<'v>(
+        &self,
+        open: char,
+        close: char,
+        items: impl ExactSizeIterator<Item = (Option<&'v String>, &'v Value)>,
+    ) -> bool {
+        use fmt::Write;
+
+        let Some(limit) = self.options.compact_width else {
+            return false;
+        };
+        let mut measure = OptionsFormatter {
+            inline_depth: 1,
+            plain: true,
+            ..OptionsFormatter::new(self.options)
+        };
+        let mut w = WidthLimit { width: 0, limit };
+        let max_items = self.options.max_items.unwrap_or(usize::MAX);
+        let len = items.len();
+        let write = || -> fmt::Result {
+            w.write_char(open)?;
+            for (i, (key, value)) in items.take(max_items).enumerate() {
+                if i > 0 {
+                    w.write_str(", ")?;
+                }
+                if let Some(key) = key {
+                    measure.write_key(&mut w, key)?;
+                    w.write_str(": ")?;
+                }
+                write_item(&mut w, value, &mut measure, self.depth + 1)?;
+            }
+            if len > max_items {
+                if max_items > 0 {
+                    w.write_str(", ")?;
+                }
+                write_more(&mut w, len - max_items)?;
+            }
+            w.write_char(close)
+        };
+        write().is_ok()
+    }
+
+    /// 色付けするなら `color` で囲んで `write` を呼ぶ
+    fn paint(
+        &self,
+        w: &mut dyn fmt::Write,
+        color: &str,
+        write: impl FnOnce(&mut dyn fmt::Write) -> fmt::Result,
+    ) -> fmt::Result {
+        if !self.options.color || self.plain {
+            return write(w);
+        }
+        w.write_str(color)?;
+        write(w)?;
+        w.write_str(RESET_COLOR)
+    }
+
+    fn newline(&self, w: &mut dyn fmt::Write) -> fmt::Result {
+        if self.options.pretty {
+            w.write_str(self.options.line_ending.as_str())?;
+            for _ in 0..self.depth * self.options.indent_width {
+                w.write_char(self.options.indent_char)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// `inline` ならこのコンテナを 1 行にまとめる
+    fn begin(&mut self, w: &mut dyn fmt::Write, open: char, inline: bool) -> fmt::Result {
+        self.depth += 1;
+        self.has_value = false;
+        if self.inline_depth > 0 || inline {
+            self.inline_depth += 1;
+        }
+        w.write_char(open)
+    }
+
+    fn end(&mut self, w: &mut dyn fmt::Write, close: char) -> fmt::Result {
+        self.depth -= 1;
+        if self.inline_depth > 0 {
+            self.inline_depth -= 1;
+        } else if self.has_value {
+            self.newline(w)?;
+        }
+        w.write_char(close)
+    }
+
+    fn begin_item(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        if !first {
+            w.write_char(',')?;
+        }
+        if let Some((breaks, next)) = &mut self.wrap {
+            let start = breaks.get(*next).copied().unwrap_or(true);
+            *next += 1;
+            return if start {
+                self.newline(w)
+            } else {
+                w.write_char(' ')
+            };
+        }
+        match self.inline_depth {
+            0 => self.newline(w),
+            _ if first => Ok(()),
+            _ => w.write_char(' '),
+        }
+    }
+
+    /// 整形していて、まだ 1 行にまとめていないなら、まとめられるかを調べる
+    fn should_inline<'v>(
+        &self,
+        open: char,
+        close: char,
+        items: impl ExactSizeIterator<Item = (Option<&'v String>, &'v Value)>,
+    ) -> bool {
+        self.options.pretty && self.inline_depth == 0 && self.fits_inline(open, close, items)
+    }
+}
+
+impl Formatter for OptionsFormatter<'_> {
+    fn write_null(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.paint(w, NULL_COLOR, |w| w.write_str("null"))
+    }
+
+    fn write_bool(&mut self, w: &mut dyn fmt::Write, b: bool) -> fmt::Result {
+        self.paint(w, BOOL_COLOR, |w| write!(w, "{}", b))
+    }
+
+    fn write_number(&mut self, w: &mut dyn fmt::Write, n: &Number) -> fmt::Result {
+        let options = self.options;
+        self.paint(w, NUMBER_COLOR, |w| {
+            if let Some(text) = n.text().filter(|_| options.keep_number_text) {
+                w.write_str(text)
+            } else if n.is_f64() {
+                w.write_str(&JsonPrinter::format_float(n.as_f64(), options))
+            } else {
+                write!(w, "{}", n)
+            }
+        })
+    }
+
+    fn write_string(&mut self, w: &mut dyn fmt::Write, s: &str) -> fmt::Result {
+        let options = self.options;
+        self.paint(w, STRING_COLOR, |w| {
+            JsonPrinter::write_escaped(w, s, options)
+        })
+    }
+
+    /// `sort_keys` なら解析し直して書き出す。解析できなければそのまま書く
+    fn write_raw(&mut self, w: &mut dyn fmt::Write, raw: &RawValue) -> fmt::Result {
+        if self.options.sort_keys {
+            if let Ok(value) = raw.parse() {
+                return write_with(w, &value, self);
+            }
+        }
+        w.write_str(raw.get())
+    }
+
+    fn begin_array(&mut self, w: &mut dyn fmt::Write, array: &[Value]) -> fmt::Result {
+        let inline = self.should_inline('[', ']', array.iter().map(|v| (None, v)));
+        if !inline && self.inline_depth == 0 {
+            self.wrap = self.wrap_points(array).map(|breaks| (breaks, 0));
+        }
+        self.begin(w, '[', inline)
+    }
+
+    fn end_array(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.wrap = None;
+        self.end(w, ']')
+    }
+
+    fn begin_array_value(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        self.begin_item(w, first)
+    }
+
+    fn end_array_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+
+    fn begin_object(
+        &mut self,
+        w: &mut dyn fmt::Write,
+        object: &BTreeMap<String, Value>,
+    ) -> fmt::Result {
+        let inline = self.should_inline('{', '}', object.iter().map(|(k, v)| (Some(k), v)));
+        self.begin(w, '{', inline)
+    }
+
+    fn end_object(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        self.end(w, '}')
+    }
+
+    fn begin_object_key(&mut self, w: &mut dyn fmt::Write, first: bool) -> fmt::Result {
+        self.begin_item(w, first)
+    }
+
+    fn write_key(&mut self, w: &mut dyn fmt::Write, key: &str) -> fmt::Result {
+        let options = self.options;
+        self.paint(w, KEY_COLOR, |w| {
+            JsonPrinter::write_escaped(w, key, options)
+        })
+    }
+
+    fn begin_object_value(&mut self, w: &mut dyn fmt::Write) -> fmt::Result {
+        w.write_str(if self.options.pretty { ": " } else { ":" })
+    }
+
+    fn end_object_value(&mut self, _w: &mut dyn fmt::Write) -> fmt::Result {
+        self.has_value = true;
+        Ok(())
+    }
+}
+
+/// 書いた文字数を数え、`limit` を超えたらエラーにして書き出しを打ち切る
+struct WidthLimit {
+    width: usize,
+    limit: usize,
+}
+
+impl fmt::Write for WidthLimit {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.width += s.chars().count();
+        if self.width > self.limit {
+            Err(fmt::Error)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// 標準出力に色付けして書き出すべきかを返す。`NO_COLOR` (<https://no-color.org>) が空でなければ色を付けない
+pub fn color_enabled() -> bool {
+    let no_color = env::var_os("NO_COLOR").is_some_and(|v| !v.is_empty());
+    !no_color && io::stdout().is_terminal()
+}
+
+/// `value` を空白や末尾のカンマのない、最小の JSON の文字列にする
+pub fn to_string(value: &Value) -> String {
+    to_string_with(value, &PrinterOptions::default())
+}
+
+/// `value` を 2 文字の空白で字下げした JSON の文字列にする
+pub fn to_string_pretty(value: &Value) -> String {
+    to_string_with(value, &PrinterOptions::pretty())
+}
+
+/// `value` を `options` に従って JSON の文字列にする
+pub fn to_string_with(value: &Value, options: &PrinterOptions) -> String {
+    let mut buf = String::new();
+    // String への書き込みは失敗しない
+    let _ = fmt_value(&mut buf, value, options);
+    buf
+}
+
+/// `to_string_at` のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub struct PointerError {
+    pub msg: String,
+}
+
+impl fmt::Display for PointerError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for PointerError {}
+
+/// `value` の `pointer` が指す部分木だけを、空白のない JSON の文字列にする
+pub fn to_string_at<P: ToPointer + ?Sized>(
+    value: &Value,
+    pointer: &P,
+) -> Result<String, PointerError> {
+    to_string_at_with(value, pointer, &PrinterOptions::default())
+}
+
+/// `value` の `pointer` が指す部分木だけを、`options` に従って JSON の文字列にする。
+/// 不正な pointer や、指す値がなければエラー
+pub fn to_string_at_with<P: ToPointer + ?Sized>(
+    value: &Value,
+    pointer: &P,
+    options: &PrinterOptions,
+) -> Result<String, PointerError> {
+    let pointer = pointer
+        .to_pointer()
+        .map_err(|e| PointerError { msg: e.msg })?;
+    let subtree = pointer.resolve(value).ok_or_else(|| PointerError {
+        msg: format!("error: no value at {}", pointer),
+    })?;
+    Ok(to_string_with(subtree, options))
+}
+
+/// `value` を `options` に従って `fmt::Write` に書き出す。`Value` の `Display` もこれを使う
+///
+/// `String` や `fmt::Formatter` に入出力の仕組みなしで書き出せる
+pub fn fmt_value<W: fmt::Write>(w: &mut W, value: &Value, options: &PrinterOptions) -> fmt::Result {
+    write_item(w, value, &mut OptionsFormatter::new(options), 0)
+}
+
+/// 深さ `depth` の `value` を書き出す。`max_depth` か `max_items` があれば `write_truncated` を使う
+fn write_item(
+    w: &mut dyn fmt::Write,
+    value: &Value,
+    f: &mut OptionsFormatter,
+    depth: usize,
+) -> fmt::Result {
+    if f.options.max_depth.is_some() || f.options.max_items.is_some() {
+        write_truncated(w, value, f, depth)
+    } else {
+        write_with(w, value, f)
+    }
+}
+
+/// 省いた `n` 個の要素の代わりに書く印
+fn write_more(w: &mut dyn fmt::Write, n: usize) -> fmt::Result {
+    write!(w, "… {} more", n)
+}
+
+/// `write_with` と同じく書き出すが、`max_depth` より深いコンテナと `max_items` を超える要素を省く
+fn write_truncated(
+    w: &mut dyn fmt::Write,
+    value: &Value,
+    f: &mut OptionsFormatter,
+    depth: usize,
+) -> fmt::Result {
+    let max_items = f.options.max_items.unwrap_or(usize::MAX);
+    let too_deep = f.options.max_depth.is_some_and(|max| depth >= max);
+    match value {
+        Value::Array(array) if too_deep && !array.is_empty() => w.write_str("[…]"),
+        Value::Object(object) if too_deep && !object.is_empty() => w.write_str("{…}"),
+        Value::Array(array) => {
+            f.begin_array(w, array)?;
+            for (i, value) in array.iter().take(max_items).enumerate() {
+                f.begin_array_value(w, i == 0)?;
+                write_truncated(w, value, f, depth + 1)?;
+                f.end_array_value(w)?;
+            }
+            if array.len() > max_items {
+                f.begin_array_value(w, max_items == 0)?;
+                write_more(w, array.len() - max_items)?;
+                f.end_array_value(w)?;
+            }
+            f.end_array(w)
+        }
+        Value::Object(object) => {
+            f.begin_object(w, object)?;
+            for (i, (key, value)) in object.iter().take(max_items).enumerate() {
+                f.begin_object_key(w, i == 0)?;
+                f.write_key(w, key)?;
+                f.begin_object_value(w)?;
+                write_truncated(w, value, f, depth + 1)?;
+                f.end_object_value(w)?;
+            }
+            if object.len() > max_items {
+                f.begin_object_key(w, max_items == 0)?;
+                write_more(w, object.len() - max_items)?;
+                f.end_object_value(w)?;
+            }
+            f.end_object(w)
+        }
+        value => write_with(w, value, f),
+    }
+}
+
+/// `write!` や `format!` で設定を指定して書き出すための、`Value::display_with` の返す値
+pub struct JsonDisplay<'a> {
+    value: &'a Value,
+    options: &'a PrinterOptions,
+}
+
+impl fmt::Display for JsonDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt_value(f, self.value, self.options)
+    }
+}
+
+impl Value {
+    /// `options` に従って表示する値を返す。`format!("{}", value.display_with(&options))` のように使う
+    pub fn display_with<'a>(&'a self, options: &'a PrinterOptions) -> JsonDisplay<'a> {
+        JsonDisplay {
+            value: self,
+            options,
+        }
+    }
+}
+
+/// `value` を空白のない JSON として `w` に書き出す。中間の `String` は作らない
+///
+/// 細かく書き込むので、ファイルやソケットには `BufWriter` で包んで渡す
+pub fn write_value<W: io::Write>(w: &mut W, value: &Value) -> io::Result<()> {
+    write_value_with(w, value, &PrinterOptions::default())
+}
+
+/// `value` を 2 文字の空白で字下げした JSON として `w` に書き出す
+pub fn write_value_pretty<W: io::Write>(w: &mut W, value: &Value) -> io::Result<()> {
+    write_value_with(w, value, &PrinterOptions::pretty())
+}
+
+/// `value` を `options` に従って JSON として `w` に書き出す
+pub fn write_value_with<W: io::Write>(
+    w: &mut W,
+    value: &Value,
+    options: &PrinterOptions,
+) -> io::Result<()> {
+    let mut adapter = IoAdapter::new(w);
+    fmt_value(&mut adapter, value, options).map_err(|_| adapter.take_error())
+}
+
+/// `value` を `options` に従って書き出し、`path` のファイルを置き換える
+///
+/// 同じディレクトリの一時ファイルに書いてから名前を変えるので、途中で落ちても `path` が
+/// 書きかけになることはない。`sync` なら名前を変える前後にディスクへの書き込みを待つ
+pub fn write_to_file<P: AsRef<Path>>(
+    path: P,
+    value: &Value,
+    options: &PrinterOptions,
+    sync: bool,
+) -> io::Result<()> {
+    let path = path.as_ref();
+    let name = path
+        .file_name()
+        .ok_or_else(|| io::Error::other("error: the path has no file name"))?;
+    let dir = path.parent().unwrap_or(Path::new(""));
+    let (temp, file) = create_temp_file(dir, name)?;
+
+    let result = (|| {
+        let mut w = io::BufWriter::new(file);
+        write_value_with(&mut w, value, options)?;
+        let file = w.into_inner().map_err(|e| e.into_error())?;
+        if sync {
+            file.sync_all()?;
+        }
+        fs::rename(&temp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result?;
+    // 名前の変更をディレクトリに書き込む (Unix 以外ではディレクトリを開けない)
+    #[cfg(unix)]
+    if sync {
+        let dir = if dir.as_os_str().is_empty() {
+            Path::new(".")
+        } else {
+            dir
+        };
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
+}
+
+/// `write_to_file` の一時ファイルの名前に付ける連番
+static TEMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// `dir` に `.{name}.{pid}.{連番}.tmp` の一時ファイルを新しく作る
+///
+/// 既にあるファイルは開かないので、同じ `path` に同時に書き出すスレッドどうしでも
+/// 一時ファイルを共有しない。名前が使われていれば次の連番で作り直す
+fn create_temp_file(dir: &Path, name: &OsStr) -> io::Result<(PathBuf, fs::File)> {
+    loop {
+        let mut temp_name = OsString::from(".");
+        temp_name.push(name);
+        temp_name.push(format!(
+            ".{}.{}.tmp",
+            process::id(),
+            TEMP_COUNTER.fetch_add(1, atomic::Ordering::Relaxed)
+        ));
+        let temp = dir.join(temp_name);
+        match fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&temp)
+        {
+            Ok(file) => return Ok((temp, file)),
+            Err(e) if e.kind() == io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// `io::Write` を `fmt::Write` として使い、最初の入出力エラーを覚えておく
+pub(crate) struct IoAdapter<'w, W> {
+    inner: &'w mut W,
+    error: Option<io::Error>,
+}
+
+impl<'w, W> IoAdapter<'w, W> {
+    pub(crate) fn new(inner: &'w mut W) -> IoAdapter<'w, W> {
+        IoAdapter { inner, error: None }
+    }
+
+    /// 書き出しが失敗した原因の入出力エラーを返す。なければ書式のエラーとする
+    pub(crate) fn take_error(&mut self) -> io::Error {
+        self.error
+            .take()
+            .unwrap_or_else(|| io::Error::other("error: failed to format JSON"))
+    }
+}
+
+impl<W: io::Write> fmt::Write for IoAdapter<'_, W> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.inner.write_all(s.as_bytes()).map_err(|e| {
+            self.error = Some(e);
+            fmt::Error
+        })
+    }
+}
+
 impl JsonPrinter {
+    /// 2 文字の空白で字下げした JSON を標準出力に表示する。出力はそのまま解析し直せる
     pub fn print_json(value: &Value) {
+        println!("{}", to_string_pretty(value));
+    }
+
+    /// 端末なら色付けして、2 文字の空白で字下げした JSON を標準出力に表示する
+    pub fn print_json_colored(value: &Value) {
+        println!(
+            "{}",
+            to_string_with(value, &PrinterOptions::pretty_for_terminal())
+        );
+    }
+
+    /// `Infinity` や `NaN` をそのまま出力する JSON5 形式で表示する
+    pub fn print_json5(value: &Value) {
+        let options = PrinterOptions {
+            json5: true,
+            ..PrinterOptions::pretty()
+        };
+        println!("{}", to_string_with(value, &options));
+    }
+
+    /// 以前の形式 (すべての要素の後にカンマを付ける) でデバッグ用に表示する。出力は JSON として正しくない
+    pub fn print_debug_dump(value: &Value) {
         Self::in_print(value, 0, true);
     }
 
@@ -11,13 +750,15 @@ impl JsonPrinter {
         match value {
             Value::Null => print!("null"),
             Value::Bool(b) => print!("{}", b),
+            Value::Number(n) if n.is_f64() => print!("{}", Self::format_number(n.as_f64(), false)),
             Value::Number(n) => print!("{}", n),
-            Value::String(s) => print!("\"{}\"", s),
+            Value::String(s) => print!("{}", Self::escape(s)),
+            Value::Raw(raw) => print!("{}", raw.get()),
             Value::Object(object) => {
                 println!("{{");
                 object.iter().for_each(|(key, value)| {
                     print!("{:indent$}", "", indent = (depth + 1) * 2);
-                    print!("\"{}\": ", key);
+                    print!("{}: ", Self::escape(key));
                     Self::in_print(value, depth + 1, false);
                     println!(",");
                 });
@@ -39,11 +780,94 @@ impl JsonPrinter {
             println!();
         }
     }
+
+    /// 整数以外の数値を `options` の桁数と表記に従って文字列にする
+    fn format_float(n: f64, options: &PrinterOptions) -> String {
+        if !n.is_finite() {
+            return Self::format_number(n, options.json5);
+        }
+        let s = format_f64_with(n, options.float_precision, &options.decimal_exponents);
+        if !options.trim_trailing_zeros {
+            return s;
+        }
+        let (mantissa, exponent) = match s.find('e') {
+            Some(i) => s.split_at(i),
+            None => (s.as_str(), ""),
+        };
+        let mantissa = if mantissa.contains('.') {
+            mantissa.trim_end_matches('0').trim_end_matches('.')
+        } else {
+            mantissa
+        };
+        format!("{}{}", mantissa, exponent)
+    }
+
+    /// 数値を文字列にする。JSON で表せない値は、JSON5 形式でなければ null にする
+    fn format_number(n: f64, json5: bool) -> String {
+        match (n, json5) {
+            (n, _) if n.is_finite() => format_f64(n),
+            (_, false) => "null".to_string(),
+            (n, true) if n.is_nan() => "NaN".to_string(),
+            (n, true) if n > 0.0 => "Infinity".to_string(),
+            (_, true) => "-Infinity".to_string(),
+        }
+    }
+
+    /// 文字列を `"` で囲み、JSON のエスケープを施す
+    fn escape(s: &str) -> String {
+        let mut buf = String::with_capacity(s.len() + 2);
+        // String への書き込みは失敗しない
+        let _ = Self::write_escaped(&mut buf, s, &PrinterOptions::default());
+        buf
+    }
+
+    /// 文字列を `"` で囲み、JSON のエスケープを施して `w` に書き出す
+    ///
+    /// `"` と `\` と制御文字 (U+0000 - U+001F) をエスケープする。短い表記 (`\n` など) のない
+    /// 制御文字は `\u00XX` にする。エスケープの要らない部分はまとめて書き出す
+    pub(crate) fn write_escaped<W: fmt::Write + ?Sized>(
+        w: &mut W,
+        s: &str,
+        options: &PrinterOptions,
+    ) -> fmt::Result {
+        w.write_char('"')?;
+        let mut start = 0;
+        for (i, c) in s.char_indices() {
+            let escaped = match c {
+                '"' => "\\\"",
+                '\\' => "\\\\",
+                '/' if options.escape_slashes => "\\/",
+                '\n' => "\\n",
+                '\r' => "\\r",
+                '\t' => "\\t",
+                '\u{8}' => "\\b",
+                '\u{c}' => "\\f",
+                c if (c as u32) < 0x20 => "",
+                c if options.escape_non_ascii && !c.is_ascii() => "",
+                '<' | '>' | '&' | '\u{2028}' | '\u{2029}' if options.escape_html => "",
+                _ => continue,
+            };
+            w.write_str(&s[start..i])?;
+            if escaped.is_empty() {
+                for unit in c.encode_utf16(&mut [0; 2]) {
+                    write!(w, "\\u{:04x}", unit)?;
+                }
+            } else {
+                w.write_str(escaped)?;
+            }
+            start = i + c.len_utf8();
+        }
+        w.write_str(&s[start..])?;
+        w.write_char('"')
+    }
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{lexer::Lexer, parser::Parser};
+    use crate::{
+        lexer::{Lexer, LexerOptions},
+        parser::{Parser, ParserOptions},
+    };
 
     use super::*;
 
@@ -115,4 +939,490 @@ mod tests {
             .unwrap();
         JsonPrinter::print_json(&value);
     }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(JsonPrinter::escape("abc"), r#""abc""#);
+        assert_eq!(
+            JsonPrinter::escape("\"quoted\"\\\n\t\u{1}"),
+            r#""\"quoted\"\\\n\t\u0001""#
+        );
+
+        let json = r#""line\nbreak \"q\" \\ \/""#;
+        let value = Parser::new(Lexer::new(json).tokenize().unwrap())
+            .parse()
+            .unwrap();
+        match value {
+            Value::String(s) => {
+                assert_eq!(JsonPrinter::escape(&s), r#""line\nbreak \"q\" \\ /""#)
+            }
+            _ => panic!("a string value is expected"),
+        }
+    }
+
+    #[test]
+    fn test_format_number() {
+        assert_eq!(JsonPrinter::format_number(2.5, false), "2.5");
+        assert_eq!(JsonPrinter::format_number(f64::INFINITY, false), "null");
+        assert_eq!(JsonPrinter::format_number(f64::NAN, false), "null");
+        assert_eq!(JsonPrinter::format_number(f64::INFINITY, true), "Infinity");
+        assert_eq!(
+            JsonPrinter::format_number(f64::NEG_INFINITY, true),
+            "-Infinity"
+        );
+        assert_eq!(JsonPrinter::format_number(f64::NAN, true), "NaN");
+    }
+
+    #[test]
+    fn test_float_format() {
+        let value: Value = "[3.14159, 2.5, 1.0e25, 0.00001234, 7]".parse().unwrap();
+        let fixed = PrinterOptions {
+            float_precision: Some(3),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &fixed),
+            "[3.142,2.500,1.000e+25,0.000,7]"
+        );
+        let trimmed = PrinterOptions {
+            trim_trailing_zeros: true,
+            ..fixed.clone()
+        };
+        assert_eq!(to_string_with(&value, &trimmed), "[3.142,2.5,1e+25,0,7]");
+        let scientific = PrinterOptions {
+            decimal_exponents: -3..3,
+            ..trimmed
+        };
+        assert_eq!(
+            to_string_with(&value, &scientific),
+            "[3.142,2.5,1e+25,1.234e-5,7]"
+        );
+        assert_eq!(to_string(&value), "[3.14159,2.5,1e+25,0.00001234,7]");
+    }
+
+    #[test]
+    fn test_sort_keys() {
+        let raw = RawValue::new(r#"{"b": 1, "a": {"d": 2, "c": 3}}"#.to_string());
+        let value = Value::object()
+            .field("z", Value::Raw(raw))
+            .field("y", Value::Null)
+            .build();
+        assert_eq!(
+            to_string(&value),
+            r#"{"y":null,"z":{"b": 1, "a": {"d": 2, "c": 3}}}"#
+        );
+        let sorted = PrinterOptions {
+            sort_keys: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &sorted),
+            r#"{"y":null,"z":{"a":{"c":3,"d":2},"b":1}}"#
+        );
+    }
+
+    #[test]
+    fn test_color() {
+        let value: Value = r#"{"a": [1, "x", true, null], "b": {}}"#.parse().unwrap();
+        let options = PrinterOptions {
+            color: true,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\x1b[34;1m\"a\"\x1b[0m:[\x1b[36m1\x1b[0m,\x1b[32m\"x\"\x1b[0m,\
+             \x1b[33mtrue\x1b[0m,\x1b[90mnull\x1b[0m],\x1b[34;1m\"b\"\x1b[0m:{}}"
+        );
+    }
+
+    #[test]
+    fn test_compact_width() {
+        let value: Value =
+            r#"{"point": {"x": 1, "y": 2}, "tags": ["a", "b"], "matrix": [[1, 2], [3, 4]],
+                "long": ["aaaaaaaaaa", "bbbbbbbbbb", "cccccccccc"], "empty": []}"#
+                .parse()
+                .unwrap();
+        let options = PrinterOptions {
+            compact_width: Some(20),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            r#"{
+  "empty": [],
+  "long": [
+    "aaaaaaaaaa",
+    "bbbbbbbbbb",
+    "cccccccccc"
+  ],
+  "matrix": [[1, 2], [3, 4]],
+  "point": {"x": 1, "y": 2},
+  "tags": ["a", "b"]
+}"#
+        );
+        let compact = PrinterOptions {
+            compact_width: Some(20),
+            ..Default::default()
+        };
+        assert_eq!(to_string_with(&value, &compact), to_string(&value));
+    }
+
+    #[test]
+    fn test_max_width() {
+        let value: Value =
+            r#"{"ids": [100, 200, 300, 400, 500, 600, 700], "names": ["alpha", "beta"],
+                               "rows": [[1], {"a": 2}]}"#
+                .parse()
+                .unwrap();
+        let options = PrinterOptions {
+            max_width: Some(20),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            r#"{
+  "ids": [
+    100, 200, 300,
+    400, 500, 600,
+    700
+  ],
+  "names": [
+    "alpha", "beta"
+  ],
+  "rows": [
+    [
+      1
+    ],
+    {
+      "a": 2
+    }
+  ]
+}"#
+        );
+        let both = PrinterOptions {
+            compact_width: Some(20),
+            ..options
+        };
+        assert!(to_string_with(&value, &both).contains(r#""names": ["alpha", "beta"],"#));
+    }
+
+    #[test]
+    fn test_indent_and_line_ending() {
+        let value: Value = r#"{"a": [1, {}]}"#.parse().unwrap();
+        assert_eq!(
+            to_string_with(&value, &PrinterOptions::pretty_with_tabs()),
+            "{\n\t\"a\": [\n\t\t1,\n\t\t{}\n\t]\n}"
+        );
+        let options = PrinterOptions {
+            indent_width: 4,
+            line_ending: LineEnding::CrLf,
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\r\n    \"a\": [\r\n        1,\r\n        {}\r\n    ]\r\n}"
+        );
+    }
+
+    #[test]
+    fn test_escape_slashes() {
+        let value = Value::from("</script>");
+        assert_eq!(to_string(&value), r#""</script>""#);
+        let options = PrinterOptions {
+            escape_slashes: true,
+            ..Default::default()
+        };
+        let json = to_string_with(&value, &options);
+        assert_eq!(json, r#""<\/script>""#);
+        assert_eq!(json.parse::<Value>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_escape_html() {
+        let value = Value::from("<a href='x'>&</a>\u{2028}\u{2029}é");
+        let options = PrinterOptions {
+            escape_html: true,
+            ..Default::default()
+        };
+        let json = to_string_with(&value, &options);
+        assert_eq!(
+            json,
+            r#""\u003ca href='x'\u003e\u0026\u003c/a\u003e\u2028\u2029é""#
+        );
+        assert_eq!(json.parse::<Value>().unwrap(), value);
+    }
+
+    #[test]
+    fn test_write_to_file() {
+        let dir = env::temp_dir().join(format!("json-parser-test-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("config.json");
+        let value: Value = r#"{"a": [1, 2]}"#.parse().unwrap();
+        write_to_file(&path, &value, &PrinterOptions::pretty(), true).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), to_string_pretty(&value));
+        write_to_file(&path, &Value::Null, &PrinterOptions::default(), false).unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "null");
+        // 一時ファイルは残らない
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        assert!(write_to_file(
+            dir.join("missing/x.json"),
+            &value,
+            &Default::default(),
+            false
+        )
+        .is_err());
+
+        // 同じ path に同時に書き出しても、どれか 1 つの完全な内容になる
+        let values: Vec<Value> = (0..8)
+            .map(|i| Value::Array(vec![Value::from(i); 10_000]))
+            .collect();
+        std::thread::scope(|s| {
+            for value in &values {
+                let path = &path;
+                s.spawn(move || {
+                    for _ in 0..5 {
+                        write_to_file(path, value, &PrinterOptions::default(), false).unwrap();
+                    }
+                });
+            }
+        });
+        let written: Value = fs::read_to_string(&path).unwrap().parse().unwrap();
+        assert!(values.contains(&written));
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 1);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_truncate() {
+        let value: Value = r#"{"a": [1, 2, 3, 4], "b": {"c": {"d": 1}, "e": []}, "f": "x"}"#
+            .parse()
+            .unwrap();
+        let options = PrinterOptions {
+            max_depth: Some(2),
+            max_items: Some(2),
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            r#"{"a":[1,2,… 2 more],"b":{"c":{…},"e":[]},… 1 more}"#
+        );
+        let options = PrinterOptions {
+            max_depth: Some(0),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(to_string_with(&value, &options), "{…}");
+        let options = PrinterOptions {
+            max_items: Some(0),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(to_string_with(&value, &options), "{\n  … 3 more\n}");
+        assert_eq!(to_string_with(&Value::from(1), &options), "1");
+
+        // 省いた後の幅で 1 行にまとめるか、折り返すかを決める
+        let value: Value =
+            r#"{"a": [1, 2, 3, 4, 5, 6, 7, 8, 9, 10], "b": {"c": {"d": [1, 2, 3, 4, 5]}}}"#
+                .parse()
+                .unwrap();
+        let options = PrinterOptions {
+            compact_width: Some(20),
+            max_depth: Some(2),
+            max_items: Some(2),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\n  \"a\": [1, 2, … 8 more],\n  \"b\": {\"c\": {…}}\n}"
+        );
+        let options = PrinterOptions {
+            max_width: Some(16),
+            max_items: Some(3),
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value["a"], &options),
+            "[\n  1, 2, 3,\n  … 7 more\n]"
+        );
+    }
+
+    #[test]
+    fn test_to_string_at() {
+        let value: Value = r#"{"data": {"items": [0, 1, 2, {"id": 3}]}}"#.parse().unwrap();
+        assert_eq!(
+            to_string_at(&value, "/data/items/3"),
+            Ok(r#"{"id":3}"#.to_string())
+        );
+        assert_eq!(to_string_at(&value, "").unwrap(), to_string(&value));
+        assert_eq!(
+            to_string_at_with(&value, "/data/items/3", &PrinterOptions::pretty()).unwrap(),
+            "{\n  \"id\": 3\n}"
+        );
+        assert_eq!(
+            to_string_at(&value, "/data/items/9").unwrap_err().msg,
+            "error: no value at /data/items/9"
+        );
+        assert!(to_string_at(&value, "data").is_err());
+    }
+
+    #[test]
+    fn test_keep_number_text() {
+        let parse = |json: &str, lexer_options: LexerOptions| {
+            let tokens = Lexer::with_options(json, lexer_options).tokenize().unwrap();
+            let options = ParserOptions {
+                keep_number_text: true,
+                ..Default::default()
+            };
+            Parser::with_options(tokens, options).parse().unwrap()
+        };
+        let value = parse(
+            "[1.50, 1e3, -0.0, 2.5E-3, 7, 1.0, -0]",
+            LexerOptions::default(),
+        );
+        assert_eq!(to_string(&value), "[1.50,1e3,-0.0,2.5E-3,7,1.0,-0]");
+        assert_eq!(value[6], Value::from(0));
+        assert_eq!(value[4].as_number().unwrap().text(), None);
+        assert_eq!(value[0], Value::from(1.5));
+        assert_eq!(value[0].as_number().unwrap().text(), Some("1.50"));
+        let normalized = PrinterOptions {
+            keep_number_text: false,
+            ..Default::default()
+        };
+        assert_eq!(
+            to_string_with(&value, &normalized),
+            "[1.5,1000,-0,0.0025,7,1,0]"
+        );
+        // JSON の数値として書けない表記は使わない
+        let json5 = LexerOptions {
+            allow_extended_numbers: true,
+            ..Default::default()
+        };
+        let value = parse("[.5, +1.5, 0x10, +1]", json5);
+        assert_eq!(value[0].as_number().unwrap().text(), None);
+        assert_eq!(to_string(&value), "[0.5,1.5,16,1]");
+        assert_eq!(value[3], Value::from(1));
+        let plain: Value = "[1.50]".parse().unwrap();
+        assert_eq!(to_string(&plain), "[1.5]");
+    }
+
+    #[test]
+    fn test_to_string() {
+        let json = r#"{"a": [1, 2.5, {"b": null}], "c\n\"d\"": "tab\t", "e": [], "f": {}}"#;
+        let value: Value = json.parse().unwrap();
+        let compact = to_string(&value);
+        assert_eq!(
+            compact,
+            r#"{"a":[1,2.5,{"b":null}],"c\n\"d\"":"tab\t","e":[],"f":{}}"#
+        );
+        assert_eq!(compact.parse::<Value>().unwrap(), value);
+        assert_eq!(to_string(&Value::from(f64::NAN)), "null");
+    }
+
+    #[test]
+    fn test_to_string_pretty() {
+        let value: Value = r#"{"a": [1, {}], "b": "x"}"#.parse().unwrap();
+        assert_eq!(
+            to_string_pretty(&value),
+            "{\n  \"a\": [\n    1,\n    {}\n  ],\n  \"b\": \"x\"\n}"
+        );
+        let options = PrinterOptions {
+            indent_char: '\t',
+            indent_width: 1,
+            ..PrinterOptions::pretty()
+        };
+        assert_eq!(
+            to_string_with(&value, &options),
+            "{\n\t\"a\": [\n\t\t1,\n\t\t{}\n\t],\n\t\"b\": \"x\"\n}"
+        );
+        assert_eq!(to_string_pretty(&value).parse::<Value>().unwrap(), value);
+        assert_eq!(to_string_pretty(&Value::from(1)), "1");
+    }
+
+    #[test]
+    fn test_write_value() {
+        let value: Value = r#"{"a": [1, "x"]}"#.parse().unwrap();
+        let mut buf = vec![];
+        write_value(&mut buf, &value).unwrap();
+        assert_eq!(buf, br#"{"a":[1,"x"]}"#);
+
+        let mut buf = vec![];
+        write_value_pretty(&mut buf, &value).unwrap();
+        assert_eq!(String::from_utf8(buf).unwrap(), to_string_pretty(&value));
+
+        // 書き込み先のエラーはそのまま返す
+        struct Full;
+        impl io::Write for Full {
+            fn write(&mut self, _: &[u8]) -> io::Result<usize> {
+                Err(io::Error::new(io::ErrorKind::WriteZero, "full"))
+            }
+            fn flush(&mut self) -> io::Result<()> {
+                Ok(())
+            }
+        }
+        let e = write_value(&mut Full, &value).unwrap_err();
+        assert_eq!(e.kind(), io::ErrorKind::WriteZero);
+    }
+
+    #[test]
+    fn test_fmt_value() {
+        use std::fmt::Write;
+
+        let value: Value = r#"{"a": [1]}"#.parse().unwrap();
+        let mut buf = String::from("value = ");
+        fmt_value(&mut buf, &value, &PrinterOptions::default()).unwrap();
+        assert_eq!(buf, r#"value = {"a":[1]}"#);
+
+        let options = PrinterOptions {
+            indent_width: 1,
+            ..PrinterOptions::pretty()
+        };
+        let mut buf = String::new();
+        write!(buf, "{}", value.display_with(&options)).unwrap();
+        assert_eq!(buf, "{\n \"a\": [\n  1\n ]\n}");
+        assert_eq!(format!("{}", value), to_string(&value));
+        assert_eq!(format!("{:#}", value), to_string_pretty(&value));
+    }
+
+    #[test]
+    fn test_escape_round_trip() {
+        // すべての制御文字と、エスケープの要る文字を含む文字列
+        let s: String = (0u8..0x20)
+            .map(char::from)
+            .chain("\"\\/ é 日本 😀 \u{7f}".chars())
+            .collect();
+        let value = Value::from(s.as_str());
+        let json = to_string(&value);
+        assert!(json[1..json.len() - 1].chars().all(|c| c >= ' '));
+        assert!(json.starts_with(r#""\u0000\u0001"#));
+        assert!(json.contains(r#"\b\t\n\u000b\f\r"#));
+        assert_eq!(json.parse::<Value>().unwrap(), value);
+
+        let object = Value::object().field("k\"\n", "v").build();
+        assert_eq!(to_string(&object), r#"{"k\"\n":"v"}"#);
+    }
+
+    #[test]
+    fn test_escape_non_ascii() {
+        let value = Value::object().field("é", "日本 😀 a\n").build();
+        let options = PrinterOptions {
+            escape_non_ascii: true,
+            ..Default::default()
+        };
+        let json = to_string_with(&value, &options);
+        assert_eq!(json, r#"{"\u00e9":"\u65e5\u672c \ud83d\ude00 a\n"}"#);
+        assert!(json.is_ascii());
+        assert_eq!(json.parse::<Value>().unwrap(), value);
+        assert_eq!(to_string(&value), "{\"é\":\"日本 😀 a\\n\"}");
+    }
+
+    #[test]
+    fn test_json5() {
+        let value = Value::from(vec![f64::INFINITY, f64::NAN, 1.5]);
+        let options = PrinterOptions {
+            json5: true,
+            ..Default::default()
+        };
+        assert_eq!(to_string_with(&value, &options), "[Infinity,NaN,1.5]");
+        assert_eq!(to_string(&value), "[null,null,1.5]");
+        JsonPrinter::print_json5(&value);
+        JsonPrinter::print_debug_dump(&value);
+    }
 }
@@ -0,0 +1,547 @@
+//! jq に似た小さな式言語
+//!
+//! `.items[] | select(.active) | .name` のような式で値を取り出す。対応する構文は次のとおり
+//!
+//! - `.`、`.key`、`."key"`、`.[0]`、`.[-1]`、`.["key"]`、`.[]` (配列の要素やオブジェクトの値を順に)
+//! - `a | b` (a の各出力を b に渡す)、`( ... )`
+//! - `select(cond)`、比較 `==` `!=` `<` `<=` `>` `>=`
+//! - `length`、`keys`、`not`、リテラル (数値、文字列、`true`、`false`、`null`)
+
+use std::{borrow::Cow, cmp::Ordering, fmt};
+
+use crate::{sort::compare, value::Value};
+
+/// 式の解析や評価のエラー
+#[derive(Debug, Clone, PartialEq)]
+pub struct QueryError {
+    pub msg: String,
+}
+
+impl QueryError {
+    fn new(msg: &str) -> QueryError {
+        QueryError {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for QueryError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for QueryError {}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Op {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Expr {
+    Identity,
+    Literal(Value),
+    Field(Box<Expr>, String),
+    Index(Box<Expr>, i64),
+    Iterate(Box<Expr>),
+    Pipe(Box<Expr>, Box<Expr>),
+    Select(Box<Expr>),
+    Compare(Box<Expr>, Op, Box<Expr>),
+    Length,
+    Keys,
+    Not,
+}
+
+/// 解析済みの式
+#[derive(Debug, Clone, PartialEq)]
+pub struct Query {
+    expr: Expr,
+}
+
+impl Query {
+    pub fn parse(src: &str) -> Result<Query, QueryError> {
+        let mut parser = QueryParser { src, pos: 0 };
+        let expr = parser.parse_pipe()?;
+        parser.skip_whitespace();
+        if parser.pos < src.len() {
+            return Err(parser.error("an unexpected input"));
+        }
+        Ok(Query { expr })
+    }
+
+    /// `input` に式を適用し、すべての出力を返す
+    pub fn run(&self, input: &Value) -> Result<Vec<Value>, QueryError> {
+        Ok(eval(&self.expr, input)?
+            .into_iter()
+            .map(Cow::into_owned)
+            .collect())
+    }
+}
+
+impl Value {
+    /// jq に似た式 `expr` を適用し、すべての出力を返す
+    pub fn query(&self, expr: &str) -> Result<Vec<Value>, QueryError> {
+        Query::parse(expr)?.run(self)
+    }
+}
+
+struct QueryParser<'s> {
+    src: &'s str,
+    pos: usize,
+}
+
+impl QueryParser<'_> {
+    fn error(&self, msg: &str) -> QueryError {
+        QueryError::new(&format!(
+            "error: {} at {} in \"{}\"",
+            msg, self.pos, self.src
+        ))
+    }
+
+    fn peek(&self) -> Option<char> {
+        self.src[self.pos..].chars().next()
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(c) = self.peek().filter(|c| c.is_whitespace()) {
+            self.pos += c.len_utf8();
+        }
+    }
+
+    /// 空白を読み飛ばし、次が `s` なら読んで true を返す
+    fn eat(&mut self, s: &str) -> bool {
+        self.skip_whitespace();
+        if self.src[self.pos..].starts_with(s) {
+            self.pos += s.len();
+            true
+        } else {
+            false
+        }
+    }
+
+    fn expect(&mut self, s: &str) -> Result<(), QueryError> {
+        if self.eat(s) {
+            Ok(())
+        } else {
+            Err(self.error(&format!("\"{}\" is expected", s)))
+        }
+    }
+
+    fn parse_pipe(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_compare()?;
+        while self.eat("|") {
+            expr = Expr::Pipe(Box::new(expr), Box::new(self.parse_compare()?));
+        }
+        Ok(expr)
+    }
+
+    fn parse_compare(&mut self) -> Result<Expr, QueryError> {
+        let left = self.parse_postfix()?;
+        let ops = [
+            ("==", Op::Eq),
+            ("!=", Op::Ne),
+            ("<=", Op::Le),
+            (">=", Op::Ge),
+            ("<", Op::Lt),
+            (">", Op::Gt),
+        ];
+        for (s, op) in ops {
+            if self.eat(s) {
+                let right = self.parse_postfix()?;
+                return Ok(Expr::Compare(Box::new(left), op, Box::new(right)));
+            }
+        }
+        Ok(left)
+    }
+
+    /// 項に続く `.key` や `[0]`、`[]` を読む
+    fn parse_postfix(&mut self) -> Result<Expr, QueryError> {
+        let mut expr = self.parse_term()?;
+        loop {
+            if self.src[self.pos..].starts_with('.')
+                && self.src[self.pos + 1..].starts_with(|c: char| is_ident_start(c) || c == '"')
+            {
+                self.pos += 1;
+                expr = Expr::Field(Box::new(expr), self.parse_key()?);
+            } else if self.src[self.pos..].starts_with('[') {
+                self.pos += 1;
+                expr = self.parse_bracket(expr)?;
+            } else {
+                return Ok(expr);
+            }
+        }
+    }
+
+    /// `[` の後の `]`、`0]`、`"key"]` を読む
+    fn parse_bracket(&mut self, base: Expr) -> Result<Expr, QueryError> {
+        let base = Box::new(base);
+        if self.eat("]") {
+            return Ok(Expr::Iterate(base));
+        }
+        self.skip_whitespace();
+        let expr = if self.peek() == Some('"') {
+            Expr::Field(base, self.parse_string()?)
+        } else {
+            let start = self.pos;
+            if self.peek() == Some('-') {
+                self.pos += 1;
+            }
+            while self.peek().is_some_and(|c| c.is_ascii_digit()) {
+                self.pos += 1;
+            }
+            let index = self.src[start..self.pos]
+                .parse()
+                .map_err(|_| self.error("an array index is expected"))?;
+            Expr::Index(base, index)
+        };
+        self.expect("]")?;
+        Ok(expr)
+    }
+
+    fn parse_term(&mut self) -> Result<Expr, QueryError> {
+        self.skip_whitespace();
+        match self.peek() {
+            Some('.') => {
+                self.pos += 1;
+                match self.peek() {
+                    Some(c) if is_ident_start(c) || c == '"' => {
+                        Ok(Expr::Field(Box::new(Expr::Identity), self.parse_key()?))
+                    }
+                    _ => Ok(Expr::Identity),
+                }
+            }
+            Some('(') => {
+                self.pos += 1;
+                let expr = self.parse_pipe()?;
+                self.expect(")")?;
+                Ok(expr)
+            }
+            Some('"') => Ok(Expr::Literal(Value::String(self.parse_string()?))),
+            Some(c) if c.is_ascii_digit() || c == '-' => {
+                let start = self.pos;
+                while self
+                    .peek()
+                    .is_some_and(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '+' | '.'))
+                {
+                    self.pos += 1;
+                }
+                let number = &self.src[start..self.pos];
+                let value = number
+                    .parse::<Value>()
+                    .ok()
+                    .filter(Value::is_number)
+                    .ok_or_else(|| self.error(&format!("an invalid number \"{}\"", number)))?;
+                Ok(Expr::Literal(value))
+            }
+            Some(c) if is_ident_start(c) => {
+                let start = self.pos;
+                let ident = self.parse_ident().to_string();
+                match ident.as_str() {
+                    "true" => Ok(Expr::Literal(Value::Bool(true))),
+                    "false" => Ok(Expr::Literal(Value::Bool(false))),
+                    "null" => Ok(Expr::Literal(Value::Null)),
+                    "length" => Ok(Expr::Length),
+                    "keys" => Ok(Expr::Keys),
+                    "not" => Ok(Expr::Not),
+                    "select" => {
+                        self.expect("(")?;
+                        let cond = self.parse_pipe()?;
+                        self.expect(")")?;
+                        Ok(Expr::Select(Box::new(cond)))
+                    }
+                    _ => {
+                        self.pos = start;
+                        Err(self.error(&format!("an unknown function \"{}\"", ident)))
+                    }
+                }
+            }
+            _ => Err(self.error("an expression is expected")),
+        }
+    }
+
+    fn parse_ident(&mut self) -> &str {
+        let start = self.pos;
+        while self.peek().is_some_and(|c| c.is_alphanumeric() || c == '_') {
+            self.pos += self.peek().map_or(0, char::len_utf8);
+        }
+        &self.src[start..self.pos]
+    }
+
+    /// `.` の後のキー (識別子または文字列)
+    fn parse_key(&mut self) -> Result<String, QueryError> {
+        if self.peek() == Some('"') {
+            self.parse_string()
+        } else {
+            Ok(self.parse_ident().to_string())
+        }
+    }
+
+    /// JSON の文字列リテラルを読む
+    fn parse_string(&mut self) -> Result<String, QueryError> {
+        let start = self.pos;
+        self.pos += 1;
+        let mut escaped = false;
+        for (i, c) in self.src[self.pos..].char_indices() {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => {
+                    self.pos += i + 1;
+                    return match self.src[start..self.pos].parse::<Value>() {
+                        Ok(Value::String(s)) => Ok(s),
+                        _ => Err(self.error("an invalid string")),
+                    };
+                }
+                _ => (),
+            }
+        }
+        Err(self.error("an unterminated string"))
+    }
+}
+
+fn is_ident_start(c: char) -> bool {
+    c.is_alphabetic() || c == '_'
+}
+
+fn type_name(value: &Value) -> &'static str {
+    match value {
+        Value::String(_) => "string",
+        Value::Number(_) => "number",
+        Value::Bool(_) => "boolean",
+        Value::Null => "null",
+        Value::Array(_) => "array",
+        Value::Object(_) => "object",
+        Value::Raw(_) => "raw value",
+    }
+}
+
+/// false と null 以外は真
+fn truthy(value: &Value) -> bool {
+    !matches!(value, Value::Null | Value::Bool(false))
+}
+
+/// `expr` を `input` に適用する
+///
+/// 出力のうち `input` や式の中の値の一部はそのまま借用し、複製するのは `Query::run` で返すときだけにする
+fn eval<'v>(expr: &'v Expr, input: &'v Value) -> Result<Vec<Cow<'v, Value>>, QueryError> {
+    // base の各出力に f を適用する
+    let each =
+        |base: &'v Expr, f: &dyn Fn(Cow<'v, Value>) -> Result<Vec<Cow<'v, Value>>, QueryError>| {
+            let mut outputs = vec![];
+            for value in eval(base, input)? {
+                outputs.extend(f(value)?);
+            }
+            Ok(outputs)
+        };
+    let null = || Cow::Owned(Value::Null);
+    match expr {
+        Expr::Identity => Ok(vec![Cow::Borrowed(input)]),
+        Expr::Literal(value) => Ok(vec![Cow::Borrowed(value)]),
+        Expr::Field(base, key) => each(base, &|value| match value {
+            Cow::Borrowed(Value::Object(object)) => {
+                Ok(vec![object.get(key).map_or_else(null, Cow::Borrowed)])
+            }
+            Cow::Owned(Value::Object(mut object)) => {
+                Ok(vec![object.remove(key).map_or_else(null, Cow::Owned)])
+            }
+            value if value.is_null() => Ok(vec![null()]),
+            value => Err(QueryError::new(&format!(
+                "error: cannot index {} with \"{}\"",
+                type_name(&value),
+                key
+            ))),
+        }),
+        Expr::Index(base, index) => {
+            each(base, &|value| {
+                let position = |len: usize| {
+                    let len = len as i64;
+                    let i = if *index < 0 { len + index } else { *index };
+                    (0..len).contains(&i).then_some(i as usize)
+                };
+                match value {
+                    Cow::Borrowed(Value::Array(array)) => Ok(vec![
+                        position(array.len()).map_or_else(null, |i| Cow::Borrowed(&array[i]))
+                    ]),
+                    Cow::Owned(Value::Array(mut array)) => Ok(vec![position(array.len())
+                        .map_or_else(null, |i| Cow::Owned(array.swap_remove(i)))]),
+                    value if value.is_null() => Ok(vec![null()]),
+                    value => Err(QueryError::new(&format!(
+                        "error: cannot index {} with {}",
+                        type_name(&value),
+                        index
+                    ))),
+                }
+            })
+        }
+        Expr::Iterate(base) => each(base, &|value| match value {
+            Cow::Borrowed(Value::Array(array)) => Ok(array.iter().map(Cow::Borrowed).collect()),
+            Cow::Borrowed(Value::Object(object)) => {
+                Ok(object.values().map(Cow::Borrowed).collect())
+            }
+            Cow::Owned(Value::Array(array)) => Ok(array.into_iter().map(Cow::Owned).collect()),
+            Cow::Owned(Value::Object(object)) => Ok(object.into_values().map(Cow::Owned).collect()),
+            value => Err(QueryError::new(&format!(
+                "error: cannot iterate over {}",
+                type_name(&value)
+            ))),
+        }),
+        Expr::Pipe(first, second) => each(first, &|value| match value {
+            Cow::Borrowed(value) => eval(second, value),
+            // 途中で作った値の一部は、その値より長く借用できないので取り出す
+            Cow::Owned(value) => Ok(eval(second, &value)?
+                .into_iter()
+                .map(|v| Cow::Owned(v.into_owned()))
+                .collect()),
+        }),
+        Expr::Select(cond) => {
+            let outputs = eval(cond, input)?;
+            Ok(outputs
+                .iter()
+                .filter(|c| truthy(c))
+                .map(|_| Cow::Borrowed(input))
+                .collect())
+        }
+        Expr::Compare(left, op, right) => {
+            let (left, right) = (eval(left, input)?, eval(right, input)?);
+            let mut outputs = vec![];
+            for r in &right {
+                for l in &left {
+                    let ordering = compare(l, r);
+                    let result = match op {
+                        Op::Eq => l == r,
+                        Op::Ne => l != r,
+                        Op::Lt => ordering == Ordering::Less,
+                        Op::Le => ordering != Ordering::Greater,
+                        Op::Gt => ordering == Ordering::Greater,
+                        Op::Ge => ordering != Ordering::Less,
+                    };
+                    outputs.push(Cow::Owned(Value::Bool(result)));
+                }
+            }
+            Ok(outputs)
+        }
+        Expr::Length => {
+            let length = match input {
+                Value::String(s) => Value::from(s.chars().count() as u64),
+                Value::Array(array) => Value::from(array.len() as u64),
+                Value::Object(object) => Value::from(object.len() as u64),
+                Value::Null => Value::from(0),
+                Value::Number(n) => Value::from(n.as_f64().abs()),
+                value => {
+                    return Err(QueryError::new(&format!(
+                        "error: {} has no length",
+                        type_name(value)
+                    )))
+                }
+            };
+            Ok(vec![Cow::Owned(length)])
+        }
+        Expr::Keys => match input {
+            Value::Object(object) => Ok(vec![Cow::Owned(
+                object.keys().map(|k| Value::from(k.as_str())).collect(),
+            )]),
+            Value::Array(array) => Ok(vec![Cow::Owned(
+                (0..array.len() as u64).map(Value::from).collect(),
+            )]),
+            value => Err(QueryError::new(&format!(
+                "error: {} has no keys",
+                type_name(value)
+            ))),
+        },
+        Expr::Not => Ok(vec![Cow::Owned(Value::Bool(!truthy(input)))]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    fn query(json: &str, expr: &str) -> Vec<String> {
+        parse(json)
+            .query(expr)
+            .unwrap()
+            .iter()
+            .map(Value::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn test_query() {
+        let json = r#"{"items": [
+            {"name": "a", "active": true, "age": 20},
+            {"name": "b", "active": false, "age": 35},
+            {"name": "c", "active": true, "age": 40, "tags": ["x", "y"]}
+        ], "first name": "q"}"#;
+        assert_eq!(
+            query(json, ".items[] | select(.active) | .name"),
+            [r#""a""#, r#""c""#]
+        );
+        assert_eq!(
+            query(json, ".items[] | select(.age > 30) | .name"),
+            [r#""b""#, r#""c""#]
+        );
+        assert_eq!(
+            query(json, r#".items[] | select(.name == "b") | .age"#),
+            ["35"]
+        );
+        assert_eq!(query(json, ".items[-1].tags[0]"), [r#""x""#]);
+        assert_eq!(query(json, r#"."first name""#), [r#""q""#]);
+        assert_eq!(query(json, r#".["first name"]"#), [r#""q""#]);
+        assert_eq!(query(json, ".items | length"), ["3"]);
+        assert_eq!(
+            query(json, ".items[0] | keys"),
+            [r#"["active","age","name"]"#]
+        );
+        assert_eq!(
+            query(json, ".items[] | .active | not"),
+            ["false", "true", "false"]
+        );
+        assert_eq!(query(json, ".missing.deeper"), ["null"]);
+        assert_eq!(query(json, ".items[5]"), ["null"]);
+        assert_eq!(query(json, "."), [parse(json).to_string()]);
+        assert_eq!(query("[3, 1, 2]", ".[] | select(. >= 2)"), ["3", "2"]);
+        assert_eq!(query(r#"{"a": 1, "b": 2}"#, ".[]"), ["1", "2"]);
+        // 途中で作った値をたどっても同じ結果になる
+        assert_eq!(query(json, ".items[0] | keys | .[1]"), [r#""age""#]);
+        assert_eq!(
+            query(json, ".items[0] | keys[]"),
+            [r#""active""#, r#""age""#, r#""name""#]
+        );
+    }
+
+    #[test]
+    fn test_borrow() {
+        // 入力の一部を返すときは複製せずに借用する
+        let value = parse(r#"{"items": [{"name": "a", "active": true}, {"active": false}]}"#);
+        let expr = Query::parse(".items[] | select(.active) | .name")
+            .unwrap()
+            .expr;
+        let outputs = eval(&expr, &value).unwrap();
+        assert_eq!(outputs.len(), 1);
+        assert!(
+            matches!(outputs[0], Cow::Borrowed(v) if std::ptr::eq(v, &value["items"][0]["name"]))
+        );
+    }
+
+    #[test]
+    fn test_error() {
+        let e = parse(r#"{"a": 1}"#).query(".a.b").unwrap_err();
+        assert_eq!(e.msg, r#"error: cannot index number with "b""#);
+        assert!(parse("1").query(".[]").is_err());
+        let e = Query::parse(".a | foo").unwrap_err();
+        assert_eq!(
+            e.msg,
+            r#"error: an unknown function "foo" at 5 in ".a | foo""#
+        );
+        assert!(Query::parse(".a[").is_err());
+        assert!(Query::parse("select(.a").is_err());
+        assert!(Query::parse(".a )").is_err());
+    }
+}
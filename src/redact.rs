@@ -0,0 +1,83 @@
+use crate::{pointer::JsonPointer, value::Value};
+
+/// 伏せた値の代わりに置く文字列
+pub const REDACTED: &str = "***";
+
+impl Value {
+    /// `patterns` に合う値を `"***"` で置き換える
+    ///
+    /// `/` で始まる pattern は JSON Pointer として位置に合わせ、`*` の段は任意のキーや添字に合う
+    /// (`/users/*/ssn`)。それ以外の pattern はどの深さのオブジェクトのキーにも合う (`password`)
+    pub fn redact(&mut self, patterns: &[&str]) {
+        let mut names = vec![];
+        let mut pointers = vec![];
+        for pattern in patterns {
+            match JsonPointer::parse(pattern) {
+                Ok(pointer) if !pointer.is_root() => pointers.push(pointer),
+                _ => names.push(*pattern),
+            }
+        }
+        redact_in(self, &names, &pointers, &mut JsonPointer::root());
+    }
+}
+
+fn redact_in(value: &mut Value, names: &[&str], pointers: &[JsonPointer], path: &mut JsonPointer) {
+    let matches = |path: &JsonPointer| {
+        pointers.iter().any(|pointer| {
+            let (pattern, path) = (pointer.tokens(), path.tokens());
+            pattern.len() == path.len() && pattern.iter().zip(path).all(|(p, s)| p == "*" || p == s)
+        })
+    };
+    match value {
+        Value::Object(object) => {
+            for (key, value) in object {
+                path.push(key.as_str());
+                if names.contains(&key.as_str()) || matches(path) {
+                    *value = Value::from(REDACTED);
+                } else {
+                    redact_in(value, names, pointers, path);
+                }
+                path.pop();
+            }
+        }
+        Value::Array(array) => {
+            for (i, value) in array.iter_mut().enumerate() {
+                path.push(i.to_string());
+                if matches(path) {
+                    *value = Value::from(REDACTED);
+                } else {
+                    redact_in(value, names, pointers, path);
+                }
+                path.pop();
+            }
+        }
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_redact() {
+        let mut value = parse(
+            r#"{"user": "a", "password": "p", "nested": {"token": {"id": 1}},
+                "users": [{"name": "b", "ssn": "1"}, {"name": "c", "ssn": "2"}],
+                "keys": ["k0", "k1"], "ssn": "top"}"#,
+        );
+        value.redact(&["password", "token", "/users/*/ssn", "/keys/1"]);
+        assert_eq!(
+            value,
+            parse(
+                r#"{"user": "a", "password": "***", "nested": {"token": "***"},
+                    "users": [{"name": "b", "ssn": "***"}, {"name": "c", "ssn": "***"}],
+                    "keys": ["k0", "***"], "ssn": "top"}"#
+            )
+        );
+
+        let mut value = parse(r#"{"a/b": 1, "c": 2}"#);
+        value.redact(&["/a~1b"]);
+        assert_eq!(value, parse(r#"{"a/b": "***", "c": 2}"#));
+    }
+}
@@ -0,0 +1,145 @@
+use std::collections::BTreeMap;
+
+use crate::{parser::ParserError, pointer::ToPointer, value::Value};
+
+impl Value {
+    /// 配列の要素を、オブジェクトのキー `key` の値ごとに配列にまとめたオブジェクトを返す。
+    /// 配列でなければ None
+    ///
+    /// まとめる値が文字列ならそのまま、それ以外は JSON の表記 (ないときは `null`) をキーにする
+    pub fn group_by(&self, key: &str) -> Option<Value> {
+        self.group_by_value(|item| item.get(key))
+    }
+
+    /// `group_by` と同じく、各要素の `pointer` の指す値ごとにまとめる。不正な pointer ならエラー
+    pub fn group_by_pointer<P: ToPointer + ?Sized>(
+        &self,
+        pointer: &P,
+    ) -> Result<Option<Value>, ParserError> {
+        let pointer = pointer.to_pointer()?;
+        Ok(self.group_by_value(|item| pointer.resolve(item)))
+    }
+
+    fn group_by_value<'v>(&'v self, f: impl Fn(&'v Value) -> Option<&'v Value>) -> Option<Value> {
+        let mut groups: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for item in self.as_array()? {
+            let group = match f(item) {
+                Some(Value::String(s)) => s.clone(),
+                Some(value) => value.to_string(),
+                None => "null".to_string(),
+            };
+            groups.entry(group).or_default().push(item.clone());
+        }
+        Some(
+            groups
+                .into_iter()
+                .map(|(k, v)| (k, Value::Array(v)))
+                .collect(),
+        )
+    }
+
+    /// オブジェクトの配列 (行) を、キーごとの配列 (列) のオブジェクトにする
+    ///
+    /// `[{"a":1,"b":2},{"a":3}]` は `{"a":[1,3],"b":[2,null]}` になる。ない値は null で埋める。
+    /// 配列でないか、オブジェクトでない要素があれば None
+    pub fn to_columns(&self) -> Option<Value> {
+        let rows = self.as_array()?;
+        let mut columns: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+        for (i, row) in rows.iter().enumerate() {
+            for (key, value) in row.as_object()? {
+                let column = columns.entry(key.clone()).or_default();
+                column.resize(i, Value::Null);
+                column.push(value.clone());
+            }
+        }
+        Some(
+            columns
+                .into_iter()
+                .map(|(key, mut column)| {
+                    column.resize(rows.len(), Value::Null);
+                    (key, Value::Array(column))
+                })
+                .collect(),
+        )
+    }
+
+    /// `to_columns` の逆。キーごとの配列のオブジェクトを、オブジェクトの配列にする
+    ///
+    /// 行の数は最も長い列に合わせ、短い列は null で埋める。
+    /// オブジェクトでないか、配列でない値があれば None
+    pub fn from_columns(&self) -> Option<Value> {
+        let columns = self.as_object()?;
+        let mut rows = vec![];
+        for (key, column) in columns {
+            let column = column.as_array()?;
+            if rows.len() < column.len() {
+                rows.resize(column.len(), BTreeMap::new());
+            }
+            for (row, value) in rows.iter_mut().zip(column) {
+                row.insert(key.clone(), value.clone());
+            }
+        }
+        Some(
+            rows.into_iter()
+                .map(|mut row| {
+                    for key in columns.keys() {
+                        row.entry(key.clone()).or_insert(Value::Null);
+                    }
+                    Value::Object(row)
+                })
+                .collect(),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_group_by() {
+        let value = parse(
+            r#"[{"team": "a", "n": 1, "m": {"k": 1}}, {"team": "b", "n": 2, "m": {"k": 1}},
+                {"team": "a", "n": 3}, {"n": 4}]"#,
+        );
+        assert_eq!(
+            value.group_by("team"),
+            Some(parse(
+                r#"{"a": [{"team": "a", "n": 1, "m": {"k": 1}}, {"team": "a", "n": 3}],
+                    "b": [{"team": "b", "n": 2, "m": {"k": 1}}], "null": [{"n": 4}]}"#
+            ))
+        );
+        let by_k = value.group_by_pointer("/m/k").unwrap().unwrap();
+        assert_eq!(
+            by_k.get("1").and_then(Value::as_array).map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(
+            by_k.get("null").and_then(Value::as_array).map(Vec::len),
+            Some(2)
+        );
+        assert_eq!(parse("{}").group_by("a"), None);
+        assert!(value.group_by_pointer("m/k").is_err());
+    }
+
+    #[test]
+    fn test_columns() {
+        let rows = parse(r#"[{"a": 1, "b": 2}, {"a": 3}, {"b": 4}]"#);
+        let columns = rows.to_columns().unwrap();
+        assert_eq!(columns, parse(r#"{"a": [1, 3, null], "b": [2, null, 4]}"#));
+        assert_eq!(
+            columns.from_columns(),
+            Some(parse(
+                r#"[{"a": 1, "b": 2}, {"a": 3, "b": null}, {"a": null, "b": 4}]"#
+            ))
+        );
+        assert_eq!(
+            parse(r#"{"a": [1], "b": [2, 3]}"#).from_columns(),
+            Some(parse(r#"[{"a": 1, "b": 2}, {"a": null, "b": 3}]"#))
+        );
+        assert_eq!(parse("[]").to_columns(), Some(parse("{}")));
+        assert_eq!(parse("[1]").to_columns(), None);
+        assert_eq!(parse(r#"{"a": 1}"#).from_columns(), None);
+    }
+}
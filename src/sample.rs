@@ -0,0 +1,78 @@
+use crate::value::Value;
+
+impl Value {
+    /// 配列の先頭の `n` 要素を新しい配列で返す。配列でなければ None
+    pub fn head(&self, n: usize) -> Option<Value> {
+        let array = self.as_array()?;
+        Some(Value::Array(array[..n.min(array.len())].to_vec()))
+    }
+
+    /// 配列の末尾の `n` 要素を新しい配列で返す。配列でなければ None
+    pub fn tail(&self, n: usize) -> Option<Value> {
+        let array = self.as_array()?;
+        Some(Value::Array(
+            array[array.len().saturating_sub(n)..].to_vec(),
+        ))
+    }
+
+    /// 配列から `n` 要素を無作為に選び、元の順のまま新しい配列で返す。配列でなければ None
+    ///
+    /// 同じ `seed` なら同じ要素を選ぶ。複製するのは選んだ要素だけ
+    pub fn sample(&self, n: usize, seed: u64) -> Option<Value> {
+        let array = self.as_array()?;
+        if n >= array.len() {
+            return Some(self.clone());
+        }
+        // 添字の部分的な Fisher-Yates シャッフル
+        let mut rng = SplitMix64(seed);
+        let mut indexes: Vec<usize> = (0..array.len()).collect();
+        for i in 0..n {
+            let j = i + (rng.next() % (array.len() - i) as u64) as usize;
+            indexes.swap(i, j);
+        }
+        let mut chosen = indexes[..n].to_vec();
+        chosen.sort_unstable();
+        Some(chosen.into_iter().map(|i| array[i].clone()).collect())
+    }
+}
+
+/// 外部の crate に頼らない小さな擬似乱数生成器
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9e37_79b9_7f4a_7c15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_head_tail() {
+        let value: Value = (1..=5).map(Value::from).collect();
+        assert_eq!(value.head(2), Some(Value::from(vec![1, 2])));
+        assert_eq!(value.tail(2), Some(Value::from(vec![4, 5])));
+        assert_eq!(value.head(10), Some(value.clone()));
+        assert_eq!(value.tail(0), Some(Value::Array(vec![])));
+        assert_eq!(Value::from(1).head(1), None);
+    }
+
+    #[test]
+    fn test_sample() {
+        let value: Value = (0..100).map(Value::from).collect();
+        let sample = value.sample(10, 42).unwrap();
+        let numbers: Vec<i64> = sample.iter().filter_map(Value::as_i64).collect();
+        assert_eq!(numbers.len(), 10);
+        assert!(numbers.windows(2).all(|w| w[0] < w[1]));
+        assert_eq!(value.sample(10, 42), Some(sample.clone()));
+        assert_ne!(value.sample(10, 7), Some(sample));
+        assert_eq!(value.sample(200, 1), Some(value.clone()));
+        assert_eq!(Value::Null.sample(1, 1), None);
+    }
+}
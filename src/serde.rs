@@ -0,0 +1,996 @@
+//! serde との相互運用 (`serde` feature)
+//!
+//! `Value` に `Serialize` / `Deserialize` を実装し、Rust の値と `Value` を直接変換する
+//! `to_value` / `from_value` を提供する。enum は serde_json と同じく外部タグ
+//! (`{"Variant": ...}`、unit variant は文字列) で表す。
+
+use std::{collections::BTreeMap, fmt};
+
+use ::serde::{
+    de::{self, DeserializeOwned, IntoDeserializer, Visitor},
+    forward_to_deserialize_any,
+    ser::{self, Impossible},
+    Deserialize, Deserializer, Serialize, Serializer,
+};
+
+use crate::{number::Number, value::Value};
+
+/// serde による変換の失敗
+#[derive(Debug, Clone, PartialEq)]
+pub struct SerdeError {
+    pub msg: String,
+}
+
+impl SerdeError {
+    fn new(msg: &str) -> SerdeError {
+        SerdeError {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+impl fmt::Display for SerdeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.msg)
+    }
+}
+
+impl std::error::Error for SerdeError {}
+
+impl ser::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> SerdeError {
+        SerdeError::new(&format!("error: {}", msg))
+    }
+}
+
+impl de::Error for SerdeError {
+    fn custom<T: fmt::Display>(msg: T) -> SerdeError {
+        SerdeError::new(&format!("error: {}", msg))
+    }
+}
+
+/// `T` を `Value` に変換する
+pub fn to_value<T: Serialize + ?Sized>(value: &T) -> Result<Value, SerdeError> {
+    value.serialize(ValueSerializer)
+}
+
+/// `Value` を `T` に変換する
+pub fn from_value<T: DeserializeOwned>(value: Value) -> Result<T, SerdeError> {
+    T::deserialize(value)
+}
+
+impl Serialize for Number {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if let Some(n) = self.as_u64() {
+            serializer.serialize_u64(n)
+        } else if let Some(n) = self.as_i64() {
+            serializer.serialize_i64(n)
+        } else {
+            serializer.serialize_f64(self.as_f64())
+        }
+    }
+}
+
+impl Serialize for Value {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Value::Null => serializer.serialize_unit(),
+            Value::Bool(b) => serializer.serialize_bool(*b),
+            Value::Number(n) => n.serialize(serializer),
+            Value::String(s) => serializer.serialize_str(s),
+            Value::Array(array) => serializer.collect_seq(array),
+            Value::Object(object) => serializer.collect_map(object),
+            Value::Raw(raw) => raw
+                .parse()
+                .map_err(ser::Error::custom)?
+                .serialize(serializer),
+        }
+    }
+}
+
+struct ValueVisitor;
+
+impl<'de> Visitor<'de> for ValueVisitor {
+    type Value = Value;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "a JSON value")
+    }
+
+    fn visit_bool<E>(self, b: bool) -> Result<Value, E> {
+        Ok(Value::Bool(b))
+    }
+
+    fn visit_i64<E>(self, n: i64) -> Result<Value, E> {
+        Ok(Value::from(n))
+    }
+
+    fn visit_u64<E>(self, n: u64) -> Result<Value, E> {
+        Ok(Value::from(n))
+    }
+
+    fn visit_f64<E>(self, n: f64) -> Result<Value, E> {
+        Ok(Value::from(n))
+    }
+
+    fn visit_str<E>(self, s: &str) -> Result<Value, E> {
+        Ok(Value::from(s))
+    }
+
+    fn visit_string<E>(self, s: String) -> Result<Value, E> {
+        Ok(Value::String(s))
+    }
+
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_some<D: Deserializer<'de>>(self, deserializer: D) -> Result<Value, D::Error> {
+        Value::deserialize(deserializer)
+    }
+
+    fn visit_seq<A: de::SeqAccess<'de>>(self, mut seq: A) -> Result<Value, A::Error> {
+        let mut array = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(value) = seq.next_element()? {
+            array.push(value);
+        }
+        Ok(Value::Array(array))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<Value, A::Error> {
+        let mut object = BTreeMap::new();
+        while let Some((key, value)) = map.next_entry()? {
+            object.insert(key, value);
+        }
+        Ok(Value::Object(object))
+    }
+}
+
+impl<'de> Deserialize<'de> for Value {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Value, D::Error> {
+        deserializer.deserialize_any(ValueVisitor)
+    }
+}
+
+/// Rust の値を `Value` に変換する `Serializer`
+struct ValueSerializer;
+
+impl Serializer for ValueSerializer {
+    type Ok = Value;
+    type Error = SerdeError;
+    type SerializeSeq = SerializeArray;
+    type SerializeTuple = SerializeArray;
+    type SerializeTupleStruct = SerializeArray;
+    type SerializeTupleVariant = SerializeArray;
+    type SerializeMap = SerializeObject;
+    type SerializeStruct = SerializeObject;
+    type SerializeStructVariant = SerializeObject;
+
+    fn serialize_bool(self, v: bool) -> Result<Value, SerdeError> {
+        Ok(Value::Bool(v))
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<Value, SerdeError> {
+        Ok(Value::from(v as i64))
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<Value, SerdeError> {
+        Ok(Value::from(v as i64))
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<Value, SerdeError> {
+        Ok(Value::from(v as u64))
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<Value, SerdeError> {
+        Ok(Value::from(v as u64))
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<Value, SerdeError> {
+        Ok(Value::from(v as f64))
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    fn serialize_char(self, v: char) -> Result<Value, SerdeError> {
+        Ok(Value::String(v.to_string()))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<Value, SerdeError> {
+        Ok(Value::from(v))
+    }
+
+    /// バイト列は数値の配列にする
+    fn serialize_bytes(self, v: &[u8]) -> Result<Value, SerdeError> {
+        Ok(Value::Array(
+            v.iter().map(|&b| Value::from(b as u64)).collect(),
+        ))
+    }
+
+    fn serialize_none(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<Value, SerdeError> {
+        Ok(Value::Null)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<Value, SerdeError> {
+        Ok(Value::from(variant))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<Value, SerdeError> {
+        let mut object = BTreeMap::new();
+        object.insert(variant.to_string(), to_value(value)?);
+        Ok(Value::Object(object))
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: None,
+            array: Vec::with_capacity(len.unwrap_or(0)),
+        })
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<SerializeArray, SerdeError> {
+        Ok(SerializeArray {
+            variant: Some(variant),
+            array: Vec::with_capacity(len),
+        })
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: None,
+            object: BTreeMap::new(),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        self.serialize_map(Some(len))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+        _len: usize,
+    ) -> Result<SerializeObject, SerdeError> {
+        Ok(SerializeObject {
+            variant: Some(variant),
+            object: BTreeMap::new(),
+            key: None,
+        })
+    }
+}
+
+/// variant があれば `{variant: value}` で包む
+fn wrap_variant(variant: Option<&'static str>, value: Value) -> Value {
+    match variant {
+        Some(variant) => {
+            let mut object = BTreeMap::new();
+            object.insert(variant.to_string(), value);
+            Value::Object(object)
+        }
+        None => value,
+    }
+}
+
+struct SerializeArray {
+    variant: Option<&'static str>,
+    array: Vec<Value>,
+}
+
+impl ser::SerializeSeq for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        self.array.push(to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(wrap_variant(self.variant, Value::Array(self.array)))
+    }
+}
+
+impl ser::SerializeTuple for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleStruct for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+impl ser::SerializeTupleVariant for SerializeArray {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeSeq::end(self)
+    }
+}
+
+struct SerializeObject {
+    variant: Option<&'static str>,
+    object: BTreeMap<String, Value>,
+    key: Option<String>, // 値を待っているキー
+}
+
+impl ser::SerializeMap for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), SerdeError> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), SerdeError> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| SerdeError::new("error: a map value is serialized before its key"))?;
+        self.object.insert(key, to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        Ok(wrap_variant(self.variant, Value::Object(self.object)))
+    }
+}
+
+impl ser::SerializeStruct for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        self.object.insert(key.to_string(), to_value(value)?);
+        Ok(())
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+impl ser::SerializeStructVariant for SerializeObject {
+    type Ok = Value;
+    type Error = SerdeError;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), SerdeError> {
+        ser::SerializeStruct::serialize_field(self, key, value)
+    }
+
+    fn end(self) -> Result<Value, SerdeError> {
+        ser::SerializeMap::end(self)
+    }
+}
+
+/// オブジェクトのキーを文字列にする `Serializer`。文字列のほか、数値や真偽値は文字列にして受け付ける
+struct KeySerializer;
+
+impl KeySerializer {
+    fn invalid() -> SerdeError {
+        SerdeError::new("error: an object key must be a string")
+    }
+}
+
+impl Serializer for KeySerializer {
+    type Ok = String;
+    type Error = SerdeError;
+    type SerializeSeq = Impossible<String, SerdeError>;
+    type SerializeTuple = Impossible<String, SerdeError>;
+    type SerializeTupleStruct = Impossible<String, SerdeError>;
+    type SerializeTupleVariant = Impossible<String, SerdeError>;
+    type SerializeMap = Impossible<String, SerdeError>;
+    type SerializeStruct = Impossible<String, SerdeError>;
+    type SerializeStructVariant = Impossible<String, SerdeError>;
+
+    fn serialize_bool(self, v: bool) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_char(self, v: char) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_str(self, v: &str) -> Result<String, SerdeError> {
+        Ok(v.to_string())
+    }
+
+    fn serialize_bytes(self, _v: &[u8]) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_none(self) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit(self) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<String, SerdeError> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, SerdeError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, SerdeError> {
+        Err(Self::invalid())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, SerdeError> {
+        Err(Self::invalid())
+    }
+}
+
+/// `Value` を読み出す `Deserializer`
+impl<'de> Deserializer<'de> for Value {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Value::Null => visitor.visit_unit(),
+            Value::Bool(b) => visitor.visit_bool(b),
+            Value::Number(n) => {
+                if let Some(n) = n.as_u64() {
+                    visitor.visit_u64(n)
+                } else if let Some(n) = n.as_i64() {
+                    visitor.visit_i64(n)
+                } else {
+                    visitor.visit_f64(n.as_f64())
+                }
+            }
+            Value::String(s) => visitor.visit_string(s),
+            Value::Array(array) => {
+                let len = array.len();
+                let mut seq = SeqDeserializer {
+                    iter: array.into_iter(),
+                };
+                let value = visitor.visit_seq(&mut seq)?;
+                match seq.iter.len() {
+                    0 => Ok(value),
+                    rest => Err(de::Error::invalid_length(
+                        len,
+                        &format!("{} elements", len - rest).as_str(),
+                    )),
+                }
+            }
+            Value::Object(object) => visitor.visit_map(MapDeserializer {
+                iter: object.into_iter(),
+                value: None,
+            }),
+            Value::Raw(raw) => raw
+                .parse()
+                .map_err(de::Error::custom)?
+                .deserialize_any(visitor),
+        }
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        match self {
+            Value::Null => visitor.visit_none(),
+            value => visitor.visit_some(value),
+        }
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    /// 文字列は unit variant、要素が 1 つのオブジェクトは `{variant: value}` として読む
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        let (variant, value) = match self {
+            Value::String(variant) => (variant, None),
+            Value::Object(object) if object.len() == 1 => {
+                let (variant, value) = object.into_iter().next().unwrap();
+                (variant, Some(value))
+            }
+            value => {
+                return Err(SerdeError::new(&format!(
+                    "error: a string or an object with a single key is expected for an enum but found {}",
+                    value
+                )))
+            }
+        };
+        visitor.visit_enum(EnumDeserializer { variant, value })
+    }
+
+    forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct seq tuple tuple_struct map struct
+        identifier ignored_any
+    }
+}
+
+impl IntoDeserializer<'_, SerdeError> for Value {
+    type Deserializer = Value;
+
+    fn into_deserializer(self) -> Value {
+        self
+    }
+}
+
+struct SeqDeserializer {
+    iter: std::vec::IntoIter<Value>,
+}
+
+impl<'de> de::SeqAccess<'de> for SeqDeserializer {
+    type Error = SerdeError;
+
+    fn next_element_seed<T: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, SerdeError> {
+        self.iter
+            .next()
+            .map(|value| seed.deserialize(value))
+            .transpose()
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+struct MapDeserializer {
+    iter: std::collections::btree_map::IntoIter<String, Value>,
+    value: Option<Value>, // 読んだキーに対応する値
+}
+
+impl<'de> de::MapAccess<'de> for MapDeserializer {
+    type Error = SerdeError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, SerdeError> {
+        match self.iter.next() {
+            Some((key, value)) => {
+                self.value = Some(value);
+                seed.deserialize(KeyDeserializer(key)).map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, SerdeError> {
+        let value = self
+            .value
+            .take()
+            .ok_or_else(|| SerdeError::new("error: a map value is read before its key"))?;
+        seed.deserialize(value)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.iter.len())
+    }
+}
+
+/// オブジェクトのキーを読む `Deserializer`。整数や真偽値として読むときは文字列を解釈する
+struct KeyDeserializer(String);
+
+macro_rules! deserialize_parsed_key {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+                match self.0.parse() {
+                    Ok(n) => visitor.$visit(n),
+                    Err(_) => visitor.visit_string(self.0),
+                }
+            }
+        )*
+    };
+}
+
+impl<'de> Deserializer<'de> for KeyDeserializer {
+    type Error = SerdeError;
+
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_string(self.0)
+    }
+
+    deserialize_parsed_key! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, SerdeError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        Value::String(self.0).deserialize_enum(name, variants, visitor)
+    }
+
+    forward_to_deserialize_any! {
+        i128 u128 f32 f64 char str string bytes byte_buf unit unit_struct seq
+        tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+struct EnumDeserializer {
+    variant: String,
+    value: Option<Value>, // unit variant なら None
+}
+
+impl<'de> de::EnumAccess<'de> for EnumDeserializer {
+    type Error = SerdeError;
+    type Variant = VariantDeserializer;
+
+    fn variant_seed<V: de::DeserializeSeed<'de>>(
+        self,
+        seed: V,
+    ) -> Result<(V::Value, VariantDeserializer), SerdeError> {
+        let variant = seed.deserialize(Value::String(self.variant))?;
+        Ok((variant, VariantDeserializer { value: self.value }))
+    }
+}
+
+struct VariantDeserializer {
+    value: Option<Value>,
+}
+
+impl<'de> de::VariantAccess<'de> for VariantDeserializer {
+    type Error = SerdeError;
+
+    fn unit_variant(self) -> Result<(), SerdeError> {
+        match self.value {
+            None | Some(Value::Null) => Ok(()),
+            Some(value) => Err(SerdeError::new(&format!(
+                "error: a unit variant is expected but found {}",
+                value
+            ))),
+        }
+    }
+
+    fn newtype_variant_seed<T: de::DeserializeSeed<'de>>(
+        self,
+        seed: T,
+    ) -> Result<T::Value, SerdeError> {
+        match self.value {
+            Some(value) => seed.deserialize(value),
+            None => Err(SerdeError::new(
+                "error: a newtype variant is expected but found a unit variant",
+            )),
+        }
+    }
+
+    fn tuple_variant<V: Visitor<'de>>(
+        self,
+        _len: usize,
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Some(value @ Value::Array(_)) => value.deserialize_any(visitor),
+            _ => Err(SerdeError::new("error: a tuple variant is expected")),
+        }
+    }
+
+    fn struct_variant<V: Visitor<'de>>(
+        self,
+        _fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, SerdeError> {
+        match self.value {
+            Some(value @ Value::Object(_)) => value.deserialize_any(visitor),
+            _ => Err(SerdeError::new("error: a struct variant is expected")),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashMap;
+
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_value_round_trip() {
+        let value = parse(
+            r#"{"a": [1, -2, 1.5, 18446744073709551615, true, null, "s"],
+                "b": {"c": {}, "d": []}}"#,
+        );
+        assert_eq!(to_value(&value), Ok(value.clone()));
+        assert_eq!(from_value::<Value>(value.clone()), Ok(value.clone()));
+        let raw = Value::Raw(crate::value::RawValue::new(r#"{"x": [1]}"#.to_string()));
+        assert_eq!(to_value(&raw), Ok(parse(r#"{"x": [1]}"#)));
+    }
+
+    #[test]
+    fn test_rust_round_trip() {
+        let mut map: BTreeMap<String, Vec<Option<i64>>> = BTreeMap::new();
+        map.insert("a".to_string(), vec![Some(1), None, Some(-3)]);
+        map.insert("b".to_string(), vec![]);
+        let value = to_value(&map).unwrap();
+        assert_eq!(value.to_string(), r#"{"a":[1,null,-3],"b":[]}"#);
+        assert_eq!(
+            from_value::<BTreeMap<String, Vec<Option<i64>>>>(value),
+            Ok(map)
+        );
+
+        let tuple = (u64::MAX, 'x', "s".to_string(), 0.25, (), true);
+        let value = to_value(&tuple).unwrap();
+        assert_eq!(
+            value.to_string(),
+            r#"[18446744073709551615,"x","s",0.25,null,true]"#
+        );
+        assert_eq!(from_value(value), Ok(tuple));
+
+        // 数値のキーは文字列にする
+        let mut map = HashMap::new();
+        map.insert(1u32, "one");
+        assert_eq!(to_value(&map).unwrap().to_string(), r#"{"1":"one"}"#);
+        let back: HashMap<u32, String> = from_value(parse(r#"{"1":"one"}"#)).unwrap();
+        assert_eq!(back[&1], "one");
+
+        // enum は外部タグで表す
+        let results: Vec<Result<i32, String>> = vec![Ok(1), Err("e".to_string())];
+        let value = to_value(&results).unwrap();
+        assert_eq!(value.to_string(), r#"[{"Ok":1},{"Err":"e"}]"#);
+        assert_eq!(from_value(value), Ok(results));
+    }
+
+    #[test]
+    fn test_error() {
+        assert!(from_value::<i32>(parse(r#""1""#)).is_err());
+        assert!(from_value::<u8>(parse("256")).is_err());
+        assert!(from_value::<HashMap<u32, i32>>(parse(r#"{"x": 1}"#)).is_err());
+        assert!(from_value::<(i32, i32)>(parse("[1, 2, 3]")).is_err());
+        assert!(from_value::<Result<i32, i32>>(parse(r#"{"Ok": 1, "Err": 2}"#)).is_err());
+        let mut map = BTreeMap::new();
+        map.insert(vec![1], 1);
+        assert_eq!(
+            to_value(&map).unwrap_err().msg,
+            "error: an object key must be a string"
+        );
+    }
+}
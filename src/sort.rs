@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+
+use crate::{number::Number, parser::ParserError, pointer::ToPointer, value::Value};
+
+/// 並べる向き
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Order {
+    /// 昇順
+    #[default]
+    Asc,
+    /// 降順
+    Desc,
+}
+
+impl Value {
+    /// オブジェクトの配列を、各要素の `pointer` の指す値で安定にその場で並べる
+    ///
+    /// 値は `compare` の順で比べ、値がない要素は null として扱う。配列でなければ何もしない。
+    /// 不正な pointer ならエラー
+    pub fn sort_array_by_pointer<P: ToPointer + ?Sized>(
+        &mut self,
+        pointer: &P,
+        order: Order,
+    ) -> Result<(), ParserError> {
+        let pointer = pointer.to_pointer()?;
+        self.sort_array_by_key(
+            |item| pointer.resolve(item).cloned().unwrap_or_default(),
+            order,
+        );
+        Ok(())
+    }
+
+    /// 配列を、各要素から `f` で取り出した値で安定にその場で並べる。配列でなければ何もしない
+    pub fn sort_array_by_key<F: FnMut(&Value) -> Value>(&mut self, mut f: F, order: Order) {
+        if let Value::Array(array) = self {
+            // 比べるたびに取り出さないよう、先に取り出しておく
+            let mut keyed: Vec<(Value, Value)> =
+                array.drain(..).map(|item| (f(&item), item)).collect();
+            keyed.sort_by(|(a, _), (b, _)| match order {
+                Order::Asc => compare(a, b),
+                Order::Desc => compare(b, a),
+            });
+            array.extend(keyed.into_iter().map(|(_, item)| item));
+        }
+    }
+}
+
+/// jq と同じく null < false < true < 数値 < 文字列 < 配列 < オブジェクト の順で比べる
+///
+/// 数値は値で比べ、NaN はどの数値よりも小さい。配列は要素ごとに、オブジェクトはキーの列、値の列の順に比べる
+pub(crate) fn compare(a: &Value, b: &Value) -> Ordering {
+    let rank = |v: &Value| match v {
+        Value::Null => 0,
+        Value::Bool(false) => 1,
+        Value::Bool(true) => 2,
+        Value::Number(_) => 3,
+        Value::String(_) => 4,
+        Value::Array(_) => 5,
+        Value::Object(_) => 6,
+        Value::Raw(_) => 7,
+    };
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => compare_numbers(x, y),
+        (Value::String(x), Value::String(y)) => x.cmp(y),
+        (Value::Array(x), Value::Array(y)) => x
+            .iter()
+            .zip(y)
+            .map(|(x, y)| compare(x, y))
+            .find(|o| o.is_ne())
+            .unwrap_or(x.len().cmp(&y.len())),
+        (Value::Object(x), Value::Object(y)) => x.keys().cmp(y.keys()).then_with(|| {
+            x.values()
+                .zip(y.values())
+                .map(|(x, y)| compare(x, y))
+                .find(|o| o.is_ne())
+                .unwrap_or(Ordering::Equal)
+        }),
+        (Value::Raw(x), Value::Raw(y)) => x.get().cmp(y.get()),
+        _ => rank(a).cmp(&rank(b)),
+    }
+}
+
+fn compare_numbers(x: &Number, y: &Number) -> Ordering {
+    let int = |n: &Number| n.as_i64().map(i128::from).or(n.as_u64().map(i128::from));
+    match (int(x), int(y)) {
+        // 整数どうしは f64 に丸めず厳密に比べる
+        (Some(x), Some(y)) => x.cmp(&y),
+        _ if x == y => Ordering::Equal,
+        _ => {
+            let (x, y) = (x.as_f64(), y.as_f64());
+            match (x.is_nan(), y.is_nan()) {
+                (true, true) => Ordering::Equal,
+                (true, false) => Ordering::Less,
+                (false, true) => Ordering::Greater,
+                (false, false) => x.partial_cmp(&y).unwrap_or(Ordering::Equal),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pointer::JsonPointer;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_sort_by_pointer() {
+        let mut value = parse(
+            r#"[{"n": "a", "age": 30}, {"n": "b", "age": 25}, {"n": "c"},
+                {"n": "d", "age": 30}, {"n": "e", "age": "old"}]"#,
+        );
+        let names = |value: &Value| {
+            value
+                .iter()
+                .filter_map(|v| v.get("n").and_then(Value::as_str).map(str::to_string))
+                .collect::<Vec<_>>()
+        };
+        value.sort_array_by_pointer("/age", Order::Asc).unwrap();
+        assert_eq!(names(&value), ["c", "b", "a", "d", "e"]);
+        // 降順でも等しい要素の順は保つ
+        let age = JsonPointer::root().key("age");
+        value.sort_array_by_pointer(&age, Order::Desc).unwrap();
+        assert_eq!(names(&value), ["e", "a", "d", "b", "c"]);
+
+        value.sort_array_by_key(|v| v["n"].clone(), Order::Desc);
+        assert_eq!(names(&value), ["e", "d", "c", "b", "a"]);
+        // 不正な pointer は null として扱わずにエラーにする
+        assert!(value.sort_array_by_pointer("age", Order::Asc).is_err());
+        assert_eq!(names(&value), ["e", "d", "c", "b", "a"]);
+    }
+
+    #[test]
+    fn test_compare() {
+        let sorted =
+            parse(r#"[null, false, true, -1, 1.5, 2, "a", "b", [1], [1, 2], {"a": 1}, {"b": 0}]"#);
+        let items = sorted.as_array().unwrap();
+        for (i, a) in items.iter().enumerate() {
+            for (j, b) in items.iter().enumerate() {
+                assert_eq!(compare(a, b), i.cmp(&j), "{} {}", a, b);
+            }
+        }
+        assert_eq!(
+            compare(
+                &Value::from(9_007_199_254_740_993u64),
+                &Value::from(9_007_199_254_740_992u64)
+            ),
+            Ordering::Greater
+        );
+        assert_eq!(
+            compare(&Value::from(f64::NAN), &Value::from(-1)),
+            Ordering::Less
+        );
+        assert_eq!(compare(&Value::from(1.0), &Value::from(1)), Ordering::Equal);
+    }
+}
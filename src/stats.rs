@@ -0,0 +1,69 @@
+use crate::value::Value;
+
+/// `Value::stats` の返す文書の統計
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Stats {
+    pub nodes: usize,          // 自身を含む節点の数
+    pub max_depth: usize,      // 入れ子の最大の深さ (スカラーだけなら 0)
+    pub keys: usize,           // オブジェクトのキーの総数
+    pub key_bytes: usize,      // キーの UTF-8 のバイト数の合計
+    pub string_bytes: usize,   // 文字列の値の UTF-8 のバイト数の合計
+    pub arrays: usize,         // 配列の数
+    pub array_elements: usize, // 配列の要素の総数
+    pub max_array_len: usize,  // 最も長い配列の要素数
+}
+
+impl Value {
+    /// 節点の数や深さなどの統計を、値を複製せずに一度辿って集める
+    pub fn stats(&self) -> Stats {
+        let mut stats = Stats::default();
+        let mut stack = vec![(self, 0)];
+        while let Some((value, depth)) = stack.pop() {
+            stats.nodes += 1;
+            stats.max_depth = stats.max_depth.max(depth);
+            match value {
+                Value::String(s) => stats.string_bytes += s.len(),
+                Value::Array(array) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    stats.arrays += 1;
+                    stats.array_elements += array.len();
+                    stats.max_array_len = stats.max_array_len.max(array.len());
+                    stack.extend(array.iter().map(|value| (value, depth + 1)));
+                }
+                Value::Object(object) => {
+                    stats.max_depth = stats.max_depth.max(depth + 1);
+                    stats.keys += object.len();
+                    stats.key_bytes += object.keys().map(String::len).sum::<usize>();
+                    stack.extend(object.values().map(|value| (value, depth + 1)));
+                }
+                _ => (),
+            }
+        }
+        stats
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_stats() {
+        let value: Value = r#"{"a": [1, "xy", [true]], "bc": {"d": "é"}}"#.parse().unwrap();
+        assert_eq!(
+            value.stats(),
+            Stats {
+                nodes: 8,
+                max_depth: 3,
+                keys: 3,
+                key_bytes: 4,
+                string_bytes: 4,
+                arrays: 2,
+                array_elements: 4,
+                max_array_len: 3,
+            }
+        );
+        assert_eq!(Value::from(1).stats().max_depth, 0);
+        assert_eq!(Value::Array(vec![]).stats().max_depth, 1);
+    }
+}
@@ -0,0 +1,59 @@
+use crate::value::Value;
+
+impl Value {
+    /// 値が null のメンバーをオブジェクトから再帰的に取り除く
+    ///
+    /// 配列の要素は添字がずれないよう取り除かない (要素の中は辿る)
+    pub fn strip_nulls(&mut self) {
+        self.strip(false);
+    }
+
+    /// `strip_nulls` に加え、取り除いた結果が空になったものも含め、
+    /// 空のオブジェクトや配列を値に持つメンバーも取り除く
+    pub fn strip_nulls_and_empty(&mut self) {
+        self.strip(true);
+    }
+
+    fn strip(&mut self, drop_empty: bool) {
+        match self {
+            Value::Object(object) => {
+                object.retain(|_, value| {
+                    value.strip(drop_empty);
+                    match value {
+                        Value::Null => false,
+                        Value::Object(o) => !(drop_empty && o.is_empty()),
+                        Value::Array(a) => !(drop_empty && a.is_empty()),
+                        _ => true,
+                    }
+                });
+            }
+            Value::Array(array) => array.iter_mut().for_each(|value| value.strip(drop_empty)),
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_strip_nulls() {
+        let json = r#"{"a": null, "b": {"c": null, "d": 1}, "e": [null, {"f": null}], "g": {"h": null}, "i": []}"#;
+        let mut value = parse(json);
+        value.strip_nulls();
+        assert_eq!(
+            value,
+            parse(r#"{"b": {"d": 1}, "e": [null, {}], "g": {}, "i": []}"#)
+        );
+
+        let mut value = parse(json);
+        value.strip_nulls_and_empty();
+        assert_eq!(value, parse(r#"{"b": {"d": 1}, "e": [null, {}]}"#));
+
+        let mut value = Value::Null;
+        value.strip_nulls();
+        assert_eq!(value, Value::Null);
+    }
+}
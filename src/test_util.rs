@@ -0,0 +1,8 @@
+//! テストで共通に使う補助関数
+
+use crate::value::Value;
+
+/// テスト用の JSON を `Value` に解析する。不正な JSON なら panic する
+pub(crate) fn parse(json: &str) -> Value {
+    json.parse().unwrap()
+}
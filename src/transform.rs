@@ -0,0 +1,120 @@
+use std::collections::BTreeMap;
+
+use crate::value::Value;
+
+impl Value {
+    /// すべてのスカラー (オブジェクトと配列以外) を `f` で変換した文書を返す
+    pub fn map_values<F: FnMut(&Value) -> Value>(&self, mut f: F) -> Value {
+        map_values(self, &mut f)
+    }
+
+    /// すべての深さのオブジェクトのキーを `f` で変換した文書を返す
+    ///
+    /// 変換後のキーが重なったときは後のメンバーが残る
+    pub fn map_keys<F: FnMut(&str) -> String>(&self, mut f: F) -> Value {
+        map_keys(self, &mut f)
+    }
+
+    /// すべての深さのオブジェクトから、`f` が true を返すメンバーだけを残した文書を返す
+    pub fn filter_entries<F: FnMut(&str, &Value) -> bool>(&self, mut f: F) -> Value {
+        filter_entries(self, &mut f)
+    }
+
+    /// すべての深さの配列の要素とオブジェクトの値のうち、`f` が false を返すものをその場で取り除く
+    ///
+    /// 残した値の中も辿る。自身は取り除かない
+    pub fn retain<F: FnMut(&Value) -> bool>(&mut self, mut f: F) {
+        retain(self, &mut f)
+    }
+}
+
+fn map_values(value: &Value, f: &mut dyn FnMut(&Value) -> Value) -> Value {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (key.clone(), map_values(value, f)))
+            .collect(),
+        Value::Array(array) => array.iter().map(|value| map_values(value, f)).collect(),
+        value => f(value),
+    }
+}
+
+fn map_keys(value: &Value, f: &mut dyn FnMut(&str) -> String) -> Value {
+    match value {
+        Value::Object(object) => object
+            .iter()
+            .map(|(key, value)| (f(key), map_keys(value, f)))
+            .collect(),
+        Value::Array(array) => array.iter().map(|value| map_keys(value, f)).collect(),
+        value => value.clone(),
+    }
+}
+
+fn filter_entries(value: &Value, f: &mut dyn FnMut(&str, &Value) -> bool) -> Value {
+    match value {
+        Value::Object(object) => {
+            let mut filtered = BTreeMap::new();
+            for (key, value) in object {
+                if f(key, value) {
+                    filtered.insert(key.clone(), filter_entries(value, f));
+                }
+            }
+            Value::Object(filtered)
+        }
+        Value::Array(array) => array.iter().map(|value| filter_entries(value, f)).collect(),
+        value => value.clone(),
+    }
+}
+
+fn retain(value: &mut Value, f: &mut dyn FnMut(&Value) -> bool) {
+    match value {
+        Value::Object(object) => object.retain(|_, value| {
+            let keep = f(value);
+            if keep {
+                retain(value, f);
+            }
+            keep
+        }),
+        Value::Array(array) => array.retain_mut(|value| {
+            let keep = f(value);
+            if keep {
+                retain(value, f);
+            }
+            keep
+        }),
+        _ => (),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_map() {
+        let value = parse(r#"{"a": [1, "x"], "b_c": {"d_e": 2}}"#);
+        let doubled = value.map_values(|v| match v.as_i64() {
+            Some(n) => Value::from(n * 2),
+            None => v.clone(),
+        });
+        assert_eq!(doubled, parse(r#"{"a": [2, "x"], "b_c": {"d_e": 4}}"#));
+        assert_eq!(
+            value.map_keys(|k| k.replace('_', "-")),
+            parse(r#"{"a": [1, "x"], "b-c": {"d-e": 2}}"#)
+        );
+    }
+
+    #[test]
+    fn test_filter() {
+        let value = parse(r#"{"_id": 1, "a": [{"_rev": 2, "b": 3}], "c": {"_x": null}}"#);
+        assert_eq!(
+            value.filter_entries(|k, _| !k.starts_with('_')),
+            parse(r#"{"a": [{"b": 3}], "c": {}}"#)
+        );
+
+        let mut value = parse(r#"{"a": [1, null, {"b": null, "c": 2}], "d": null}"#);
+        value.retain(|v| !v.is_null());
+        assert_eq!(value, parse(r#"{"a": [1, {"c": 2}]}"#));
+    }
+}
@@ -0,0 +1,64 @@
+use crate::value::Value;
+
+impl Value {
+    /// ログ向けに要約した複製を返す
+    ///
+    /// 深さ `depth` より深い空でないオブジェクトと配列は文字列 `"{...}"` と `"[...]"` に、
+    /// `max_items` を超える配列の要素は末尾の `"[...N more]"` に、オブジェクトのメンバーは
+    /// キー `"..."` と値 `"{...N more}"` にまとめる。結果も正しい JSON として表示できる
+    pub fn truncated(&self, depth: usize, max_items: usize) -> Value {
+        match self {
+            Value::Object(object) if !object.is_empty() && depth == 0 => Value::from("{...}"),
+            Value::Array(array) if !array.is_empty() && depth == 0 => Value::from("[...]"),
+            Value::Object(object) => {
+                let mut truncated: Value = object
+                    .iter()
+                    .take(max_items)
+                    .map(|(key, value)| (key.clone(), value.truncated(depth - 1, max_items)))
+                    .collect();
+                if object.len() > max_items {
+                    let more = format!("{{...{} more}}", object.len() - max_items);
+                    truncated["..."] = Value::from(more);
+                }
+                truncated
+            }
+            Value::Array(array) => {
+                let mut truncated: Vec<Value> = array
+                    .iter()
+                    .take(max_items)
+                    .map(|value| value.truncated(depth - 1, max_items))
+                    .collect();
+                if array.len() > max_items {
+                    truncated.push(Value::from(format!(
+                        "[...{} more]",
+                        array.len() - max_items
+                    )));
+                }
+                Value::Array(truncated)
+            }
+            value => value.clone(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_truncated() {
+        let value = parse(r#"{"a": [1, 2, 3, 4], "b": {"c": {"d": 1}}, "e": [], "f": "x"}"#);
+        assert_eq!(
+            value.truncated(2, 2),
+            parse(r#"{"a": [1, 2, "[...2 more]"], "b": {"c": "{...}"}, "...": "{...2 more}"}"#)
+        );
+        assert_eq!(
+            value.truncated(1, 10),
+            parse(r#"{"a": "[...]", "b": "{...}", "e": [], "f": "x"}"#)
+        );
+        assert_eq!(value.truncated(0, 10), Value::from("{...}"));
+        assert_eq!(value.truncated(usize::MAX, usize::MAX), value);
+        assert_eq!(Value::from(1).truncated(0, 0), Value::from(1));
+    }
+}
@@ -0,0 +1,866 @@
+use std::{
+    collections::{btree_map::Entry, BTreeMap, HashMap},
+    fmt,
+    ops::{Index, IndexMut},
+    str::FromStr,
+};
+
+use crate::{
+    builder::{ArrayBuilder, ObjectBuilder},
+    error::JsonError,
+    lexer::Lexer,
+    number::Number,
+    parser::Parser,
+    printer::{fmt_value, PrinterOptions},
+};
+
+/// JSON の値
+///
+/// `Eq` と `Hash` は `Number` の等価性に従う (NaN どうしは等しく、-0.0 は 0 と等しい)。
+/// `HashMap` や `HashSet` のキーにして重複を除ける
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub enum Value {
+    String(String), // 文字列
+    Number(Number), // 数値
+    Bool(bool),     // 真偽値
+    #[default]
+    Null, // Null
+    Array(Vec<Value>), // JSON Array
+    /// JSON Object。キーは `BTreeMap` なので常に辞書順 (コードポイント順) に並ぶ
+    Object(BTreeMap<String, Value>),
+    Raw(RawValue), // 未解析の JSON
+}
+
+/// 解析を後回しにした JSON の部分木
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct RawValue {
+    json: String,
+}
+
+impl RawValue {
+    pub fn new(json: String) -> RawValue {
+        RawValue { json }
+    }
+
+    /// 元の JSON 文字列を返す
+    pub fn get(&self) -> &str {
+        &self.json
+    }
+
+    /// 部分木を解析して `Value` を返す
+    pub fn parse(&self) -> Result<Value, JsonError> {
+        Ok(Parser::new(Lexer::new(&self.json).tokenize()?).parse()?)
+    }
+}
+
+impl Value {
+    /// オブジェクトを組み立てる `ObjectBuilder` を返す
+    pub fn object() -> ObjectBuilder {
+        ObjectBuilder::new()
+    }
+
+    /// 配列を組み立てる `ArrayBuilder` を返す
+    pub fn array() -> ArrayBuilder {
+        ArrayBuilder::new()
+    }
+
+    /// オブジェクトのキーまたは配列の添字で値を返す。なければ None
+    pub fn get<I: ValueIndex>(&self, index: I) -> Option<&Value> {
+        index.index_into(self)
+    }
+
+    /// オブジェクトのキーまたは配列の添字で可変の値を返す。なければ None
+    pub fn get_mut<I: ValueIndex>(&mut self, index: I) -> Option<&mut Value> {
+        index.index_into_mut(self)
+    }
+
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            Value::String(s) => Some(s),
+            _ => None,
+        }
+    }
+
+    pub fn as_number(&self) -> Option<&Number> {
+        match self {
+            Value::Number(n) => Some(n),
+            _ => None,
+        }
+    }
+
+    /// 数値を `f64` で返す (整数も変換する)
+    pub fn as_f64(&self) -> Option<f64> {
+        self.as_number().map(Number::as_f64)
+    }
+
+    /// `i64` で表せる整数を返す (小数は None)
+    pub fn as_i64(&self) -> Option<i64> {
+        self.as_number().and_then(Number::as_i64)
+    }
+
+    /// `u64` で表せる整数を返す (負の数や小数は None)
+    pub fn as_u64(&self) -> Option<u64> {
+        self.as_number().and_then(Number::as_u64)
+    }
+
+    pub fn as_bool(&self) -> Option<bool> {
+        match self {
+            Value::Bool(b) => Some(*b),
+            _ => None,
+        }
+    }
+
+    pub fn as_array(&self) -> Option<&Vec<Value>> {
+        match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        }
+    }
+
+    pub fn as_object(&self) -> Option<&BTreeMap<String, Value>> {
+        match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        }
+    }
+
+    pub fn as_null(&self) -> Option<()> {
+        match self {
+            Value::Null => Some(()),
+            _ => None,
+        }
+    }
+
+    pub fn is_null(&self) -> bool {
+        matches!(self, Value::Null)
+    }
+
+    pub fn is_string(&self) -> bool {
+        matches!(self, Value::String(_))
+    }
+
+    pub fn is_number(&self) -> bool {
+        matches!(self, Value::Number(_))
+    }
+
+    pub fn is_i64(&self) -> bool {
+        self.as_number().is_some_and(Number::is_i64)
+    }
+
+    pub fn is_u64(&self) -> bool {
+        self.as_number().is_some_and(Number::is_u64)
+    }
+
+    pub fn is_f64(&self) -> bool {
+        self.as_number().is_some_and(Number::is_f64)
+    }
+
+    pub fn is_bool(&self) -> bool {
+        matches!(self, Value::Bool(_))
+    }
+
+    pub fn is_array(&self) -> bool {
+        matches!(self, Value::Array(_))
+    }
+
+    pub fn is_object(&self) -> bool {
+        matches!(self, Value::Object(_))
+    }
+
+    /// オブジェクトの `key` の `Entry` を返す
+    ///
+    /// `Value::Null` は空のオブジェクトにしてから返す。それ以外のオブジェクトでない値は None
+    pub fn entry(&mut self, key: impl Into<String>) -> Option<Entry<'_, String, Value>> {
+        if let Value::Null = self {
+            *self = Value::Object(BTreeMap::new());
+        }
+        match self {
+            Value::Object(object) => Some(object.entry(key.into())),
+            _ => None,
+        }
+    }
+
+    /// オブジェクトに `key` と `value` を挿入し、元の値があれば返す
+    ///
+    /// `Value::Null` は空のオブジェクトにしてから挿入する。それ以外のオブジェクトでない値には
+    /// 挿入せず、`value` を `Err` で返す
+    pub fn insert(
+        &mut self,
+        key: impl Into<String>,
+        value: impl Into<Value>,
+    ) -> Result<Option<Value>, Value> {
+        match self.entry(key) {
+            Some(Entry::Occupied(mut entry)) => Ok(Some(entry.insert(value.into()))),
+            Some(Entry::Vacant(entry)) => {
+                entry.insert(value.into());
+                Ok(None)
+            }
+            None => Err(value.into()),
+        }
+    }
+
+    /// オブジェクトから `key` を取り除いて値を返す。ない場合やオブジェクトでない場合は None
+    pub fn remove(&mut self, key: &str) -> Option<Value> {
+        match self {
+            Value::Object(object) => object.remove(key),
+            _ => None,
+        }
+    }
+
+    /// 配列の末尾に `value` を加える
+    ///
+    /// `Value::Null` は空の配列にしてから加える。それ以外の配列でない値には加えず、`value` を
+    /// `Err` で返す
+    pub fn push(&mut self, value: impl Into<Value>) -> Result<(), Value> {
+        if let Value::Null = self {
+            *self = Value::Array(vec![]);
+        }
+        match self {
+            Value::Array(array) => {
+                array.push(value.into());
+                Ok(())
+            }
+            _ => Err(value.into()),
+        }
+    }
+
+    /// 配列の末尾の要素を取り除いて返す。空の場合や配列でない場合は None
+    pub fn pop(&mut self) -> Option<Value> {
+        match self {
+            Value::Array(array) => array.pop(),
+            _ => None,
+        }
+    }
+
+    /// 数値は差が `epsilon` 以内なら等しいとみなし、それ以外は厳密に比べる
+    ///
+    /// 整数と小数も数値として比べる。`==` と同じく NaN は NaN とだけ等しい
+    pub fn approx_eq(&self, other: &Value, epsilon: f64) -> bool {
+        match (self, other) {
+            (Value::Array(a), Value::Array(b)) => {
+                a.len() == b.len() && a.iter().zip(b).all(|(a, b)| a.approx_eq(b, epsilon))
+            }
+            (Value::Object(a), Value::Object(b)) => {
+                a.len() == b.len()
+                    && a.iter()
+                        .all(|(key, a)| b.get(key).is_some_and(|b| a.approx_eq(b, epsilon)))
+            }
+            (Value::Number(a), Value::Number(b)) => {
+                a == b || (a.as_f64() - b.as_f64()).abs() <= epsilon
+            }
+            (a, b) => a == b,
+        }
+    }
+
+    /// 配列の要素を順に返す。配列でなければ何も返さない
+    pub fn iter(&self) -> impl Iterator<Item = &Value> + '_ {
+        self.as_array().into_iter().flatten()
+    }
+
+    pub fn iter_mut(&mut self) -> impl Iterator<Item = &mut Value> + '_ {
+        let array = match self {
+            Value::Array(array) => Some(array),
+            _ => None,
+        };
+        array.into_iter().flatten()
+    }
+
+    /// オブジェクトのキーと値の組をキーの順に返す。オブジェクトでなければ何も返さない
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Value)> + '_ {
+        self.as_object().into_iter().flatten()
+    }
+
+    pub fn entries_mut(&mut self) -> impl Iterator<Item = (&String, &mut Value)> + '_ {
+        let object = match self {
+            Value::Object(object) => Some(object),
+            _ => None,
+        };
+        object.into_iter().flatten()
+    }
+
+    /// 値を取り出し、代わりに `Value::Null` を残す
+    pub fn take(&mut self) -> Value {
+        std::mem::take(self)
+    }
+
+    /// 値を `value` に置き換え、元の値を返す
+    pub fn replace(&mut self, value: Value) -> Value {
+        std::mem::replace(self, value)
+    }
+}
+
+/// `Value::get` に渡せる添字 (オブジェクトのキー `str` と配列の添字 `usize`)
+pub trait ValueIndex {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value>;
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value>;
+}
+
+impl ValueIndex for str {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value {
+            Value::Object(object) => object.get(self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        match value {
+            Value::Object(object) => object.get_mut(self),
+            _ => None,
+        }
+    }
+}
+
+impl ValueIndex for String {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        self.as_str().index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        self.as_str().index_into_mut(value)
+    }
+}
+
+impl ValueIndex for usize {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        match value {
+            Value::Array(array) => array.get(*self),
+            _ => None,
+        }
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        match value {
+            Value::Array(array) => array.get_mut(*self),
+            _ => None,
+        }
+    }
+}
+
+impl<T: ValueIndex + ?Sized> ValueIndex for &T {
+    fn index_into<'v>(&self, value: &'v Value) -> Option<&'v Value> {
+        (**self).index_into(value)
+    }
+
+    fn index_into_mut<'v>(&self, value: &'v mut Value) -> Option<&'v mut Value> {
+        (**self).index_into_mut(value)
+    }
+}
+
+impl From<&str> for Value {
+    fn from(s: &str) -> Value {
+        Value::String(s.to_string())
+    }
+}
+
+impl From<String> for Value {
+    fn from(s: String) -> Value {
+        Value::String(s)
+    }
+}
+
+impl From<Number> for Value {
+    fn from(n: Number) -> Value {
+        Value::Number(n)
+    }
+}
+
+impl From<f64> for Value {
+    fn from(n: f64) -> Value {
+        Value::Number(n.into())
+    }
+}
+
+impl From<i64> for Value {
+    fn from(n: i64) -> Value {
+        Value::Number(n.into())
+    }
+}
+
+impl From<u64> for Value {
+    fn from(n: u64) -> Value {
+        Value::Number(n.into())
+    }
+}
+
+/// 整数リテラル (`i32`) からも作れるようにする
+impl From<i32> for Value {
+    fn from(n: i32) -> Value {
+        Value::Number(n.into())
+    }
+}
+
+impl From<u32> for Value {
+    fn from(n: u32) -> Value {
+        Value::Number(n.into())
+    }
+}
+
+impl From<bool> for Value {
+    fn from(b: bool) -> Value {
+        Value::Bool(b)
+    }
+}
+
+impl<T: Into<Value>> From<Vec<T>> for Value {
+    fn from(array: Vec<T>) -> Value {
+        Value::Array(array.into_iter().map(Into::into).collect())
+    }
+}
+
+impl From<BTreeMap<String, Value>> for Value {
+    fn from(object: BTreeMap<String, Value>) -> Value {
+        Value::Object(object)
+    }
+}
+
+impl<V: Into<Value>> From<HashMap<String, V>> for Value {
+    fn from(object: HashMap<String, V>) -> Value {
+        Value::Object(object.into_iter().map(|(k, v)| (k, v.into())).collect())
+    }
+}
+
+/// `None` は `Value::Null` にする
+impl<T: Into<Value>> From<Option<T>> for Value {
+    fn from(option: Option<T>) -> Value {
+        option.map_or(Value::Null, Into::into)
+    }
+}
+
+/// `Value` の列を配列にする
+impl FromIterator<Value> for Value {
+    fn from_iter<I: IntoIterator<Item = Value>>(iter: I) -> Value {
+        Value::Array(iter.into_iter().collect())
+    }
+}
+
+/// キーと値の組の列をオブジェクトにする。同じキーは後のものが残る
+impl FromIterator<(String, Value)> for Value {
+    fn from_iter<I: IntoIterator<Item = (String, Value)>>(iter: I) -> Value {
+        Value::Object(iter.into_iter().collect())
+    }
+}
+
+/// `Value` と Rust の値を `value == "alice"` のように両方向から比べられるようにする
+macro_rules! impl_partial_eq {
+    ($($ty:ty => |$v:ident, $other:ident| $eq:expr;)*) => {
+        $(
+            impl PartialEq<$ty> for Value {
+                fn eq(&self, other: &$ty) -> bool {
+                    let ($v, $other) = (self, other);
+                    $eq
+                }
+            }
+
+            impl PartialEq<Value> for $ty {
+                fn eq(&self, other: &Value) -> bool {
+                    other == self
+                }
+            }
+        )*
+    };
+}
+
+impl_partial_eq! {
+    str => |v, other| v.as_str() == Some(other);
+    &str => |v, other| v.as_str() == Some(*other);
+    String => |v, other| v.as_str() == Some(other.as_str());
+    f64 => |v, other| v.as_number() == Some(&Number::from(*other));
+    i64 => |v, other| v.as_number() == Some(&Number::from(*other));
+    u64 => |v, other| v.as_number() == Some(&Number::from(*other));
+    i32 => |v, other| v.as_number() == Some(&Number::from(*other));
+    bool => |v, other| v.as_bool() == Some(*other);
+}
+
+/// 空白のない JSON を書き出す。`{:#}` では 2 文字の字下げで整形する
+impl fmt::Display for Value {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let options = if f.alternate() {
+            PrinterOptions::pretty()
+        } else {
+            PrinterOptions::default()
+        };
+        fmt_value(f, self, &options)
+    }
+}
+
+/// `text.parse::<Value>()` で JSON を解析する。値の後に余分な token があればエラー
+impl FromStr for Value {
+    type Err = JsonError;
+
+    fn from_str(s: &str) -> Result<Value, JsonError> {
+        Ok(Parser::new(Lexer::new(s).tokenize()?).parse_document()?)
+    }
+}
+
+static NULL: Value = Value::Null;
+
+/// `value["key"]` でオブジェクトの値を返す。キーがない場合やオブジェクトでない場合は `Value::Null`
+impl Index<&str> for Value {
+    type Output = Value;
+
+    fn index(&self, key: &str) -> &Value {
+        self.get(key).unwrap_or(&NULL)
+    }
+}
+
+/// `value[0]` で配列の要素を返す。範囲外の場合や配列でない場合は `Value::Null`
+impl Index<usize> for Value {
+    type Output = Value;
+
+    fn index(&self, index: usize) -> &Value {
+        self.get(index).unwrap_or(&NULL)
+    }
+}
+
+/// `value["key"] = ...` でオブジェクトの値を書き換える
+///
+/// キーがなければ `Value::Null` を挿入し、`Value::Null` 自身は空のオブジェクトにしてから挿入する。
+/// オブジェクトでも `Value::Null` でもない場合は panic する
+impl IndexMut<&str> for Value {
+    fn index_mut(&mut self, key: &str) -> &mut Value {
+        if let Value::Null = self {
+            *self = Value::Object(BTreeMap::new());
+        }
+        match self {
+            Value::Object(object) => object.entry(key.to_string()).or_insert(Value::Null),
+            _ => panic!("cannot index a non-object JSON value with \"{}\"", key),
+        }
+    }
+}
+
+/// `value[0] = ...` で配列の要素を書き換える。範囲外の場合や配列でない場合は panic する
+impl IndexMut<usize> for Value {
+    fn index_mut(&mut self, index: usize) -> &mut Value {
+        match self {
+            Value::Array(array) => {
+                let len = array.len();
+                array.get_mut(index).unwrap_or_else(|| {
+                    panic!(
+                        "index {} is out of range for a JSON array of length {}",
+                        index, len
+                    )
+                })
+            }
+            _ => panic!("cannot index a non-array JSON value with {}", index),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_index() {
+        let value = parse(r#"{"config": {"ports": [80, 443], "name": "web"}}"#);
+        assert_eq!(value["config"]["ports"][1], Value::from(443));
+        assert_eq!(value["config"]["name"], Value::String("web".to_string()));
+        assert_eq!(value["config"]["missing"]["deeper"], Value::Null);
+        assert_eq!(value["config"]["ports"][2], Value::Null);
+        assert_eq!(value["config"]["name"][0], Value::Null);
+    }
+
+    #[test]
+    fn test_index_mut() {
+        let mut value = Value::Null;
+        value["config"]["name"] = Value::String("web".to_string());
+        value["config"]["ports"] = Value::Array(vec![Value::from(80)]);
+        value["config"]["ports"][0] = Value::from(8080);
+        assert_eq!(
+            value,
+            parse(r#"{"config": {"name": "web", "ports": [8080]}}"#)
+        );
+    }
+
+    #[test]
+    fn test_get() {
+        let mut value = parse(r#"{"user": {"name": "alice", "tags": ["a", "b"]}}"#);
+        let name = || value.get("user")?.get("name");
+        assert_eq!(name(), Some(&Value::String("alice".to_string())));
+        assert_eq!(value.get("user").and_then(|u| u.get("age")), None);
+        assert_eq!(value.get("user").and_then(|u| u.get(0)), None);
+        assert_eq!(
+            value["user"]["tags"].get(1),
+            Some(&Value::String("b".to_string()))
+        );
+        assert_eq!(value["user"]["tags"].get(2), None);
+        let key = "user".to_string();
+        assert!(value.get(&key).is_some());
+
+        *value.get_mut("user").unwrap().get_mut("name").unwrap() = Value::Null;
+        assert_eq!(value["user"]["name"], Value::Null);
+        assert!(value.get_mut("nobody").is_none());
+    }
+
+    #[test]
+    fn test_as() {
+        let value =
+            parse(r#"{"s": "x", "f": 1.5, "i": 2, "b": true, "a": [], "o": {}, "n": null}"#);
+        assert_eq!(value["s"].as_str(), Some("x"));
+        assert_eq!(value["f"].as_f64(), Some(1.5));
+        assert_eq!(value["i"].as_f64(), Some(2.0));
+        assert_eq!(value["i"].as_i64(), Some(2));
+        assert_eq!(value["f"].as_i64(), None);
+        assert_eq!(value["i"].as_u64(), Some(2));
+        assert!(value["i"].is_i64() && value["f"].is_f64() && !value["f"].is_u64());
+        assert_eq!(value["b"].as_bool(), Some(true));
+        assert_eq!(value["a"].as_array(), Some(&vec![]));
+        assert_eq!(value["o"].as_object(), Some(&BTreeMap::new()));
+        assert_eq!(value["n"].as_null(), Some(()));
+        assert_eq!(value["s"].as_null(), None);
+        assert_eq!(value["n"].as_str(), None);
+    }
+
+    #[test]
+    fn test_is() {
+        let value = parse(r#"[null, "x", 1, 1.5, false, [], {}]"#);
+        let kinds = |v: &Value| {
+            [
+                v.is_null(),
+                v.is_string(),
+                v.is_number(),
+                v.is_bool(),
+                v.is_array(),
+                v.is_object(),
+            ]
+        };
+        let expected = [
+            [true, false, false, false, false, false],
+            [false, true, false, false, false, false],
+            [false, false, true, false, false, false],
+            [false, false, true, false, false, false],
+            [false, false, false, true, false, false],
+            [false, false, false, false, true, false],
+            [false, false, false, false, false, true],
+        ];
+        for (i, expected) in expected.iter().enumerate() {
+            assert_eq!(&kinds(&value[i]), expected, "{}", i);
+        }
+    }
+
+    #[test]
+    fn test_take() {
+        let mut value = parse(r#"{"a": [1, 2], "b": true}"#);
+        let a = value["a"].take();
+        assert_eq!(a, parse("[1, 2]"));
+        assert_eq!(value, parse(r#"{"a": null, "b": true}"#));
+
+        let b = value["b"].replace(Value::from(3));
+        assert_eq!(b, Value::Bool(true));
+        assert_eq!(value, parse(r#"{"a": null, "b": 3}"#));
+    }
+
+    #[test]
+    fn test_from() {
+        assert_eq!(Value::from("a"), Value::String("a".to_string()));
+        assert_eq!(Value::from("a".to_string()), Value::String("a".to_string()));
+        assert_eq!(Value::from(1.5), Value::from(1.5));
+        assert_eq!(Value::from(2i64), Value::Number(Number::from(2)));
+        assert_eq!(Value::from(u64::MAX).as_u64(), Some(u64::MAX));
+        assert_eq!(Value::from(true), Value::Bool(true));
+        assert_eq!(Value::from(vec![1, 2]), parse("[1, 2]"));
+        assert_eq!(Value::from(vec![Value::Null]), parse("[null]"));
+        assert_eq!(Value::from(Some("a")), Value::from("a"));
+        assert_eq!(Value::from(None::<bool>), Value::Null);
+
+        let mut map = HashMap::new();
+        map.insert("a".to_string(), vec![true]);
+        assert_eq!(Value::from(map), parse(r#"{"a": [true]}"#));
+        assert_eq!(Value::from(BTreeMap::new()), parse("{}"));
+    }
+
+    #[test]
+    fn test_from_iter() {
+        let array = (1..=3).map(Value::from).collect::<Value>();
+        assert_eq!(array, parse("[1, 2, 3]"));
+
+        let object = ["a", "b", "a"]
+            .iter()
+            .enumerate()
+            .map(|(i, k)| (k.to_string(), Value::from(i as i64)))
+            .collect::<Value>();
+        assert_eq!(object, parse(r#"{"a": 2, "b": 1}"#));
+    }
+
+    #[test]
+    fn test_eq_primitive() {
+        let value = parse(r#"{"name": "alice", "age": 30, "score": 1.5, "admin": false}"#);
+        assert!(value["name"] == "alice");
+        assert!("alice" == value["name"]);
+        let name = String::from("alice");
+        assert!(value["name"] == name);
+        assert!(value["name"] != "bob");
+        assert!(value["age"] == 30);
+        assert!(value["age"] == 30i64);
+        assert!(value["age"] == 30.0);
+        assert!(value["score"] == 1.5);
+        assert!(value["score"] != 1);
+        assert!(value["age"] == 30u64);
+        assert!(value["admin"] == false);
+        assert!(value["missing"] != "alice");
+    }
+
+    #[test]
+    fn test_display() {
+        let value = parse(r#"{"a": [1, 2.5, "x\"y"], "b": {}, "c": [], "d": {"e": null}}"#);
+        assert_eq!(
+            value.to_string(),
+            r#"{"a":[1,2.5,"x\"y"],"b":{},"c":[],"d":{"e":null}}"#
+        );
+        assert_eq!(
+            format!("{:#}", value),
+            r#"{
+  "a": [
+    1,
+    2.5,
+    "x\"y"
+  ],
+  "b": {},
+  "c": [],
+  "d": {
+    "e": null
+  }
+}"#
+        );
+        assert_eq!(parse(&value.to_string()), value);
+        assert_eq!(Value::from(f64::NAN).to_string(), "null");
+    }
+
+    #[test]
+    fn test_from_str() {
+        let value: Value = r#"{"a": [1, true]}"#.parse().unwrap();
+        assert_eq!(value, parse(r#"{"a": [1, true]}"#));
+        assert!(matches!("[1,".parse::<Value>(), Err(JsonError::Parser(_))));
+        assert!(matches!(
+            "[1] 2".parse::<Value>(),
+            Err(JsonError::Parser(_))
+        ));
+        assert!(matches!("[x]".parse::<Value>(), Err(JsonError::Lexer(_))));
+    }
+
+    #[test]
+    fn test_default() {
+        #[derive(Default)]
+        struct Config {
+            value: Value,
+        }
+        assert_eq!(Config::default().value, Value::Null);
+        assert_eq!(Value::default(), Value::Null);
+    }
+
+    #[test]
+    fn test_entry() {
+        let mut counts = Value::Null;
+        for word in ["a", "b", "a"] {
+            counts
+                .entry(word)
+                .unwrap()
+                .and_modify(|n| *n = Value::from(n.as_i64().unwrap_or(0) + 1))
+                .or_insert(Value::from(1));
+        }
+        assert_eq!(counts, parse(r#"{"a": 2, "b": 1}"#));
+
+        let mut value = Value::Null;
+        let users = value
+            .entry("users")
+            .unwrap()
+            .or_insert_with(|| Value::array().build());
+        users.replace(Value::from(vec!["alice"]));
+        assert_eq!(value, parse(r#"{"users": ["alice"]}"#));
+        assert!(Value::from(1).entry("a").is_none());
+    }
+
+    #[test]
+    fn test_mutation() {
+        let mut value = Value::Null;
+        assert_eq!(value.insert("a", 1), Ok(None));
+        assert_eq!(value.insert("a", 2), Ok(Some(Value::from(1))));
+        assert_eq!(value.insert("b", "x"), Ok(None));
+        assert_eq!(value.remove("b"), Some(Value::from("x")));
+        assert_eq!(value.remove("b"), None);
+        assert_eq!(value, parse(r#"{"a": 2}"#));
+        assert_eq!(value.push(1), Err(Value::from(1)));
+        assert_eq!(value.pop(), None);
+
+        let mut value = Value::Null;
+        assert_eq!(value.push(1), Ok(()));
+        assert_eq!(value.push(true), Ok(()));
+        assert_eq!(value.pop(), Some(Value::from(true)));
+        assert_eq!(value, parse("[1]"));
+        assert_eq!(value.insert("a", 1), Err(Value::from(1)));
+        assert_eq!(value.remove("a"), None);
+    }
+
+    #[test]
+    fn test_approx_eq() {
+        let a = parse(r#"{"lat": 35.681236, "lng": [139.767125, 1], "name": "tokyo"}"#);
+        let b = parse(r#"{"lat": 35.68123600000001, "lng": [139.767125, 1.0], "name": "tokyo"}"#);
+        assert!(a != b);
+        assert!(a.approx_eq(&b, 1e-9));
+        assert!(!a.approx_eq(&b, 0.0));
+        assert!(!a.approx_eq(&parse(r#"{"lat": 35.68, "lng": [139.767125, 1]}"#), 1e-9));
+        assert!(!a.approx_eq(
+            &parse(r#"{"lat": 35.681236, "lng": [139.767125, 1], "name": "x"}"#),
+            1.0
+        ));
+        assert!(Value::from(f64::INFINITY).approx_eq(&Value::from(f64::INFINITY), 1e-9));
+        assert!(Value::from(f64::NAN).approx_eq(&Value::from(f64::NAN), 1e-9));
+        assert!(!Value::from(f64::NAN).approx_eq(&Value::from(1), 1e-9));
+        assert!(!Value::from(1).approx_eq(&Value::from("1"), 1.0));
+    }
+
+    #[test]
+    fn test_iter() {
+        let mut value = parse(r#"{"a": [1, 2], "b": {"x": true, "y": false}}"#);
+        assert_eq!(
+            value["a"].iter().collect::<Vec<_>>(),
+            [&Value::from(1), &Value::from(2)]
+        );
+        assert_eq!(value["b"].iter().count(), 0);
+        let keys = value["b"]
+            .entries()
+            .map(|(k, _)| k.as_str())
+            .collect::<Vec<_>>();
+        assert_eq!(keys, ["x", "y"]);
+        assert_eq!(value["a"].entries().count(), 0);
+        assert_eq!(Value::Null.iter().count(), 0);
+
+        for n in value["a"].iter_mut() {
+            *n = Value::from(n.as_i64().unwrap() * 10);
+        }
+        for (_, b) in value["b"].entries_mut() {
+            *b = Value::from(!b.as_bool().unwrap());
+        }
+        assert_eq!(
+            value,
+            parse(r#"{"a": [10, 20], "b": {"x": false, "y": true}}"#)
+        );
+    }
+
+    #[test]
+    #[should_panic]
+    fn test_index_mut_out_of_range() {
+        let mut value = parse("[1]");
+        value[1] = Value::Null;
+    }
+
+    #[test]
+    fn test_hash_set() {
+        use std::collections::HashSet;
+
+        let values: HashSet<Value> = [
+            parse(r#"{"a": [1, 2.0]}"#),
+            parse(r#"{"a": [1.0, 2]}"#),
+            Value::from(f64::NAN),
+            Value::from(f64::NAN),
+            Value::from(-0.0),
+            Value::from(0),
+            Value::from("0"),
+        ]
+        .into_iter()
+        .collect();
+        assert_eq!(values.len(), 4);
+    }
+}
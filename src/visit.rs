@@ -0,0 +1,198 @@
+use std::collections::BTreeMap;
+
+use crate::{pointer::JsonPointer, value::Value};
+
+/// `Value::accept` で木を辿るときに各節点で呼ばれる
+///
+/// コンテナは子より先に呼ばれる (行きがけ順)。何もしない既定の実装があるので、必要なものだけ実装する
+pub trait Visitor {
+    fn visit_object(&mut self, _pointer: &JsonPointer, _object: &BTreeMap<String, Value>) {}
+    fn visit_array(&mut self, _pointer: &JsonPointer, _array: &[Value]) {}
+    /// 文字列、数値、真偽値、null、未解析の値
+    fn visit_scalar(&mut self, _pointer: &JsonPointer, _value: &Value) {}
+}
+
+/// `VisitorMut` が節点ごとに返す、その後の扱い
+#[derive(Debug, Clone, PartialEq)]
+pub enum Action {
+    Continue,       // そのまま子を辿る
+    Skip,           // 子を辿らない
+    Replace(Value), // 節点を置き換える (置き換えた値の子は辿らない)
+    Delete,         // 親から取り除く (根の場合は null にする)
+}
+
+/// `Value::accept_mut` で木を書き換えながら辿るときに各節点で呼ばれる
+pub trait VisitorMut {
+    fn visit_object(
+        &mut self,
+        _pointer: &JsonPointer,
+        _object: &mut BTreeMap<String, Value>,
+    ) -> Action {
+        Action::Continue
+    }
+
+    fn visit_array(&mut self, _pointer: &JsonPointer, _array: &mut Vec<Value>) -> Action {
+        Action::Continue
+    }
+
+    fn visit_scalar(&mut self, _pointer: &JsonPointer, _value: &mut Value) -> Action {
+        Action::Continue
+    }
+}
+
+impl Value {
+    /// `visitor` で全節点を行きがけ順に辿る
+    pub fn accept<V: Visitor + ?Sized>(&self, visitor: &mut V) {
+        accept(self, &mut JsonPointer::root(), visitor);
+    }
+
+    /// `visitor` で全節点を行きがけ順に辿り、返された `Action` に従って書き換える
+    pub fn accept_mut<V: VisitorMut + ?Sized>(&mut self, visitor: &mut V) {
+        if !accept_mut(self, &mut JsonPointer::root(), visitor) {
+            *self = Value::Null;
+        }
+    }
+}
+
+fn accept<V: Visitor + ?Sized>(value: &Value, pointer: &mut JsonPointer, visitor: &mut V) {
+    match value {
+        Value::Object(object) => {
+            visitor.visit_object(pointer, object);
+            for (key, value) in object {
+                pointer.push(key.clone());
+                accept(value, pointer, visitor);
+                pointer.pop();
+            }
+        }
+        Value::Array(array) => {
+            visitor.visit_array(pointer, array);
+            for (i, value) in array.iter().enumerate() {
+                pointer.push(i.to_string());
+                accept(value, pointer, visitor);
+                pointer.pop();
+            }
+        }
+        value => visitor.visit_scalar(pointer, value),
+    }
+}
+
+/// 節点を残す場合は true、取り除く場合は false を返す
+fn accept_mut<V: VisitorMut + ?Sized>(
+    value: &mut Value,
+    pointer: &mut JsonPointer,
+    visitor: &mut V,
+) -> bool {
+    let action = match &mut *value {
+        Value::Object(object) => visitor.visit_object(pointer, object),
+        Value::Array(array) => visitor.visit_array(pointer, array),
+        scalar => visitor.visit_scalar(pointer, scalar),
+    };
+    match action {
+        Action::Continue => (),
+        Action::Skip => return true,
+        Action::Replace(new) => {
+            *value = new;
+            return true;
+        }
+        Action::Delete => return false,
+    }
+    match value {
+        Value::Object(object) => {
+            object.retain(|key, value| {
+                pointer.push(key.clone());
+                let keep = accept_mut(value, pointer, visitor);
+                pointer.pop();
+                keep
+            });
+        }
+        Value::Array(array) => {
+            // 取り除いた要素の後ろは添字が詰まる
+            let mut i = 0;
+            array.retain_mut(|value| {
+                pointer.push(i.to_string());
+                let keep = accept_mut(value, pointer, visitor);
+                pointer.pop();
+                if keep {
+                    i += 1;
+                }
+                keep
+            });
+        }
+        _ => (),
+    }
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_accept() {
+        #[derive(Default)]
+        struct Collect(Vec<String>);
+        impl Visitor for Collect {
+            fn visit_object(&mut self, pointer: &JsonPointer, object: &BTreeMap<String, Value>) {
+                self.0.push(format!("object {} {}", pointer, object.len()));
+            }
+            fn visit_array(&mut self, pointer: &JsonPointer, array: &[Value]) {
+                self.0.push(format!("array {} {}", pointer, array.len()));
+            }
+            fn visit_scalar(&mut self, pointer: &JsonPointer, value: &Value) {
+                self.0.push(format!("scalar {} {}", pointer, value));
+            }
+        }
+        let mut visitor = Collect::default();
+        parse(r#"{"a": [1, "x"], "b": null}"#).accept(&mut visitor);
+        assert_eq!(
+            visitor.0,
+            [
+                "object  2",
+                "array /a 2",
+                "scalar /a/0 1",
+                r#"scalar /a/1 "x""#,
+                "scalar /b null",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_accept_mut() {
+        // 文字列を大文字にし、null を取り除き、"secret" の中は辿らずに置き換える
+        struct Transform(Vec<String>);
+        impl VisitorMut for Transform {
+            fn visit_object(
+                &mut self,
+                pointer: &JsonPointer,
+                _object: &mut BTreeMap<String, Value>,
+            ) -> Action {
+                if pointer.tokens().last().is_some_and(|t| t == "secret") {
+                    Action::Replace(Value::from("***"))
+                } else {
+                    Action::Continue
+                }
+            }
+            fn visit_scalar(&mut self, pointer: &JsonPointer, value: &mut Value) -> Action {
+                self.0.push(pointer.to_string());
+                match value {
+                    Value::Null => Action::Delete,
+                    Value::String(s) => {
+                        *s = s.to_uppercase();
+                        Action::Continue
+                    }
+                    _ => Action::Continue,
+                }
+            }
+        }
+        let mut value = parse(r#"{"a": [null, "x", null, "y"], "b": null, "secret": {"k": "v"}}"#);
+        let mut visitor = Transform(vec![]);
+        value.accept_mut(&mut visitor);
+        assert_eq!(value, parse(r#"{"a": ["X", "Y"], "secret": "***"}"#));
+        assert_eq!(visitor.0, ["/a/0", "/a/0", "/a/1", "/a/1", "/b"]);
+
+        let mut value = Value::Null;
+        value.accept_mut(&mut Transform(vec![]));
+        assert_eq!(value, Value::Null);
+    }
+}
@@ -0,0 +1,111 @@
+use crate::{pointer::JsonPointer, value::Value};
+
+/// `Value::walk` の返す、すべての節点を行きがけ順に辿る iterator
+pub struct Walk<'v> {
+    stack: Vec<(JsonPointer, &'v Value)>,
+}
+
+impl<'v> Iterator for Walk<'v> {
+    type Item = (JsonPointer, &'v Value);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let (pointer, value) = self.stack.pop()?;
+        // 先に返す子が stack の上に来るよう、逆順に積む
+        let child = |token: String| {
+            let mut pointer = pointer.clone();
+            pointer.push(token);
+            pointer
+        };
+        match value {
+            Value::Array(array) => self.stack.extend(
+                array
+                    .iter()
+                    .enumerate()
+                    .rev()
+                    .map(|(i, value)| (child(i.to_string()), value)),
+            ),
+            Value::Object(object) => self.stack.extend(
+                object
+                    .iter()
+                    .rev()
+                    .map(|(key, value)| (child(key.clone()), value)),
+            ),
+            _ => (),
+        }
+        Some((pointer, value))
+    }
+}
+
+impl Value {
+    /// 自身を含むすべての節点を、その JSON Pointer と共に行きがけ順に返す
+    pub fn walk(&self) -> Walk<'_> {
+        Walk {
+            stack: vec![(JsonPointer::root(), self)],
+        }
+    }
+
+    /// `predicate` が true を返すすべての節点を、その JSON Pointer と共に行きがけ順に返す
+    pub fn find_all<F>(&self, mut predicate: F) -> Vec<(JsonPointer, &Value)>
+    where
+        F: FnMut(&JsonPointer, &Value) -> bool,
+    {
+        self.walk()
+            .filter(|(pointer, value)| predicate(pointer, value))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_walk() {
+        let value: Value = r#"{"a": [1, {"b": null}], "c/d": "x"}"#.parse().unwrap();
+        let nodes = value
+            .walk()
+            .map(|(pointer, value)| (pointer.to_string(), value.to_string()))
+            .collect::<Vec<_>>();
+        let expected = [
+            ("", r#"{"a":[1,{"b":null}],"c/d":"x"}"#),
+            ("/a", r#"[1,{"b":null}]"#),
+            ("/a/0", "1"),
+            ("/a/1", r#"{"b":null}"#),
+            ("/a/1/b", "null"),
+            ("/c~1d", r#""x""#),
+        ];
+        let expected = expected
+            .iter()
+            .map(|(p, v)| (p.to_string(), v.to_string()))
+            .collect::<Vec<_>>();
+        assert_eq!(nodes, expected);
+
+        // 検索も 1 行で書ける
+        let nulls = value.walk().filter(|(_, v)| v.is_null()).count();
+        assert_eq!(nulls, 1);
+        for (pointer, node) in value.walk() {
+            assert_eq!(value.pointer(&pointer), Some(node));
+        }
+    }
+
+    #[test]
+    fn test_find_all() {
+        let value: Value =
+            r#"{"cards": ["4111111111111111", "n/a"], "note": "4012888888881881", "n": 4111}"#
+                .parse()
+                .unwrap();
+        let looks_like_card = |_: &JsonPointer, v: &Value| {
+            v.as_str()
+                .is_some_and(|s| s.len() == 16 && s.bytes().all(|b| b.is_ascii_digit()))
+        };
+        let found = value
+            .find_all(looks_like_card)
+            .into_iter()
+            .map(|(pointer, _)| pointer.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(found, ["/cards/0", "/note"]);
+
+        let under_cards = value.find_all(|p, _| p.tokens().first().is_some_and(|t| t == "cards"));
+        assert_eq!(under_cards.len(), 3);
+    }
+}
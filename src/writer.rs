@@ -0,0 +1,212 @@
+use std::{collections::BTreeMap, fmt, io};
+
+use crate::{
+    formatter::{write_with, CompactFormatter, Formatter},
+    printer::IoAdapter,
+    value::Value,
+};
+
+/// `Value` の木を作らずに、開始や終了を順に伝えて JSON を少しずつ書き出す
+///
+/// `begin_object` → `key` → `value` … → `end` のように呼ぶ。入れ子の誤り (オブジェクトの外の
+/// `key`、キーのない値、閉じていないコンテナなど) は `io::ErrorKind::InvalidInput` のエラーにする。
+/// `W` の渡し方は [`write_value`](crate::printer::write_value) と同じ
+pub struct JsonWriter<W: io::Write, F: Formatter = CompactFormatter> {
+    inner: W,
+    formatter: F,
+    stack: Vec<Frame>,
+    done: bool, // 根の値を書き終えた
+}
+
+/// 開いているコンテナ
+enum Frame {
+    Array { first: bool },
+    Object { first: bool, has_key: bool },
+}
+
+impl<W: io::Write> JsonWriter<W> {
+    /// 空白のない JSON を書く
+    pub fn new(inner: W) -> JsonWriter<W> {
+        Self::with_formatter(inner, CompactFormatter)
+    }
+}
+
+impl<W: io::Write, F: Formatter> JsonWriter<W, F> {
+    /// `formatter` の体裁で書く。`begin_array` や `begin_object` のフックには先読みできる要素がないので空を渡す
+    pub fn with_formatter(inner: W, formatter: F) -> JsonWriter<W, F> {
+        JsonWriter {
+            inner,
+            formatter,
+            stack: vec![],
+            done: false,
+        }
+    }
+
+    pub fn begin_object(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.emit(|w, f| f.begin_object(w, &BTreeMap::new()))?;
+        self.stack.push(Frame::Object {
+            first: true,
+            has_key: false,
+        });
+        Ok(())
+    }
+
+    pub fn begin_array(&mut self) -> io::Result<()> {
+        self.before_value()?;
+        self.emit(|w, f| f.begin_array(w, &[]))?;
+        self.stack.push(Frame::Array { first: true });
+        Ok(())
+    }
+
+    /// オブジェクトのキーを書く。次に値かコンテナを書く
+    pub fn key(&mut self, key: &str) -> io::Result<()> {
+        let first = match self.stack.last_mut() {
+            Some(Frame::Object { first, has_key }) if !*has_key => {
+                *has_key = true;
+                *first
+            }
+            Some(Frame::Object { .. }) => {
+                return Err(invalid("error: expected a value after a key"))
+            }
+            _ => return Err(invalid("error: a key must be inside an object")),
+        };
+        self.emit(|w, f| {
+            f.begin_object_key(w, first)?;
+            f.write_key(w, key)?;
+            f.begin_object_value(w)
+        })
+    }
+
+    /// 値 (コンテナでもよい) を 1 つ書く
+    pub fn value(&mut self, value: &Value) -> io::Result<()> {
+        self.before_value()?;
+        self.emit(|w, f| write_with(w, value, f))?;
+        self.after_value()
+    }
+
+    /// いちばん内側のコンテナを閉じる
+    pub fn end(&mut self) -> io::Result<()> {
+        match self.stack.last() {
+            Some(Frame::Array { .. }) => self.emit(|w, f| f.end_array(w))?,
+            Some(Frame::Object { has_key: false, .. }) => self.emit(|w, f| f.end_object(w))?,
+            Some(Frame::Object { has_key: true, .. }) => {
+                return Err(invalid("error: a key has no value"))
+            }
+            None => return Err(invalid("error: no container to end")),
+        }
+        self.stack.pop();
+        self.after_value()
+    }
+
+    /// 書き終えたかを確かめて `flush` し、中の `W` を返す
+    pub fn finish(mut self) -> io::Result<W> {
+        if !self.stack.is_empty() {
+            return Err(invalid("error: a container is not ended"));
+        }
+        if !self.done {
+            return Err(invalid("error: no value has been written"));
+        }
+        self.inner.flush()?;
+        Ok(self.inner)
+    }
+
+    fn before_value(&mut self) -> io::Result<()> {
+        let first = match self.stack.last() {
+            None if self.done => return Err(invalid("error: only one root value can be written")),
+            None => return Ok(()),
+            Some(Frame::Object { has_key: true, .. }) => return Ok(()),
+            Some(Frame::Object { .. }) => return Err(invalid("error: expected a key")),
+            Some(Frame::Array { first }) => *first,
+        };
+        self.emit(|w, f| f.begin_array_value(w, first))
+    }
+
+    fn after_value(&mut self) -> io::Result<()> {
+        match self.stack.last_mut() {
+            None => {
+                self.done = true;
+                Ok(())
+            }
+            Some(Frame::Array { first }) => {
+                *first = false;
+                self.emit(|w, f| f.end_array_value(w))
+            }
+            Some(Frame::Object { first, has_key }) => {
+                *first = false;
+                *has_key = false;
+                self.emit(|w, f| f.end_object_value(w))
+            }
+        }
+    }
+
+    /// `formatter` で `inner` に書き出す
+    fn emit(
+        &mut self,
+        write: impl FnOnce(&mut dyn fmt::Write, &mut F) -> fmt::Result,
+    ) -> io::Result<()> {
+        let mut adapter = IoAdapter::new(&mut self.inner);
+        write(&mut adapter, &mut self.formatter).map_err(|_| adapter.take_error())
+    }
+}
+
+fn invalid(msg: &str) -> io::Error {
+    io::Error::new(io::ErrorKind::InvalidInput, msg)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::formatter::PrettyFormatter;
+    use crate::test_util::parse;
+
+    #[test]
+    fn test_json_writer() {
+        let mut w = JsonWriter::new(vec![]);
+        w.begin_object().unwrap();
+        w.key("items").unwrap();
+        w.begin_array().unwrap();
+        for i in 0..3 {
+            w.value(&Value::from(i)).unwrap();
+        }
+        w.begin_object().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+        w.key("meta").unwrap();
+        w.value(&parse(r#"{"n": null}"#)).unwrap();
+        w.end().unwrap();
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        assert_eq!(out, r#"{"items":[0,1,2,{}],"meta":{"n":null}}"#);
+
+        let mut w = JsonWriter::with_formatter(vec![], PrettyFormatter::new());
+        w.begin_array().unwrap();
+        w.value(&Value::from("a")).unwrap();
+        w.begin_array().unwrap();
+        w.end().unwrap();
+        w.end().unwrap();
+        let out = String::from_utf8(w.finish().unwrap()).unwrap();
+        assert_eq!(out, "[\n  \"a\",\n  []\n]");
+    }
+
+    #[test]
+    fn test_json_writer_errors() {
+        let kind = |e: io::Error| e.kind();
+        let mut w = JsonWriter::new(vec![]);
+        assert_eq!(w.key("a").map_err(kind), Err(io::ErrorKind::InvalidInput));
+        assert!(w.end().is_err());
+        w.begin_object().unwrap();
+        assert!(w.value(&Value::Null).is_err());
+        w.key("a").unwrap();
+        assert!(w.key("b").is_err());
+        assert!(w.end().is_err());
+        w.value(&Value::Null).unwrap();
+        w.end().unwrap();
+        assert!(w.value(&Value::Null).is_err());
+        assert!(w.finish().is_ok());
+
+        let mut w = JsonWriter::new(vec![]);
+        w.begin_array().unwrap();
+        assert!(w.finish().is_err());
+        assert!(JsonWriter::new(vec![]).finish().is_err());
+    }
+}